@@ -1,4 +1,7 @@
 use crate::draw::*;
+use crate::filter::ProtocolFilter;
+use crate::source::{self, PacketSource, Poll, RawPacket};
+use crate::summary::{Column, Summary};
 use crate::table::*;
 use crossbeam::{
     self,
@@ -9,18 +12,22 @@ use crossbeam::{
 use datalink::Payload;
 use netparse::{
     layer2::{
-        datalink::{self, Frame},
-        ethernet,
-        wifi::{dot11, radiotap},
+        datalink::{self, CapturedPacket, Frame},
+        ethernet, loopback, ppp, sll, sll2,
+        wifi::{
+            decrypt::{Credentials, Decryptor},
+            defrag::Reassembler,
+            dot11, radiotap,
+        },
     },
     layer3::ip::{ip, ipv4, ipv6, tcp, udp},
 };
-use pcap::{self, Linktype};
+use pcap::Linktype;
 use std::{
     default::Default,
-    io::{self, stdin},
+    io::{self, stdin, Write},
     sync::{Arc, Mutex, MutexGuard},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use termion::{event::Key, input::TermRead};
 use tui::layout::Constraint;
@@ -31,6 +38,8 @@ pub enum Event {
     Paused,
     Selected,
     Disconnected,
+    /// The capture stopped at its count or duration limit; the table stays up until the user quits.
+    Finished,
 }
 
 pub struct Capture {
@@ -38,6 +47,13 @@ pub struct Capture {
     wireless: bool,
     monitor: bool,
     filter: String,
+    protocols: ProtocolFilter,
+    file: Option<String>,
+    headless: bool,
+    columns: Vec<Column>,
+    count: Option<usize>,
+    duration: Option<Duration>,
+    credentials: Vec<Credentials>,
 }
 
 impl Default for Capture {
@@ -57,6 +73,13 @@ impl Default for Capture {
             wireless: false,
             monitor: false,
             filter: String::new(),
+            protocols: ProtocolFilter::default(),
+            file: None,
+            headless: false,
+            columns: Column::defaults(),
+            count: None,
+            duration: None,
+            credentials: vec![],
         }
     }
 }
@@ -80,12 +103,42 @@ impl Capture {
         self
     }
 
-    #[allow(dead_code)]
     pub fn with_filter(&mut self, filter: String) -> &Self {
         self.filter = filter;
         self
     }
 
+    pub fn with_protocols(&mut self, protocols: ProtocolFilter) -> &Self {
+        self.protocols = protocols;
+        self
+    }
+
+    /// Reads packets from a pcap file instead of the interface.
+    pub fn with_file(&mut self, file: Option<String>) -> &Self {
+        self.file = file;
+        self
+    }
+
+    /// Prints one line per packet to stdout instead of starting the interactive interface.
+    pub fn with_headless(&mut self, headless: bool, columns: Vec<Column>) -> &Self {
+        self.headless = headless;
+        self.columns = columns;
+        self
+    }
+
+    /// Stops capturing after `count` packets or once `duration` has elapsed, whichever comes first.
+    pub fn with_limits(&mut self, count: Option<usize>, duration: Option<Duration>) -> &Self {
+        self.count = count;
+        self.duration = duration;
+        self
+    }
+
+    /// Decrypts the 802.11 data frames of the WPA/WPA2 personal networks these credentials belong to.
+    pub fn with_keys(&mut self, credentials: Vec<Credentials>) -> &Self {
+        self.credentials = credentials;
+        self
+    }
+
     fn add(
         table: &mut MutexGuard<StatefulTable>,
         field: String,
@@ -200,12 +253,15 @@ impl Capture {
 
     fn capture_payload(
         table: &mut MutexGuard<StatefulTable>,
-        payload: &Option<Payload>,
+        payload: Option<&Payload>,
         index: usize,
     ) {
         match payload {
             Some(Payload::IPv4(ref packet)) => Self::capture_ipv4_packet(table, packet, index),
             Some(Payload::IPv6(ref packet)) => Self::capture_ipv6_packet(table, packet, index),
+            Some(Payload::STP(_)) => {
+                Self::add(table, "STP".to_string(), "L3".to_string(), 5, index)
+            }
             _ => {}
         };
     }
@@ -233,6 +289,27 @@ impl Capture {
         }
     }
 
+    fn capture_radiotap(
+        table: &mut MutexGuard<StatefulTable>,
+        radiotap: &radiotap::RadioTapHeader,
+        index: usize,
+    ) {
+        let signal = radiotap
+            .signal_dbm()
+            .map_or_else(String::new, |s| format!("{} dBm", s));
+        let channel = radiotap.channel().map_or_else(String::new, |c| {
+            c.number()
+                .map_or_else(|| format!("{} MHz", c.freq), |n| n.to_string())
+        });
+        let rate = radiotap
+            .rate_mbps()
+            .map_or_else(String::new, |r| format!("{:.1} Mb/s", r));
+
+        Self::add(table, signal, "SIGNAL".to_string(), 6, index);
+        Self::add(table, channel, "CH".to_string(), 4, index);
+        Self::add(table, rate, "RATE".to_string(), 6, index);
+    }
+
     fn capture_dot11_frame(
         table: &mut MutexGuard<StatefulTable>,
         frame: &dot11::Frame,
@@ -248,89 +325,300 @@ impl Capture {
         if let Some(ref addr) = frame.addr4 {
             Self::capture_dot11_addr(table, &addr, index);
         }
+        let flags = if frame.duplicate {
+            "DUP"
+        } else if u8::from(frame.fc.flags.retry) == 1 {
+            "RETRY"
+        } else {
+            ""
+        };
+        Self::add(table, flags.to_string(), "FLAGS".to_string(), 5, index);
+    }
+
+    /// Adds the Time (since the first packet), Delta (since the previous packet) and Length columns.
+    fn capture_metadata(
+        table: &mut MutexGuard<StatefulTable>,
+        packet: &CapturedPacket,
+        index: usize,
+    ) {
+        let first = table
+            .packets
+            .first()
+            .map_or(packet.timestamp, |p| p.timestamp);
+        let previous = table
+            .packets
+            .last()
+            .map_or(packet.timestamp, |p| p.timestamp);
+        let time = packet.timestamp.checked_sub(first).unwrap_or_default();
+        let delta = packet.timestamp.checked_sub(previous).unwrap_or_default();
+        let len = if packet.is_truncated() {
+            format!("{}/{}", packet.caplen, packet.len)
+        } else {
+            packet.len.to_string()
+        };
+
+        Self::add(
+            table,
+            format!("{:.6}", time.as_secs_f64()),
+            "TIME".to_string(),
+            8,
+            index,
+        );
+        Self::add(
+            table,
+            format!("{:.6}", delta.as_secs_f64()),
+            "DELTA".to_string(),
+            8,
+            index,
+        );
+        Self::add(table, len, "LEN".to_string(), 5, index);
+    }
+
+    fn capture_ether_type(
+        table: &mut MutexGuard<StatefulTable>,
+        ether_type: &Option<datalink::EtherType>,
+        index: usize,
+    ) {
+        if let Some(ref ether_type) = ether_type {
+            Self::add(
+                table,
+                format!("{:?}", ether_type),
+                "L3".to_string(),
+                5,
+                index,
+            );
+        }
     }
 
-    fn capture_frame(table: &mut MutexGuard<StatefulTable>, frame: &Frame, index: usize) {
+    fn capture_frame(table: &mut MutexGuard<StatefulTable>, packet: &CapturedPacket, index: usize) {
         table.push(
             index.to_string(),
             "N".to_string(),
             Constraint::Percentage(5),
             index,
         );
-
-        match frame {
-            Frame::Ethernet(ref frame) => {
-                if let Some(ref ether_type) = frame.ether_type {
-                    Self::add(
-                        table,
-                        format!("{:?}", ether_type),
-                        "L3".to_string(),
-                        5,
-                        index,
-                    );
+        Self::capture_metadata(table, packet, index);
+
+        match packet.frame {
+            Frame::Ethernet(ref frame) => match frame.snap {
+                Some(ref snap) => Self::capture_ether_type(table, &snap.ether_type, index),
+                None => Self::capture_ether_type(table, &frame.ether_type, index),
+            },
+            Frame::LinuxSLL(ref frame) => Self::capture_ether_type(table, &frame.protocol, index),
+            Frame::LinuxSLL2(ref frame) => Self::capture_ether_type(table, &frame.protocol, index),
+            Frame::Dot11(ref frame) => {
+                if let Some(ref radiotap) = packet.radiotap {
+                    Self::capture_radiotap(table, radiotap, index);
                 }
+                Self::capture_dot11_frame(table, frame, index)
+            }
+            Frame::Raw(_) | Frame::Loopback(_) | Frame::PPP(_) => {}
+        };
+
+        Self::capture_payload(table, packet.frame.payload(), index);
+    }
+
+    fn parse_frame(link_type: Linktype, data: &[u8]) -> Option<Frame> {
+        match link_type {
+            Linktype(1) => ethernet::Frame::parse(data)
+                .ok()
+                .map(|(_, frame)| Frame::Ethernet(frame)),
+
+            // Without radiotap there is no way to tell whether the FCS was kept; drivers usually strip it.
+            Linktype(105) => dot11::Frame::parse(data, false)
+                .ok()
+                .map(|(_, frame)| Frame::Dot11(frame)),
+
+            Linktype(113) => sll::Frame::parse(data)
+                .ok()
+                .map(|(_, frame)| Frame::LinuxSLL(frame)),
+
+            Linktype(276) => sll2::Frame::parse(data)
+                .ok()
+                .map(|(_, frame)| Frame::LinuxSLL2(frame)),
+
+            Linktype(0) => loopback::Frame::parse(data)
+                .ok()
+                .map(|(_, frame)| Frame::Loopback(frame)),
 
-                Self::capture_payload(table, &frame.payload, index);
+            Linktype(108) => loopback::Frame::parse_loop(data)
+                .ok()
+                .map(|(_, frame)| Frame::Loopback(frame)),
+
+            Linktype(9) => ppp::Frame::parse(data)
+                .ok()
+                .map(|(_, frame)| Frame::PPP(frame)),
+
+            Linktype(101) => Payload::parse_ip(data)
+                .ok()
+                .map(|(_, payload)| Frame::Raw(payload)),
+
+            _ => None,
+        }
+    }
+
+    /// Decodes a packet, keeping the radiotap header of monitor mode captures alongside the 802.11 frame.
+    /// The protected data frames of 802.11 captures are decrypted when `decryptor` has their keys,
+    /// then go through `reassembler`.
+    fn decode(
+        link_type: Linktype,
+        packet: RawPacket,
+        decryptor: Option<&mut Decryptor>,
+        reassembler: &mut Reassembler,
+    ) -> Option<CapturedPacket> {
+        let (radiotap, mut frame, mpdu) = match link_type {
+            Linktype(127) => {
+                let (remaining, header) = radiotap::RadioTapHeader::parse(&packet.data).ok()?;
+                let (_, frame) = dot11::Frame::parse_radiotap(remaining, &header).ok()?;
+                (Some(header), Frame::Dot11(frame), remaining)
+            }
+            _ => (
+                None,
+                Self::parse_frame(link_type, &packet.data)?,
+                &packet.data[..],
+            ),
+        };
+        if let Frame::Dot11(ref mut frame) = frame {
+            if let Some(decryptor) = decryptor {
+                decryptor.process(frame, mpdu);
             }
+            reassembler.process(frame);
+        }
+        // libpcap does not report the interface of a packet, but the SLL2 header of "any" captures does.
+        let interface = match frame {
+            Frame::LinuxSLL2(ref frame) => frame.interface_index,
+            _ => packet.interface,
+        };
+
+        Some(CapturedPacket {
+            timestamp: packet.timestamp,
+            caplen: packet.caplen,
+            len: packet.len,
+            interface,
+            link_type: link_type.0,
+            radiotap,
+            frame,
+        })
+    }
+
+    /// Whether `parse_frame` knows how to decode packets of this link type.
+    fn supports(link_type: Linktype) -> bool {
+        matches!(
+            link_type,
+            Linktype(0)
+                | Linktype(1)
+                | Linktype(9)
+                | Linktype(101)
+                | Linktype(105)
+                | Linktype(108)
+                | Linktype(113)
+                | Linktype(127)
+                | Linktype(276)
+        )
+    }
+
+    /// Opens the savefile given with `-r`, or the interface otherwise, and applies the pcap filter.
+    fn open(&self) -> Result<Box<dyn PacketSource>, io::Error> {
+        let mut source = match self.file {
+            Some(ref file) => source::file(file)?,
+            None => source::live(self.interface.as_str(), self.monitor)?,
+        };
+        // The survey needs every management frame, so the protocol flags of a wireless capture are only
+        // applied after parsing.
+        let protocols = if self.wireless {
+            ProtocolFilter::default()
+        } else {
+            self.protocols.clone()
+        };
+        source.set_filter(protocols.to_bpf(&self.filter).as_str())?;
+        Ok(source)
+    }
 
-            Frame::Dot11(ref frame) => Self::capture_dot11_frame(table, frame, index),
+    /// Feeds every decoded packet to `handle`, with whether it passes the protocol flags, until `handle`
+    /// returns false, the source is exhausted or one of the limits is reached. Only the packets that pass
+    /// count towards the limit. Returns whether a limit was reached.
+    fn read_packets<F>(&self, source: &mut dyn PacketSource, mut handle: F) -> bool
+    where
+        F: FnMut(CapturedPacket, bool) -> bool,
+    {
+        let link_type = source.link_type();
+        let start = Instant::now();
+        let mut count = 0;
+        let mut decryptor = if self.credentials.is_empty() {
+            None
+        } else {
+            Some(Decryptor::new(&self.credentials))
         };
+        let mut reassembler = Reassembler::new();
+
+        loop {
+            if self.count.is_some_and(|max| count >= max)
+                || self.duration.is_some_and(|max| start.elapsed() >= max)
+            {
+                return true;
+            }
+
+            let packet = match source.next_packet() {
+                Poll::Packet(packet) => packet,
+                Poll::Timeout => continue,
+                Poll::Done => return false,
+            };
+
+            if let Some(packet) =
+                Self::decode(link_type, packet, decryptor.as_mut(), &mut reassembler)
+            {
+                let selected = self.protocols.matches(&packet.frame);
+                if selected {
+                    count += 1;
+                }
+                if !handle(packet, selected) {
+                    return false;
+                }
+            }
+        }
     }
 
-    fn capture_packets(&self, table: &Arc<Mutex<StatefulTable>>, receiver: &Receiver<Event>) {
-        let monitor = self.monitor;
-        let interface = self.interface.clone();
-        let filters = self.filter.clone();
-        let mut index = 0;
-        let mut cap = pcap::Capture::from_device(interface.as_str())
-            .expect("There was a problem selecting the given interface.")
-            .promisc(true)
-            .rfmon(monitor)
-            .buffer_size(512)
-            .open()
-            .expect("There was a problem capturing on that interface.");
-        cap.filter(filters.as_str())
-            .expect("Invalid filter provided");
-
-        let link_type = cap.get_datalink();
-
-        while let Ok(packet) = cap.next() {
+    fn capture_packets(
+        &self,
+        source: &mut dyn PacketSource,
+        table: &Arc<Mutex<StatefulTable>>,
+        receiver: &Receiver<Event>,
+    ) -> bool {
+        self.read_packets(source, |packet, selected| {
             if let Ok(Event::Disconnected) = receiver.try_recv() {
-                break;
+                return false;
             }
-
             if let Ok(mut table) = table.lock() {
-                match link_type {
-                    Linktype(1) => {
-                        if let Ok((_, frame)) = ethernet::Frame::parse(packet.data) {
-                            let frame = Frame::Ethernet(frame);
-                            Self::capture_frame(&mut table, &frame, index);
-                            table.frames.push(frame);
-                        }
-                    }
-
-                    Linktype(105) => {
-                        if let Ok((_, frame)) = dot11::Frame::parse(packet.data) {
-                            let frame = Frame::Dot11(frame);
-                            Self::capture_frame(&mut table, &frame, index);
-                            table.frames.push(frame);
-                        }
-                    }
-
-                    Linktype(127) => {
-                        if let Ok((remaining, _)) = radiotap::RadioTapHeader::parse(packet.data) {
-                            if let Ok((_, frame)) = dot11::Frame::parse(remaining) {
-                                let frame = Frame::Dot11(frame);
-                                Self::capture_frame(&mut table, &frame, index);
-                                table.frames.push(frame);
-                            }
-                        }
-                    }
+                table.survey.update(&packet);
+                if selected {
+                    let index = table.packets.len();
+                    Self::capture_frame(&mut table, &packet, index);
+                    table.push_packet(packet);
+                }
+            }
+            true
+        })
+    }
 
-                    _ => unimplemented!("Unsupported interface: {:?}", link_type),
-                };
+    /// Headless mode: one summary line per packet, until a limit is hit or the source ends.
+    fn print_packets<W: Write>(
+        &self,
+        source: &mut dyn PacketSource,
+        out: &mut W,
+    ) -> Result<(), io::Error> {
+        let mut res = Ok(());
+
+        self.read_packets(source, |packet, selected| {
+            if !selected {
+                return true;
             }
-            index = index + 1;
+            res = writeln!(out, "{}", Summary::new(&packet).line(&self.columns));
+            res.is_ok()
+        });
+
+        match res {
+            Err(ref e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            res => res.and_then(|_| out.flush()),
         }
     }
 
@@ -349,10 +637,28 @@ impl Capture {
                 match evt {
                     Ok(key) => {
                         if let Ok(mut data) = table.lock() {
+                            if data.editing {
+                                match key {
+                                    Key::Char('\n') => {
+                                        data.editing = false;
+                                        data.apply_filter();
+                                    }
+                                    Key::Esc => data.editing = false,
+                                    Key::Backspace => {
+                                        data.filter_input.pop();
+                                    }
+                                    Key::Char(c) => data.filter_input.push(c),
+                                    _ => {}
+                                };
+                                sender.send(Event::Key).unwrap_or(());
+                                continue;
+                            }
                             match key {
                                 Key::Char('q') => sender.send(Event::Disconnected).unwrap_or(()),
                                 Key::Char(' ') => sender.send(Event::Paused).unwrap_or(()),
                                 Key::Char('h') => sender.send(Event::Selected).unwrap_or(()),
+                                Key::Char('/') => data.editing = true,
+                                Key::Char('w') => data.show_survey = !data.show_survey,
                                 Key::Down => data.next(false),
                                 Key::Ctrl(key) if key == 'n' => data.next(false),
                                 Key::Ctrl(key) if key == 'p' => data.previous(false),
@@ -370,6 +676,20 @@ impl Capture {
         }
     }
 
+    /// The terminal side of the interactive mode: key presses, ticks and drawing.
+    fn interact(
+        table: &Arc<Mutex<StatefulTable>>,
+        sender: &Sender<Event>,
+        receiver: &Receiver<Event>,
+    ) {
+        scope(|scope| {
+            scope.spawn(|_| Self::receive_key(table, sender, receiver));
+            scope.spawn(|_| Self::tick(sender));
+            scope.spawn(|_| draw(table, receiver));
+        })
+        .unwrap();
+    }
+
     fn tick(sender: &Sender<Event>) {
         let ticket = tick(Duration::from_secs_f64(0.5));
         loop {
@@ -383,17 +703,181 @@ impl Capture {
     }
 
     pub fn start(&self) -> Result<(), io::Error> {
-        let table = Arc::new(Mutex::new(StatefulTable::new()));
+        let mut source = self.open()?;
+        self.run(&mut *source)
+    }
+
+    /// Runs the selected interface, headless or interactive, over the packets of `source`.
+    pub fn run(&self, source: &mut dyn PacketSource) -> Result<(), io::Error> {
+        let link_type = source.link_type();
+        if !Self::supports(link_type) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unsupported link type {} ({}).",
+                    link_type.0,
+                    link_type.get_name().unwrap_or_default()
+                ),
+            ));
+        }
+
+        if self.headless {
+            let stdout = io::stdout();
+            return self.print_packets(source, &mut stdout.lock());
+        }
+
+        self.run_interactive(source, Self::interact);
+        Ok(())
+    }
+
+    /// Fills the packet table from `source` while `frontend` shows it, and tells the frontend when
+    /// a limit stops the capture.
+    fn run_interactive<F>(&self, source: &mut dyn PacketSource, frontend: F)
+    where
+        F: Fn(&Arc<Mutex<StatefulTable>>, &Sender<Event>, &Receiver<Event>) + Sync,
+    {
+        let mut table = StatefulTable::new();
+        table.show_survey = self.wireless;
+        let table = Arc::new(Mutex::new(table));
         let (sender, receiver) = bounded::<Event>(5);
 
+        // The source stays on this thread, since pcap handles cannot be sent across threads.
         scope(|scope| {
-            scope.spawn(|_| self.capture_packets(&table, &receiver));
-            scope.spawn(|_| Self::receive_key(&table, &sender, &receiver));
-            scope.spawn(|_| Self::tick(&sender));
-            scope.spawn(|_| draw(&table, &receiver));
+            scope.spawn(|_| frontend(&table, &sender, &receiver));
+            if self.capture_packets(source, &table, &receiver) {
+                sender.send(Event::Finished).unwrap_or(());
+            }
         })
         .unwrap();
+    }
+}
 
-        Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{MemorySource, RawPacket};
+
+    /// An mDNS query from 192.168.1.67:5353 to 224.0.0.251:5353.
+    const TEST_FRAME: &[u8] = &[
+        0x01, 0x00, 0x5E, 0x00, 0x00, 0xFB, 0x58, 0x00, 0xE3, 0x1D, 0x1E, 0x6B, 0x08, 0x00, 0x45,
+        0x00, 0x00, 0x3D, 0x62, 0xB8, 0x00, 0x00, 0x01, 0x11, 0xB4, 0x11, 0xC0, 0xA8, 0x01, 0x43,
+        0xE0, 0x00, 0x00, 0xFB, 0x14, 0xE9, 0x14, 0xE9, 0x00, 0x29, 0xAE, 0x6D, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x5F, 0x69, 0x70, 0x70, 0x04,
+        0x5F, 0x74, 0x63, 0x70, 0x05, 0x6C, 0x6F, 0x63, 0x61, 0x6C, 0x00, 0x00, 0x0C, 0x80, 0x01,
+    ];
+
+    fn print(capture: &Capture) -> String {
+        print_frame(capture, Linktype(1), TEST_FRAME)
+    }
+
+    fn print_frame(capture: &Capture, link_type: Linktype, frame: &[u8]) -> String {
+        let packets = (1..=3)
+            .map(|i| RawPacket::new(Duration::from_millis(i * 1500), frame.to_vec()))
+            .collect();
+        let mut source = MemorySource::new(link_type, packets);
+        let mut out = vec![];
+        capture.print_packets(&mut source, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn assert_headless_output() {
+        let mut capture = Capture::new();
+        capture.with_limits(Some(2), None);
+        assert_eq!(
+            print(&capture),
+            "00:00:01.500000 UDP 192.168.1.67 5353 224.0.0.251 5353 75 length 33\n\
+             00:00:03.000000 UDP 192.168.1.67 5353 224.0.0.251 5353 75 length 33\n"
+        );
+    }
+
+    #[test]
+    fn assert_dot11_data_output() {
+        // The same query in an open network data frame, behind an empty radiotap header.
+        let mut frame = vec![0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00];
+        frame.extend_from_slice(&[0x08, 0x00, 0x00, 0x00]);
+        frame.extend_from_slice(&TEST_FRAME[..12]);
+        frame.extend_from_slice(&[0x58, 0x00, 0xE3, 0x00, 0x00, 0x01, 0x10, 0x00]);
+        frame.extend_from_slice(&[0xAA, 0xAA, 0x03, 0x00, 0x00, 0x00]);
+        frame.extend_from_slice(&TEST_FRAME[12..]);
+
+        let mut capture = Capture::new();
+        capture.with_limits(Some(1), None);
+        assert_eq!(
+            print_frame(&capture, Linktype(127), &frame),
+            "00:00:01.500000 UDP 192.168.1.67 5353 224.0.0.251 5353 101 length 33\n"
+        );
+    }
+
+    #[test]
+    fn assert_sll2_interface() {
+        // The same query sent on interface 3 of an "any" capture.
+        let mut frame = vec![
+            0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x01, 0x04, 0x06,
+        ];
+        frame.extend_from_slice(&TEST_FRAME[6..12]);
+        frame.extend_from_slice(&[0x00, 0x00]);
+        frame.extend_from_slice(&TEST_FRAME[14..]);
+
+        let packet = RawPacket::new(Duration::from_secs(1), frame);
+        let packet = Capture::decode(Linktype(276), packet, None, &mut Reassembler::new()).unwrap();
+        assert_eq!(packet.interface, 3);
+    }
+
+    #[test]
+    fn assert_interactive_limit() {
+        let mut capture = Capture::new();
+        capture.with_limits(Some(2), None);
+        let packets = (1..=3)
+            .map(|i| RawPacket::new(Duration::from_millis(i * 1500), TEST_FRAME.to_vec()))
+            .collect();
+        let mut source = MemorySource::new(Linktype(1), packets);
+        let shown = Mutex::new(None);
+
+        // Stands in for the terminal, and quits as soon as the capture is over.
+        capture.run_interactive(&mut source, |table, _, receiver| loop {
+            match receiver.recv_timeout(Duration::from_secs(5)) {
+                Ok(Event::Finished) => {
+                    *shown.lock().unwrap() = Some(table.lock().unwrap().packets.len());
+                    break;
+                }
+                Ok(Event::Disconnected) => panic!("the capture asked the terminal to quit"),
+                Ok(_) => {}
+                Err(_) => panic!("the capture did not stop at its limit"),
+            }
+        });
+        assert_eq!(*shown.lock().unwrap(), Some(2));
+    }
+
+    #[test]
+    fn assert_protocol_flags() {
+        let mut capture = Capture::new();
+        capture.with_protocols(ProtocolFilter {
+            tcp: true,
+            ..Default::default()
+        });
+        assert_eq!(print(&capture), "");
+
+        // With -W, the frames the flags leave out of the table still reach the survey.
+        let mut beacon = vec![0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00];
+        beacon.extend_from_slice(&[0x80, 0x00, 0x00, 0x00]);
+        beacon.extend_from_slice(&[0xFF; 6]);
+        beacon.extend_from_slice(&[0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+        beacon.extend_from_slice(&[0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+        beacon.extend_from_slice(&[0x00, 0x00]);
+        beacon.extend_from_slice(&[0; 8]);
+        beacon.extend_from_slice(&[0x64, 0x00, 0x01, 0x00, 0, 4, b'h', b'o', b'm', b'e']);
+        let mut source = MemorySource::new(
+            Linktype(127),
+            vec![RawPacket::new(Duration::from_secs(1), beacon)],
+        );
+        capture.with_wireless(true);
+        let table = Arc::new(Mutex::new(StatefulTable::new()));
+        let (_, receiver) = bounded::<Event>(1);
+        capture.capture_packets(&mut source, &table, &receiver);
+
+        let table = table.lock().unwrap();
+        assert!(table.packets.is_empty());
+        assert_eq!(table.survey.access_point_rows()[0][1], "home");
     }
 }
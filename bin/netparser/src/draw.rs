@@ -1,4 +1,5 @@
 use crate::capture::Event;
+use crate::survey::Survey;
 use crate::table::*;
 
 use crossbeam::channel::Receiver;
@@ -44,6 +45,7 @@ fn draw_frame_excerpt<B: Backend>(f: &mut tui::Frame<B>, frame: &Frame, area: Re
             f.render_widget(paragraph, area);
         }
         Dot11(frame) => {}
+        LinuxSLL(_) | LinuxSLL2(_) | Raw(_) | Loopback(_) | PPP(_) => {}
     };
 }
 
@@ -57,7 +59,7 @@ fn draw_table<B: Backend>(
         .modifier(Modifier::BOLD | Modifier::ITALIC);
     let normal_style = Style::default().fg(Color::Blue).modifier(Modifier::ITALIC);
     let headers = table.headers.clone();
-    let records = table.records.clone();
+    let records = table.visible_records();
     let widths = table.widths.clone();
     let rows = records
         .iter()
@@ -89,11 +91,118 @@ fn draw_table<B: Backend>(
     }
 }
 
+fn draw_survey_table<B: Backend>(
+    f: &mut tui::Frame<B>,
+    title: &str,
+    headers: Vec<String>,
+    records: Vec<Vec<String>>,
+    widths: &[Constraint],
+    area: Rect,
+) {
+    let normal_style = Style::default().fg(Color::Blue);
+    let rows = records
+        .iter()
+        .map(|i| Row::StyledData(i.iter(), normal_style));
+    let t = Table::new(headers.iter(), rows)
+        .block(
+            Block::default()
+                .title(title)
+                .title_style(
+                    Style::default()
+                        .fg(Color::DarkGray)
+                        .modifier(Modifier::BOLD),
+                )
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded),
+        )
+        .header_style(
+            Style::default()
+                .fg(Color::DarkGray)
+                .modifier(Modifier::BOLD | Modifier::ITALIC),
+        )
+        .widths(widths)
+        .column_spacing(2);
+    f.render_widget(t, area);
+}
+
+/// The wireless survey: the access points above, their stations below.
+fn draw_survey<B: Backend>(f: &mut tui::Frame<B>, survey: &Survey, area: Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+    draw_survey_table(
+        f,
+        "Access points",
+        Survey::access_point_headers(),
+        survey.access_point_rows(),
+        &[
+            Constraint::Percentage(14),
+            Constraint::Percentage(22),
+            Constraint::Percentage(4),
+            Constraint::Percentage(10),
+            Constraint::Percentage(18),
+            Constraint::Percentage(8),
+            Constraint::Percentage(8),
+            Constraint::Percentage(8),
+        ],
+        chunks[0],
+    );
+    draw_survey_table(
+        f,
+        "Stations",
+        Survey::station_headers(),
+        survey.station_rows(),
+        &[
+            Constraint::Percentage(14),
+            Constraint::Percentage(16),
+            Constraint::Percentage(18),
+            Constraint::Percentage(14),
+            Constraint::Percentage(14),
+            Constraint::Percentage(24),
+        ],
+        chunks[1],
+    );
+}
+
+fn draw_filter_bar<B: Backend>(
+    f: &mut tui::Frame<B>,
+    table: &MutexGuard<StatefulTable>,
+    area: Rect,
+) {
+    let (text, color) = match table.filter_error {
+        Some(ref e) if !table.editing => (format!("{} ({})", table.filter_input, e), Color::Red),
+        _ if table.editing => (format!("{}_", table.filter_input), Color::White),
+        _ if table.filter.is_some() => (table.filter_input.clone(), Color::Green),
+        _ => (
+            "Press / to enter a display filter".to_string(),
+            Color::DarkGray,
+        ),
+    };
+    let text = [Text::styled(text, Style::default().fg(color))];
+    let block = Block::default()
+        .title("Filter")
+        .title_style(
+            Style::default()
+                .fg(Color::DarkGray)
+                .modifier(Modifier::BOLD),
+        )
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    let paragraph = Paragraph::new(text.iter()).block(block);
+    f.render_widget(paragraph, area);
+}
+
+/// Splits the screen into the packet table, the filter bar and, if `footer` is set, the frame view.
 fn get_rendering_area<B: Backend>(f: &mut tui::Frame<B>, footer: bool) -> Vec<Rect> {
     let constraints = if footer {
-        vec![Constraint::Percentage(50), Constraint::Percentage(50)]
+        vec![
+            Constraint::Percentage(50),
+            Constraint::Length(3),
+            Constraint::Percentage(50),
+        ]
     } else {
-        vec![Constraint::Percentage(100)]
+        vec![Constraint::Min(0), Constraint::Length(3)]
     };
     Layout::default()
         .direction(Direction::Vertical)
@@ -124,17 +233,23 @@ pub fn draw(
                 if let Ok(mut table) = table.lock() {
                     let chunks = get_rendering_area(&mut f, true);
                     draw_table(&mut f, &mut table, chunks[0]);
+                    draw_filter_bar(&mut f, &table, chunks[1]);
                     if let Some(i) = table.get_selected() {
-                        if let Some(frame) = table.frames.get(i) {
-                            draw_frame_excerpt(&mut f, &frame, chunks[1]);
+                        if let Some(packet) = table.packets.get(i) {
+                            draw_frame_excerpt(&mut f, &packet.frame, chunks[2]);
                         }
                     }
                 }
             })?,
-            Ok(Event::Key) | Ok(Event::Tick) => terminal.draw(|mut f| {
+            Ok(Event::Key) | Ok(Event::Tick) | Ok(Event::Finished) => terminal.draw(|mut f| {
                 if let Ok(mut table) = table.lock() {
                     let chunks = get_rendering_area(&mut f, false);
-                    draw_table(&mut f, &mut table, chunks[0]);
+                    if table.show_survey {
+                        draw_survey(&mut f, &table.survey, chunks[0]);
+                    } else {
+                        draw_table(&mut f, &mut table, chunks[0]);
+                    }
+                    draw_filter_bar(&mut f, &table, chunks[1]);
                 }
             })?,
             _ => {}
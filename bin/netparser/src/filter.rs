@@ -0,0 +1,195 @@
+use netparse::{
+    layer2::datalink::{Frame, Payload},
+    layer3::ip::ip,
+};
+
+/// The protocols selected with the `-U/-T/-I/-A/-4/-6` flags.
+/// Flags of the same layer are OR'ed together and the two layers are AND'ed, so `-T -6` only keeps TCP over IPv6.
+#[derive(Default, Clone)]
+pub struct ProtocolFilter {
+    pub udp: bool,
+    pub tcp: bool,
+    pub icmp: bool,
+    pub arp: bool,
+    pub ipv4: bool,
+    pub ipv6: bool,
+}
+
+impl ProtocolFilter {
+    fn network_primitives(&self) -> Vec<&'static str> {
+        let mut res = vec![];
+        if self.ipv4 {
+            res.push("ip");
+        }
+        if self.ipv6 {
+            res.push("ip6");
+        }
+        if self.arp {
+            res.push("arp");
+        }
+        res
+    }
+
+    fn transport_primitives(&self) -> Vec<&'static str> {
+        let mut res = vec![];
+        if self.udp {
+            res.push("udp");
+        }
+        if self.tcp {
+            res.push("tcp");
+        }
+        if self.icmp {
+            res.push("icmp");
+        }
+        res
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.network_primitives().is_empty() && self.transport_primitives().is_empty()
+    }
+
+    /// Builds a pcap filter expression out of the selected protocols and the user supplied `--filter` expression.
+    pub fn to_bpf(&self, filter: &str) -> String {
+        let mut clauses = vec![];
+        if !filter.trim().is_empty() {
+            clauses.push(format!("({})", filter.trim()));
+        }
+        for primitives in [self.network_primitives(), self.transport_primitives()].iter() {
+            if !primitives.is_empty() {
+                clauses.push(format!("({})", primitives.join(" or ")));
+            }
+        }
+        clauses.join(" and ")
+    }
+
+    fn matches_network(&self, payload: &Payload) -> bool {
+        match payload {
+            Payload::IPv4(_) => self.ipv4,
+            Payload::IPv6(_) => self.ipv6,
+            Payload::ARP(_) => self.arp,
+            _ => false,
+        }
+    }
+
+    fn matches_transport(&self, payload: &Payload) -> bool {
+        let payload = match payload {
+            Payload::IPv4(ref packet) => &packet.payload,
+            Payload::IPv6(ref packet) => &packet.payload,
+            _ => return false,
+        };
+        match payload {
+            ip::Payload::UDP(_) => self.udp,
+            ip::Payload::TCP(_) => self.tcp,
+            ip::Payload::ICMP(_) => self.icmp,
+            _ => false,
+        }
+    }
+
+    /// The post-parse equivalent of `to_bpf`, used where the kernel filter is not available.
+    pub fn matches(&self, frame: &Frame) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        match frame.payload() {
+            Some(payload) => {
+                (self.network_primitives().is_empty() || self.matches_network(payload))
+                    && (self.transport_primitives().is_empty() || self.matches_transport(payload))
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use netparse::layer2::ethernet;
+
+    /// An mDNS query from 192.168.1.67:5353 to 224.0.0.251:5353.
+    const UDP_FRAME: &[u8] = &[
+        0x01, 0x00, 0x5E, 0x00, 0x00, 0xFB, 0x58, 0x00, 0xE3, 0x1D, 0x1E, 0x6B, 0x08, 0x00, 0x45,
+        0x00, 0x00, 0x3D, 0x62, 0xB8, 0x00, 0x00, 0x01, 0x11, 0xB4, 0x11, 0xC0, 0xA8, 0x01, 0x43,
+        0xE0, 0x00, 0x00, 0xFB, 0x14, 0xE9, 0x14, 0xE9, 0x00, 0x29, 0xAE, 0x6D, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x5F, 0x69, 0x70, 0x70, 0x04,
+        0x5F, 0x74, 0x63, 0x70, 0x05, 0x6C, 0x6F, 0x63, 0x61, 0x6C, 0x00, 0x00, 0x0C, 0x80, 0x01,
+    ];
+
+    #[test]
+    fn assert_bpf_expression() {
+        let protocols = ProtocolFilter {
+            udp: true,
+            tcp: true,
+            ipv6: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            protocols.to_bpf(" port 53 "),
+            "(port 53) and (ip6) and (udp or tcp)"
+        );
+        assert_eq!(protocols.to_bpf(""), "(ip6) and (udp or tcp)");
+        assert_eq!(
+            ProtocolFilter::default().to_bpf("host 10.0.0.1"),
+            "(host 10.0.0.1)"
+        );
+        assert_eq!(ProtocolFilter::default().to_bpf(""), "");
+    }
+
+    #[test]
+    fn assert_udp_matches() {
+        let (_, frame) = ethernet::Frame::parse(UDP_FRAME).unwrap();
+        let frame = Frame::Ethernet(frame);
+
+        assert!(ProtocolFilter::default().matches(&frame));
+        for (protocols, expected) in [
+            (
+                ProtocolFilter {
+                    udp: true,
+                    ..Default::default()
+                },
+                true,
+            ),
+            (
+                ProtocolFilter {
+                    ipv4: true,
+                    ..Default::default()
+                },
+                true,
+            ),
+            (
+                ProtocolFilter {
+                    udp: true,
+                    ipv4: true,
+                    ..Default::default()
+                },
+                true,
+            ),
+            (
+                ProtocolFilter {
+                    udp: true,
+                    ipv6: true,
+                    ..Default::default()
+                },
+                false,
+            ),
+            (
+                ProtocolFilter {
+                    tcp: true,
+                    icmp: true,
+                    ..Default::default()
+                },
+                false,
+            ),
+            (
+                ProtocolFilter {
+                    arp: true,
+                    ..Default::default()
+                },
+                false,
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(protocols.matches(&frame), *expected);
+        }
+    }
+}
@@ -1,10 +1,15 @@
 mod capture;
 mod draw;
+mod filter;
+mod source;
+mod summary;
+mod survey;
 mod table;
 
-use crate::capture::Capture;
+use crate::{capture::Capture, filter::ProtocolFilter, summary::Column};
 use clap::Clap;
-use std::io;
+use netparse::layer2::wifi::decrypt::Credentials;
+use std::{io, time::Duration};
 
 ///Specifies output options when parsing packets.
 #[derive(Clap)]
@@ -15,11 +20,13 @@ pub struct CLI {
         short = "i",
         long = "interface",
         value_name = "name",
-        takes_value = true,
-        required = true
+        takes_value = true
     )]
     /// Specifies the interface on which to monitor.
-    pub interface: String,
+    pub interface: Option<String>,
+    #[clap(short = "r", long = "read", value_name = "file", takes_value = true)]
+    /// Read packets from a pcap or pcapng file, or a named pipe, instead of capturing on an interface. Use - for stdin.
+    pub read: Option<String>,
     #[clap(
         short = "f",
         long = "filename",
@@ -35,7 +42,8 @@ pub struct CLI {
     /// Output as JSON. This will only work if a file name is also provided.
     pub json: bool,
     /// On most OSes, 802.11 frames are converted into fake Ethernet frames, unless monitor mode is being used.
-    /// This option will use monitor mode so the raw, wireless frames are displayed instead.
+    /// This option will use monitor mode so the raw, wireless frames are displayed instead,
+    /// and starts the interface on the survey of access points and stations. Press w to switch views.
     #[clap(short = "W", long = "wireless")]
     pub wireless: bool,
     #[clap(long = "filter", takes_value = true)]
@@ -59,16 +67,87 @@ pub struct CLI {
     #[clap(short = "6", long = "ipv6")]
     /// Output only IPv6 packets.
     pub ipv6: bool,
+    #[clap(long = "headless")]
+    /// Print one line per packet to stdout instead of starting the interactive interface.
+    pub headless: bool,
+    #[clap(long = "format", value_name = "columns", takes_value = true)]
+    /// Comma separated columns to print in headless mode: time, src, dst, sport, dport, proto, len, info.
+    pub format: Option<String>,
+    #[clap(short = "c", long = "count", value_name = "n", takes_value = true)]
+    /// Exit after receiving this many packets.
+    pub count: Option<usize>,
+    #[clap(long = "duration", value_name = "seconds", takes_value = true)]
+    /// Exit after capturing for this many seconds.
+    pub duration: Option<u64>,
+    #[clap(long = "wpa-pwd", value_name = "passphrase:ssid", takes_value = true)]
+    /// Decrypt the 802.11 data frames of a WPA/WPA2 personal network, given its passphrase and SSID.
+    pub wpa_pwd: Option<String>,
+    #[clap(long = "wpa-psk", value_name = "hex", takes_value = true)]
+    /// Decrypt the 802.11 data frames of a WPA/WPA2 personal network, given its 256-bit PSK.
+    pub wpa_psk: Option<String>,
+}
+
+/// The credentials given with `--wpa-pwd` and `--wpa-psk`.
+fn credentials(cli: &CLI) -> Result<Vec<Credentials>, io::Error> {
+    let mut res = vec![];
+    if let Some(ref pwd) = cli.wpa_pwd {
+        // The SSID follows the last colon, so that passphrases may contain colons.
+        let mut parts = pwd.rsplitn(2, ':');
+        match (parts.next(), parts.next()) {
+            (Some(ssid), Some(passphrase)) => res.push(Credentials::Passphrase {
+                passphrase: passphrase.to_string(),
+                ssid: ssid.to_string(),
+            }),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Expected --wpa-pwd as passphrase:ssid.",
+                ))
+            }
+        }
+    }
+    if let Some(ref psk) = cli.wpa_psk {
+        match Credentials::from_hex_psk(psk) {
+            Some(credentials) => res.push(credentials),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Expected --wpa-psk as 64 hex digits.",
+                ))
+            }
+        }
+    }
+    Ok(res)
 }
 
 fn main() -> Result<(), io::Error> {
     #[cfg(target_os = "windows")]
     compile_error!("Sorry, no implementations for Windows.");
     let cli: CLI = CLI::parse();
+    let keys = credentials(&cli)?;
     let mut capture = Capture::new();
 
-    capture.with_interface(cli.interface);
+    if let Some(interface) = cli.interface {
+        capture.with_interface(interface);
+    }
+    capture.with_file(cli.read);
     capture.with_wireless(cli.wireless);
+    capture.with_filter(cli.filter.unwrap_or_default());
+    capture.with_protocols(ProtocolFilter {
+        udp: cli.udp,
+        tcp: cli.tcp,
+        icmp: cli.icmp,
+        arp: cli.arp,
+        ipv4: cli.ipv4,
+        ipv6: cli.ipv6,
+    });
+    let columns = match cli.format {
+        Some(ref format) => Column::parse_list(format)?,
+        None => Column::defaults(),
+    };
+    capture.with_headless(cli.headless, columns);
+    capture.with_limits(cli.count, cli.duration.map(Duration::from_secs));
+    capture.with_keys(keys);
     capture.start()?;
     Ok(())
 }
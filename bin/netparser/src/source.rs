@@ -0,0 +1,142 @@
+use pcap::{self, Activated, Linktype};
+use std::{collections::VecDeque, io, time::Duration};
+
+/// How long a live capture blocks waiting for packets, so that the duration limit is checked on quiet links.
+static READ_TIMEOUT_MS: i32 = 250;
+
+/// An undecoded packet as handed out by a `PacketSource`.
+pub struct RawPacket {
+    pub timestamp: Duration,
+    pub caplen: u32,
+    pub len: u32,
+    pub interface: u32,
+    pub data: Vec<u8>,
+}
+
+impl RawPacket {
+    /// A complete, untruncated packet captured at `timestamp`.
+    #[allow(dead_code)]
+    pub fn new(timestamp: Duration, data: Vec<u8>) -> Self {
+        Self {
+            timestamp,
+            caplen: data.len() as u32,
+            len: data.len() as u32,
+            interface: 0,
+            data,
+        }
+    }
+}
+
+pub enum Poll {
+    Packet(RawPacket),
+    /// No packet arrived within the read timeout; the source may still produce more.
+    Timeout,
+    /// The source is exhausted or failed.
+    Done,
+}
+
+/// Anything packets can be read from: an interface, a savefile, a pipe or a vector in memory.
+pub trait PacketSource {
+    /// The LINKTYPE_* of every packet produced by this source.
+    fn link_type(&self) -> Linktype;
+
+    /// Applies a pcap filter expression.
+    /// Sources that cannot run BPF ignore it and rely on the post-parse protocol filter.
+    fn set_filter(&mut self, _filter: &str) -> Result<(), io::Error> {
+        Ok(())
+    }
+
+    fn next_packet(&mut self) -> Poll;
+}
+
+fn pcap_error(e: pcap::Error, context: &str) -> io::Error {
+    io::Error::other(format!("{}: {:?}", context, e))
+}
+
+/// Reads from a libpcap handle, which is how live interfaces, savefiles and pipes are all accessed.
+struct PcapSource {
+    cap: pcap::Capture<dyn Activated>,
+}
+
+impl PacketSource for PcapSource {
+    fn link_type(&self) -> Linktype {
+        self.cap.get_datalink()
+    }
+
+    fn set_filter(&mut self, filter: &str) -> Result<(), io::Error> {
+        self.cap
+            .filter(filter)
+            .map_err(|e| pcap_error(e, "Invalid filter provided"))
+    }
+
+    fn next_packet(&mut self) -> Poll {
+        match self.cap.next() {
+            Ok(packet) => Poll::Packet(RawPacket {
+                timestamp: Duration::new(
+                    packet.header.ts.tv_sec as u64,
+                    packet.header.ts.tv_usec as u32 * 1000,
+                ),
+                caplen: packet.header.caplen,
+                len: packet.header.len,
+                interface: 0,
+                data: packet.data.to_vec(),
+            }),
+            Err(pcap::Error::TimeoutExpired) => Poll::Timeout,
+            Err(_) => Poll::Done,
+        }
+    }
+}
+
+/// Captures on a network interface, optionally in monitor mode.
+pub fn live(interface: &str, monitor: bool) -> Result<Box<dyn PacketSource>, io::Error> {
+    let cap = pcap::Capture::from_device(interface)
+        .map_err(|e| pcap_error(e, "There was a problem selecting the given interface"))?
+        .promisc(true)
+        .rfmon(monitor)
+        .buffer_size(512)
+        .timeout(READ_TIMEOUT_MS)
+        .open()
+        .map_err(|e| pcap_error(e, "There was a problem capturing on that interface"))?;
+    Ok(Box::new(PcapSource { cap: cap.into() }))
+}
+
+/// Reads a pcap or pcapng savefile. A named pipe works as well, and `-` reads the savefile from stdin.
+pub fn file(path: &str) -> Result<Box<dyn PacketSource>, io::Error> {
+    let cap = if path == "-" {
+        pcap::Capture::from_raw_fd(0)
+    } else {
+        pcap::Capture::from_file(path)
+    }
+    .map_err(|e| pcap_error(e, "There was a problem opening the given file"))?;
+    Ok(Box::new(PcapSource { cap: cap.into() }))
+}
+
+/// Replays packets held in memory, for tests and for frames synthesized by other tools.
+#[allow(dead_code)]
+pub struct MemorySource {
+    link_type: Linktype,
+    packets: VecDeque<RawPacket>,
+}
+
+impl MemorySource {
+    #[allow(dead_code)]
+    pub fn new(link_type: Linktype, packets: Vec<RawPacket>) -> Self {
+        Self {
+            link_type,
+            packets: packets.into(),
+        }
+    }
+}
+
+impl PacketSource for MemorySource {
+    fn link_type(&self) -> Linktype {
+        self.link_type
+    }
+
+    fn next_packet(&mut self) -> Poll {
+        match self.packets.pop_front() {
+            Some(packet) => Poll::Packet(packet),
+            None => Poll::Done,
+        }
+    }
+}
@@ -0,0 +1,407 @@
+use netparse::{
+    core::ux::u1,
+    layer2::{
+        arp,
+        datalink::{CapturedPacket, Frame, Payload},
+        wifi::{
+            dot11::{Dot11Addr, FcsStatus, FrameBody},
+            management::Element,
+            phy,
+            radiotap::RadioTapHeader,
+        },
+    },
+    layer3::{
+        icmp,
+        ip::{ip, tcp},
+    },
+};
+use std::{fmt, io, str::FromStr, time::Duration};
+
+/// A column of the one-line summary printed in headless mode, selected with `--format`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Column {
+    Time,
+    Src,
+    Dst,
+    SrcPort,
+    DstPort,
+    Proto,
+    Length,
+    Info,
+}
+
+impl FromStr for Column {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "time" => Ok(Column::Time),
+            "src" => Ok(Column::Src),
+            "dst" => Ok(Column::Dst),
+            "sport" => Ok(Column::SrcPort),
+            "dport" => Ok(Column::DstPort),
+            "proto" => Ok(Column::Proto),
+            "len" => Ok(Column::Length),
+            "info" => Ok(Column::Info),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unknown column '{}'. Expected one of time, src, dst, sport, dport, proto, len, info.",
+                    s
+                ),
+            )),
+        }
+    }
+}
+
+impl Column {
+    pub fn defaults() -> Vec<Self> {
+        use Column::*;
+        vec![Time, Proto, Src, SrcPort, Dst, DstPort, Length, Info]
+    }
+
+    /// Parses a comma separated column list such as `time,src,dst,info`.
+    pub fn parse_list(s: &str) -> Result<Vec<Self>, io::Error> {
+        s.split(',').map(Self::from_str).collect()
+    }
+}
+
+/// The fields of a frame that are shown on a single line.
+#[derive(Default)]
+pub struct Summary {
+    pub time: String,
+    pub src: Option<String>,
+    pub dst: Option<String>,
+    pub src_port: Option<u16>,
+    pub dst_port: Option<u16>,
+    pub proto: String,
+    pub len: u32,
+    pub info: String,
+}
+
+/// Formats a capture timestamp as `HH:MM:SS.uuuuuu` (UTC), like the default timestamps of `tcpdump`.
+fn format_time(timestamp: Duration) -> String {
+    let day = timestamp.as_secs() % 86400;
+    format!(
+        "{:02}:{:02}:{:02}.{:06}",
+        day / 3600,
+        day / 60 % 60,
+        day % 60,
+        timestamp.subsec_micros()
+    )
+}
+
+fn or_unknown<T: fmt::Debug>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map_or_else(|| "unknown".to_string(), |v| format!("{:?}", v))
+}
+
+/// Signal, channel and rate, e.g. `-60dBm ch 6 54.0Mb/s`.
+fn radio_info(radiotap: &RadioTapHeader) -> String {
+    let mut res = vec![];
+    if let Some(signal) = radiotap.signal_dbm() {
+        res.push(format!("{}dBm", signal));
+    }
+    if let Some(channel) = radiotap.channel() {
+        match channel.number() {
+            Some(number) => res.push(format!("ch {}", number)),
+            None => res.push(format!("{} MHz", channel.freq)),
+        }
+    }
+    if let Some(rate) = radiotap.rate_mbps() {
+        res.push(format!("{:.1}Mb/s", rate));
+    }
+    res.join(" ")
+}
+
+fn hex_addr(addr: &[u8]) -> String {
+    addr.iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn tcp_info(packet: &tcp::Packet) -> String {
+    let set = u1::new(1);
+    let mut flags = String::new();
+    for (bit, c) in [
+        (packet.syn, 'S'),
+        (packet.fin, 'F'),
+        (packet.rst, 'R'),
+        (packet.psh, 'P'),
+        (packet.urg, 'U'),
+        (packet.ack, '.'),
+    ]
+    .iter()
+    {
+        if *bit == set {
+            flags.push(*c);
+        }
+    }
+    let mut res = format!("Flags [{}], seq {}", flags, packet.seq_num);
+    if packet.ack == set {
+        res.push_str(&format!(", ack {}", packet.ack_num));
+    }
+    res.push_str(&format!(
+        ", win {}, length {}",
+        packet.window_size,
+        packet.payload.0.len()
+    ));
+    res
+}
+
+fn icmp_info(packet: &icmp::Packet) -> String {
+    use icmp::{DestinationUnreachable, Header, TimeExceeded, Type};
+    let echo = match packet.header {
+        Header::EchoRequest(ref echo) | Header::EchoReply(ref echo) => {
+            format!(", id {}, seq {}", echo.identifier, echo.sequence_number)
+        }
+        _ => String::new(),
+    };
+    match packet.typ {
+        Type::EchoRequest => format!("echo request{}", echo),
+        Type::EchoReply => format!("echo reply{}", echo),
+        Type::DestinationUnreachable(DestinationUnreachable::HostUnreachable) => {
+            "host unreachable".to_string()
+        }
+        Type::DestinationUnreachable(DestinationUnreachable::Other(code)) => {
+            format!("destination unreachable, code {}", code)
+        }
+        Type::TimeExceeded(TimeExceeded::TTLExpired) => "time exceeded in-transit".to_string(),
+        Type::TimeExceeded(TimeExceeded::Other(code)) => format!("time exceeded, code {}", code),
+        Type::Other(typ, code) => format!("type {}, code {}", typ, code),
+    }
+}
+
+fn arp_info(packet: &arp::Packet) -> String {
+    match packet.operation {
+        Some(arp::Operation::ARPRequest) => format!(
+            "who-has {} tell {}",
+            packet.target_ip_addr, packet.sender_ip_addr
+        ),
+        Some(arp::Operation::ARPReply) => {
+            format!("{} is-at {}", packet.sender_ip_addr, packet.sender_hw_addr)
+        }
+        Some(op) => format!("{:?}", op),
+        None => "unknown operation".to_string(),
+    }
+}
+
+impl Summary {
+    pub fn new(packet: &CapturedPacket) -> Self {
+        let frame = &packet.frame;
+        let mut res = Self {
+            time: format_time(packet.timestamp),
+            len: packet.len,
+            ..Default::default()
+        };
+
+        match frame {
+            Frame::Ethernet(ref frame) => {
+                res.src = Some(frame.src.to_string());
+                res.dst = Some(frame.dst.to_string());
+                res.proto = "Ethernet".to_string();
+                if let Some(ref ether_type) = frame.ether_type {
+                    res.info = format!("ethertype {:?}", ether_type);
+                } else if let (Some(length), Some(ref llc)) = (frame.length, &frame.llc) {
+                    res.proto = "802.3".to_string();
+                    res.info = match frame.snap {
+                        Some(ref snap) => format!(
+                            "length {}, SNAP ethertype {}",
+                            length,
+                            or_unknown(&snap.ether_type)
+                        ),
+                        None => format!(
+                            "length {}, LLC dsap 0x{:02X} ssap 0x{:02X}",
+                            length, llc.dsap, llc.ssap
+                        ),
+                    };
+                }
+            }
+            Frame::LinuxSLL(ref frame) => {
+                res.src = Some(hex_addr(&frame.addr.0));
+                res.proto = "Linux SLL".to_string();
+                res.info = format!(
+                    "{}, ethertype {}",
+                    or_unknown(&frame.packet_type),
+                    or_unknown(&frame.protocol)
+                );
+            }
+            Frame::LinuxSLL2(ref frame) => {
+                res.src = Some(hex_addr(&frame.addr.0));
+                res.proto = "Linux SLL2".to_string();
+                res.info = format!(
+                    "{} on ifindex {}, ethertype {}",
+                    or_unknown(&frame.packet_type),
+                    frame.interface_index,
+                    or_unknown(&frame.protocol)
+                );
+            }
+            Frame::Raw(_) => res.proto = "Raw IP".to_string(),
+            Frame::Loopback(ref frame) => {
+                res.proto = "Loopback".to_string();
+                res.info = format!("family {}", or_unknown(&frame.family));
+            }
+            Frame::PPP(ref frame) => {
+                res.proto = "PPP".to_string();
+                res.info = format!("protocol {}", or_unknown(&frame.protocol));
+            }
+            Frame::Dot11(ref frame) => {
+                res.proto = "802.11".to_string();
+                res.info = format!("{:?} {:?}", frame.fc.typ, frame.fc.subtype);
+                let bss = match frame.frame_body {
+                    FrameBody::Beacon(ref body) => {
+                        Some((body.ssid(), body.security(), &body.dynamic_fields))
+                    }
+                    FrameBody::ProbeResponse(ref body) => {
+                        Some((body.ssid(), body.security(), &body.dynamic_fields))
+                    }
+                    _ => None,
+                };
+                match frame.frame_body {
+                    FrameBody::Action(ref body) => {
+                        res.info = format!("{} {}", res.info, body.name());
+                    }
+                    FrameBody::ProbeRequest(ref body) => {
+                        res.info = format!("{} \"{}\"", res.info, body.ssid().unwrap_or(""));
+                    }
+                    FrameBody::AssociationRequest(ref body) => {
+                        res.info = format!("{} \"{}\"", res.info, body.ssid().unwrap_or(""));
+                    }
+                    FrameBody::ReassociationRequest(ref body) => {
+                        res.info = format!("{} \"{}\"", res.info, body.ssid().unwrap_or(""));
+                    }
+                    FrameBody::AssociationResponse(ref body) if !body.status_code.is_success() => {
+                        res.info = format!("{} {}", res.info, body.status_code);
+                    }
+                    FrameBody::Authentication(ref body) => {
+                        res.info = match body.sae {
+                            Some(ref sae) => format!("{} {}", res.info, sae.name()),
+                            None => {
+                                let algo = body
+                                    .algo_num
+                                    .map_or_else(|| "unknown".to_string(), |a| a.to_string());
+                                format!("{} {} seq {}", res.info, algo, body.auth_seq)
+                            }
+                        };
+                        if !body.status_code.is_success() {
+                            res.info = format!("{}: {}", res.info, body.status_code);
+                        }
+                    }
+                    _ => {}
+                }
+                if let Some((ssid, security, elements)) = bss {
+                    res.info = format!(
+                        "{} \"{}\" {} {}MHz",
+                        res.info,
+                        ssid.unwrap_or(""),
+                        security,
+                        phy::channel_width(elements)
+                    );
+                    if let Some(mesh_id) = Element::find_mesh_id(elements) {
+                        res.info = format!("{} mesh \"{}\"", res.info, mesh_id);
+                    }
+                }
+                if let Some(ref radiotap) = packet.radiotap {
+                    res.info = format!("{} {}", radio_info(radiotap), res.info);
+                }
+                if frame.fcs_status == FcsStatus::Invalid {
+                    res.info.push_str(" [bad FCS]");
+                }
+                if frame.duplicate {
+                    res.info.push_str(" [duplicate]");
+                } else if u8::from(frame.fc.flags.retry) == 1 {
+                    res.info.push_str(" [retry]");
+                }
+                if let (FrameBody::Fragment(_), Some(ref sc)) =
+                    (&frame.frame_body, &frame.seq_control)
+                {
+                    res.info = format!("{} [fragment {}]", res.info, sc.frag_num);
+                }
+                for addr in [Some(&frame.addr1), frame.addr2.as_ref()].iter() {
+                    match addr {
+                        Some(Dot11Addr::ReceiverAddress(a))
+                        | Some(Dot11Addr::DestinationAddress(a)) => res.dst = Some(a.to_string()),
+                        Some(Dot11Addr::TransmitterAddress(a))
+                        | Some(Dot11Addr::SourceAddress(a)) => res.src = Some(a.to_string()),
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        let payload = match frame.payload() {
+            Some(Payload::IPv4(ref packet)) => {
+                res.src = Some(packet.src.to_string());
+                res.dst = Some(packet.dst.to_string());
+                res.proto = "IPv4".to_string();
+                &packet.payload
+            }
+            Some(Payload::IPv6(ref packet)) => {
+                res.src = Some(packet.src.to_string());
+                res.dst = Some(packet.dst.to_string());
+                res.proto = "IPv6".to_string();
+                &packet.payload
+            }
+            Some(Payload::ARP(ref packet)) => {
+                res.src = Some(packet.sender_ip_addr.to_string());
+                res.dst = Some(packet.target_ip_addr.to_string());
+                res.proto = "ARP".to_string();
+                res.info = arp_info(packet);
+                return res;
+            }
+            Some(Payload::EAPOL(ref frame)) => {
+                res.proto = "EAPOL".to_string();
+                res.info = frame.to_string();
+                return res;
+            }
+            Some(Payload::STP(ref bpdu)) => {
+                res.proto = "STP".to_string();
+                res.info = bpdu.to_string();
+                return res;
+            }
+            _ => return res,
+        };
+
+        match payload {
+            ip::Payload::TCP(ref packet) => {
+                res.src_port = Some(packet.src_port);
+                res.dst_port = Some(packet.dst_port);
+                res.proto = "TCP".to_string();
+                res.info = tcp_info(packet);
+            }
+            ip::Payload::UDP(ref datagram) => {
+                res.src_port = Some(datagram.src_port);
+                res.dst_port = Some(datagram.dst_port);
+                res.proto = "UDP".to_string();
+                res.info = format!("length {}", datagram.payload.0.len());
+            }
+            ip::Payload::ICMP(ref packet) => {
+                res.proto = "ICMP".to_string();
+                res.info = icmp_info(packet);
+            }
+            _ => {}
+        }
+        res
+    }
+
+    /// Renders the selected columns separated by spaces, with `-` for fields the frame does not carry.
+    pub fn line(&self, columns: &[Column]) -> String {
+        let missing = || "-".to_string();
+        columns
+            .iter()
+            .map(|column| match column {
+                Column::Time => self.time.clone(),
+                Column::Src => self.src.clone().unwrap_or_else(missing),
+                Column::Dst => self.dst.clone().unwrap_or_else(missing),
+                Column::SrcPort => self.src_port.map_or_else(missing, |p| p.to_string()),
+                Column::DstPort => self.dst_port.map_or_else(missing, |p| p.to_string()),
+                Column::Proto => self.proto.clone(),
+                Column::Length => self.len.to_string(),
+                Column::Info => self.info.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
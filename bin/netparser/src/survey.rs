@@ -0,0 +1,404 @@
+use netparse::{
+    core::ux::u1,
+    layer2::{
+        datalink::{Addr, CapturedPacket, Frame},
+        wifi::{
+            dot11::{self, FcsStatus, FrameBody, Type},
+            management::Element,
+            rsn::Security,
+        },
+    },
+};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, HashMap},
+};
+
+/// The signal of a transmitter over all its frames, in dBm.
+#[derive(Default)]
+pub struct SignalStats {
+    count: u32,
+    sum: i64,
+    min: i8,
+    max: i8,
+    last: Option<i8>,
+}
+
+impl SignalStats {
+    fn add(&mut self, dbm: i8) {
+        if self.count == 0 || dbm < self.min {
+            self.min = dbm;
+        }
+        if self.count == 0 || dbm > self.max {
+            self.max = dbm;
+        }
+        self.count += 1;
+        self.sum += dbm as i64;
+        self.last = Some(dbm);
+    }
+
+    pub fn average(&self) -> Option<i8> {
+        match self.count {
+            0 => None,
+            n => Some((self.sum / n as i64) as i8),
+        }
+    }
+
+    /// The last signal, then the minimum, average and maximum, e.g. `-52 (-60/-55/-48)`.
+    fn describe(&self) -> String {
+        match (self.last, self.average()) {
+            (Some(last), Some(avg)) => format!("{} ({}/{}/{})", last, self.min, avg, self.max),
+            _ => String::new(),
+        }
+    }
+}
+
+/// A BSS, as advertised by its beacons and probe responses.
+pub struct AccessPoint {
+    /// Empty for hidden networks until a probe response gives the name away.
+    pub ssid: String,
+    pub channel: Option<u16>,
+    pub security: Security,
+    /// In time units of 1024 µs.
+    pub beacon_interval: u16,
+    pub beacons: u64,
+    pub signal: SignalStats,
+}
+
+/// A client station, as seen from its probes, associations and data frames.
+#[derive(Default)]
+pub struct Station {
+    pub bssid: Option<Addr>,
+    pub probed: BTreeSet<String>,
+    pub tx_frames: u64,
+    pub tx_bytes: u64,
+    pub rx_frames: u64,
+    pub rx_bytes: u64,
+    pub signal: SignalStats,
+}
+
+/// The access points and stations heard in a monitor mode capture.
+#[derive(Default)]
+pub struct Survey {
+    pub access_points: HashMap<Addr, AccessPoint>,
+    pub stations: HashMap<Addr, Station>,
+}
+
+/// The channel of a BSS, from the DS Parameter Set of its beacons.
+fn ds_channel(elements: &[Element]) -> Option<u16> {
+    elements.iter().find_map(|e| match e {
+        Element::DSParameterSet(ds) => Some(ds.current_channel as u16),
+        _ => None,
+    })
+}
+
+impl Survey {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, packet: &CapturedPacket) {
+        let frame = match packet.frame {
+            Frame::Dot11(ref frame)
+                if frame.fcs_status != FcsStatus::Invalid && !frame.duplicate =>
+            {
+                frame
+            }
+            _ => return,
+        };
+        let radiotap = packet.radiotap.as_ref();
+        let signal = radiotap.and_then(|r| r.signal_dbm());
+        let channel = radiotap.and_then(|r| r.channel()).and_then(|c| c.number());
+        let transmitter = match frame.transmitter() {
+            Some(addr) => *addr,
+            None => return,
+        };
+
+        let bss = match frame.frame_body {
+            FrameBody::Beacon(ref body) => Some((
+                body.ssid(),
+                body.security(),
+                body.beacon_interval,
+                &body.dynamic_fields,
+                true,
+            )),
+            FrameBody::ProbeResponse(ref body) => Some((
+                body.ssid(),
+                body.security(),
+                body.beacon_interval,
+                &body.dynamic_fields,
+                false,
+            )),
+            _ => None,
+        };
+        if let Some((ssid, security, beacon_interval, elements, beacon)) = bss {
+            let ap = self
+                .access_points
+                .entry(transmitter)
+                .or_insert_with(|| AccessPoint {
+                    ssid: String::new(),
+                    channel: None,
+                    security,
+                    beacon_interval,
+                    beacons: 0,
+                    signal: SignalStats::default(),
+                });
+            if let Some(ssid) = ssid.filter(|s| !s.is_empty()) {
+                ap.ssid = ssid.to_string();
+            }
+            ap.channel = ds_channel(elements).or(channel).or(ap.channel);
+            ap.security = security;
+            ap.beacon_interval = beacon_interval;
+            if beacon {
+                ap.beacons += 1;
+            }
+            if let Some(dbm) = signal {
+                ap.signal.add(dbm);
+            }
+            return;
+        }
+
+        match frame.frame_body {
+            FrameBody::ProbeRequest(ref body) => {
+                let station = self.station(transmitter);
+                if let Some(ssid) = body.ssid().filter(|s| !s.is_empty()) {
+                    station.probed.insert(ssid.to_string());
+                }
+            }
+            FrameBody::AssociationResponse(ref body) if body.status_code.is_success() => {
+                let station = *frame.addr1.addr();
+                self.station(station).bssid = Some(transmitter);
+            }
+            FrameBody::Deauthentication(_) | FrameBody::Disassociation(_) => {
+                // Either side may end the association.
+                let receiver = *frame.addr1.addr();
+                for (station, bssid) in [(transmitter, receiver), (receiver, transmitter)].iter() {
+                    if let Some(station) = self.stations.get_mut(station) {
+                        if station.bssid.as_ref() == Some(bssid) {
+                            station.bssid = None;
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        if frame.fc.typ == Type::Data {
+            self.update_traffic(frame, transmitter, packet.len as u64);
+        }
+        if let Some(dbm) = signal {
+            if let Some(station) = self.stations.get_mut(&transmitter) {
+                station.signal.add(dbm);
+            }
+        }
+    }
+
+    /// Counts the data frames between the stations and access points of infrastructure BSSs.
+    fn update_traffic(&mut self, frame: &dot11::Frame, transmitter: Addr, len: u64) {
+        let set = u1::new(1);
+        let receiver = *frame.addr1.addr();
+        let flags = &frame.fc.flags;
+        if flags.to_ds == set && flags.from_ds != set {
+            let station = self.station(transmitter);
+            station.bssid = Some(receiver);
+            station.tx_frames += 1;
+            station.tx_bytes += len;
+        } else if flags.from_ds == set && flags.to_ds != set && !receiver.is_group() {
+            let station = self.station(receiver);
+            station.bssid = Some(transmitter);
+            station.rx_frames += 1;
+            station.rx_bytes += len;
+        }
+    }
+
+    fn station(&mut self, addr: Addr) -> &mut Station {
+        self.stations.entry(addr).or_default()
+    }
+
+    /// The number of stations associated to a BSS.
+    pub fn clients(&self, bssid: &Addr) -> usize {
+        self.stations
+            .values()
+            .filter(|s| s.bssid.as_ref() == Some(bssid))
+            .count()
+    }
+
+    pub fn access_point_headers() -> Vec<String> {
+        [
+            "BSSID", "SSID", "CH", "SECURITY", "SIGNAL", "BEACONS", "INTERVAL", "CLIENTS",
+        ]
+        .iter()
+        .map(|h| h.to_string())
+        .collect()
+    }
+
+    /// One row per access point, the strongest first.
+    pub fn access_point_rows(&self) -> Vec<Vec<String>> {
+        let mut access_points: Vec<_> = self.access_points.iter().collect();
+        access_points.sort_by_key(|(bssid, ap)| (Reverse(ap.signal.last), bssid.to_string()));
+        access_points
+            .into_iter()
+            .map(|(bssid, ap)| {
+                vec![
+                    bssid.to_string(),
+                    ap.ssid.clone(),
+                    ap.channel.map_or_else(String::new, |c| c.to_string()),
+                    ap.security.to_string(),
+                    ap.signal.describe(),
+                    ap.beacons.to_string(),
+                    format!("{} TU", ap.beacon_interval),
+                    self.clients(bssid).to_string(),
+                ]
+            })
+            .collect()
+    }
+
+    pub fn station_headers() -> Vec<String> {
+        ["STATION", "BSSID", "SIGNAL", "TX", "RX", "PROBES"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect()
+    }
+
+    /// One row per station, associated ones first, grouped by BSS.
+    pub fn station_rows(&self) -> Vec<Vec<String>> {
+        let mut stations: Vec<_> = self
+            .stations
+            .iter()
+            .filter(|(addr, _)| !self.access_points.contains_key(addr))
+            .collect();
+        stations.sort_by_key(|(addr, s)| {
+            (
+                s.bssid.is_none(),
+                s.bssid.map(|b| b.to_string()),
+                addr.to_string(),
+            )
+        });
+        stations
+            .into_iter()
+            .map(|(addr, s)| {
+                vec![
+                    addr.to_string(),
+                    s.bssid
+                        .map_or_else(|| "(not associated)".to_string(), |b| b.to_string()),
+                    s.signal.describe(),
+                    format!("{} / {} B", s.tx_frames, s.tx_bytes),
+                    format!("{} / {} B", s.rx_frames, s.rx_bytes),
+                    s.probed.iter().cloned().collect::<Vec<_>>().join(", "),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use netparse::layer2::datalink::CapturedPacket;
+    use std::time::Duration;
+
+    fn packet(mpdu: &[u8]) -> CapturedPacket {
+        let (_, frame) = dot11::Frame::parse(mpdu, false).unwrap();
+        CapturedPacket {
+            timestamp: Duration::from_secs(0),
+            caplen: mpdu.len() as u32,
+            len: mpdu.len() as u32,
+            interface: 0,
+            link_type: 105,
+            radiotap: None,
+            frame: Frame::Dot11(frame),
+        }
+    }
+
+    /// A management frame with a zero sequence control.
+    fn management(subtype: u8, da: &[u8], sa: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut mpdu = vec![subtype, 0x00, 0x00, 0x00];
+        mpdu.extend_from_slice(da);
+        mpdu.extend_from_slice(sa);
+        mpdu.extend_from_slice(if subtype == 0x40 { da } else { sa });
+        mpdu.extend_from_slice(&[0x00, 0x00]);
+        mpdu.extend_from_slice(body);
+        mpdu
+    }
+
+    /// The fixed fields of a beacon or probe response, every 100 TU, then `elements`.
+    fn bss_body(elements: &[u8]) -> Vec<u8> {
+        let mut body = vec![0; 8];
+        body.extend_from_slice(&[0x64, 0x00, 0x01, 0x00]);
+        body.extend_from_slice(elements);
+        body
+    }
+
+    #[test]
+    fn assert_survey() {
+        const AP: [u8; 6] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x01];
+        const STA: [u8; 6] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x02];
+        const HIDDEN: [u8; 6] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x03];
+        const BROADCAST: [u8; 6] = [0xFF; 6];
+
+        // A beacon for "home" on channel 6
+        let beacon = management(
+            0x80,
+            &BROADCAST,
+            &AP,
+            &bss_body(&[0, 4, b'h', b'o', b'm', b'e', 3, 1, 6]),
+        );
+
+        // A null data frame from the station to the access point
+        let mut data = vec![0x48, 0x01, 0x00, 0x00];
+        data.extend_from_slice(&AP);
+        data.extend_from_slice(&STA);
+        data.extend_from_slice(&AP);
+        data.extend_from_slice(&[0x10, 0x00]);
+
+        let mut survey = Survey::new();
+        survey.update(&packet(&beacon));
+        survey.update(&packet(&beacon));
+        survey.update(&packet(&data));
+
+        let rows = survey.access_point_rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(
+            rows[0],
+            [
+                "02:00:00:00:00:01",
+                "home",
+                "6",
+                "Open",
+                "",
+                "2",
+                "100 TU",
+                "1"
+            ]
+        );
+        let rows = survey.station_rows();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][..2], ["02:00:00:00:00:02", "02:00:00:00:00:01"]);
+        assert_eq!(rows[0][3], format!("1 / {} B", data.len()));
+
+        // A hidden network, whose name only a probe response gives away
+        for ssid in [&[0, 0][..], &[0, 3, 0, 0, 0][..]].iter() {
+            let beacon = management(0x80, &BROADCAST, &HIDDEN, &bss_body(ssid));
+            survey.update(&packet(&beacon));
+            assert_eq!(survey.access_point_rows()[1][1], "");
+        }
+        let response = management(
+            0x50,
+            &STA,
+            &HIDDEN,
+            &bss_body(&[0, 6, b's', b'e', b'c', b'r', b'e', b't']),
+        );
+        survey.update(&packet(&response));
+        let beacon = management(0x80, &BROADCAST, &HIDDEN, &bss_body(&[0, 0]));
+        survey.update(&packet(&beacon));
+        assert_eq!(survey.access_point_rows()[1][1], "secret");
+
+        // Wildcard probes name no network
+        let probe = management(0x40, &BROADCAST, &STA, &[0, 0, 1, 1, 0x82]);
+        survey.update(&packet(&probe));
+        assert_eq!(survey.station_rows()[0][5], "");
+        let probe = management(0x40, &BROADCAST, &STA, &[0, 4, b'w', b'o', b'r', b'k']);
+        survey.update(&packet(&probe));
+        assert_eq!(survey.station_rows()[0][5], "work");
+    }
+}
@@ -1,4 +1,5 @@
-use netparse::layer2::datalink::Frame;
+use crate::survey::Survey;
+use netparse::{filter::expr::Filter, layer2::datalink::CapturedPacket};
 use tui::{layout::Constraint, widgets::TableState};
 
 #[allow(dead_code)]
@@ -7,7 +8,17 @@ pub struct StatefulTable {
     pub headers: Vec<String>,
     pub widths: Vec<Constraint>,
     pub records: Vec<Vec<String>>,
-    pub frames: Vec<Frame>,
+    pub packets: Vec<CapturedPacket>,
+    /// Indices into `records`/`packets` of the rows passing the display filter.
+    pub visible: Vec<usize>,
+    pub filter: Option<Filter>,
+    pub filter_input: String,
+    pub filter_error: Option<String>,
+    pub editing: bool,
+    /// The access points and stations of the packets so far, for the wireless survey.
+    pub survey: Survey,
+    /// Whether the survey is shown in place of the packets.
+    pub show_survey: bool,
 }
 
 impl StatefulTable {
@@ -17,7 +28,14 @@ impl StatefulTable {
             headers: vec![],
             widths: vec![],
             records: vec![],
-            frames: vec![],
+            packets: vec![],
+            visible: vec![],
+            filter: None,
+            filter_input: String::new(),
+            filter_error: None,
+            editing: false,
+            survey: Survey::new(),
+            show_survey: false,
         }
     }
 
@@ -34,24 +52,78 @@ impl StatefulTable {
         }
     }
 
+    /// Stores a packet whose row has already been pushed, showing it if it passes the display filter.
+    pub fn push_packet(&mut self, packet: CapturedPacket) {
+        let index = self.packets.len();
+        if self
+            .filter
+            .as_ref()
+            .is_none_or(|f| f.matches(&packet.frame))
+        {
+            self.visible.push(index);
+        }
+        self.packets.push(packet);
+    }
+
+    /// Compiles the text typed in the filter bar and recomputes the visible rows.
+    /// An invalid filter leaves the previous one in place.
+    pub fn apply_filter(&mut self) {
+        let input = self.filter_input.trim();
+        let filter = if input.is_empty() {
+            None
+        } else {
+            match Filter::parse(input) {
+                Ok(filter) => Some(filter),
+                Err(e) => {
+                    self.filter_error = Some(e);
+                    return;
+                }
+            }
+        };
+        self.filter_error = None;
+        self.filter = filter;
+        let filter = &self.filter;
+        self.visible = self
+            .packets
+            .iter()
+            .enumerate()
+            .filter(|(_, packet)| filter.as_ref().is_none_or(|f| f.matches(&packet.frame)))
+            .map(|(i, _)| i)
+            .collect();
+        self.state.select(None);
+    }
+
+    pub fn visible_records(&self) -> Vec<Vec<String>> {
+        self.visible
+            .iter()
+            .filter_map(|i| self.records.get(*i).cloned())
+            .collect()
+    }
+
+    /// The index into `packets` of the selected row.
     pub fn get_selected(&self) -> Option<usize> {
-        self.state.selected()
+        self.state
+            .selected()
+            .and_then(|i| self.visible.get(i).cloned())
     }
 
     pub fn show_frame(&self) {
         if let Some(i) = self.get_selected() {
-            if let Some(frame) = self.frames.get(i) {}
+            if let Some(frame) = self.packets.get(i) {}
         }
     }
 
     pub fn next(&mut self, long: bool) {
+        if self.visible.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
-                if i >= self.records.len() - 1 {
+                if i >= self.visible.len() - 1 {
                     0
                 } else {
                     if long {
-                        i + 10
+                        (i + 10).min(self.visible.len() - 1)
                     } else {
                         i + 1
                     }
@@ -63,13 +135,16 @@ impl StatefulTable {
     }
 
     pub fn previous(&mut self, long: bool) {
+        if self.visible.is_empty() {
+            return;
+        }
         let i = match self.state.selected() {
             Some(i) => {
                 if i == 0 {
-                    self.records.len() - 1
+                    self.visible.len() - 1
                 } else {
                     if long {
-                        i - 10
+                        i.saturating_sub(10)
                     } else {
                         i - 1
                     }
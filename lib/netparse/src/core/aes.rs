@@ -0,0 +1,208 @@
+//! AES-128 (FIPS 197) and the AES key wrap (RFC 3394) that protects group keys in EAPOL-Key frames.
+
+const ROUNDS: usize = 10;
+
+fn xtime(b: u8) -> u8 {
+    (b << 1) ^ if b & 0x80 != 0 { 0x1B } else { 0 }
+}
+
+fn mul(mut a: u8, mut b: u8) -> u8 {
+    let mut res = 0;
+    while b != 0 {
+        if b & 1 != 0 {
+            res ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    res
+}
+
+/// The AES S-box: the affine transform of the multiplicative inverse in GF(2^8).
+#[rustfmt::skip]
+pub const SBOX: [u8; 256] = [
+    0x63, 0x7C, 0x77, 0x7B, 0xF2, 0x6B, 0x6F, 0xC5, 0x30, 0x01, 0x67, 0x2B, 0xFE, 0xD7, 0xAB, 0x76,
+    0xCA, 0x82, 0xC9, 0x7D, 0xFA, 0x59, 0x47, 0xF0, 0xAD, 0xD4, 0xA2, 0xAF, 0x9C, 0xA4, 0x72, 0xC0,
+    0xB7, 0xFD, 0x93, 0x26, 0x36, 0x3F, 0xF7, 0xCC, 0x34, 0xA5, 0xE5, 0xF1, 0x71, 0xD8, 0x31, 0x15,
+    0x04, 0xC7, 0x23, 0xC3, 0x18, 0x96, 0x05, 0x9A, 0x07, 0x12, 0x80, 0xE2, 0xEB, 0x27, 0xB2, 0x75,
+    0x09, 0x83, 0x2C, 0x1A, 0x1B, 0x6E, 0x5A, 0xA0, 0x52, 0x3B, 0xD6, 0xB3, 0x29, 0xE3, 0x2F, 0x84,
+    0x53, 0xD1, 0x00, 0xED, 0x20, 0xFC, 0xB1, 0x5B, 0x6A, 0xCB, 0xBE, 0x39, 0x4A, 0x4C, 0x58, 0xCF,
+    0xD0, 0xEF, 0xAA, 0xFB, 0x43, 0x4D, 0x33, 0x85, 0x45, 0xF9, 0x02, 0x7F, 0x50, 0x3C, 0x9F, 0xA8,
+    0x51, 0xA3, 0x40, 0x8F, 0x92, 0x9D, 0x38, 0xF5, 0xBC, 0xB6, 0xDA, 0x21, 0x10, 0xFF, 0xF3, 0xD2,
+    0xCD, 0x0C, 0x13, 0xEC, 0x5F, 0x97, 0x44, 0x17, 0xC4, 0xA7, 0x7E, 0x3D, 0x64, 0x5D, 0x19, 0x73,
+    0x60, 0x81, 0x4F, 0xDC, 0x22, 0x2A, 0x90, 0x88, 0x46, 0xEE, 0xB8, 0x14, 0xDE, 0x5E, 0x0B, 0xDB,
+    0xE0, 0x32, 0x3A, 0x0A, 0x49, 0x06, 0x24, 0x5C, 0xC2, 0xD3, 0xAC, 0x62, 0x91, 0x95, 0xE4, 0x79,
+    0xE7, 0xC8, 0x37, 0x6D, 0x8D, 0xD5, 0x4E, 0xA9, 0x6C, 0x56, 0xF4, 0xEA, 0x65, 0x7A, 0xAE, 0x08,
+    0xBA, 0x78, 0x25, 0x2E, 0x1C, 0xA6, 0xB4, 0xC6, 0xE8, 0xDD, 0x74, 0x1F, 0x4B, 0xBD, 0x8B, 0x8A,
+    0x70, 0x3E, 0xB5, 0x66, 0x48, 0x03, 0xF6, 0x0E, 0x61, 0x35, 0x57, 0xB9, 0x86, 0xC1, 0x1D, 0x9E,
+    0xE1, 0xF8, 0x98, 0x11, 0x69, 0xD9, 0x8E, 0x94, 0x9B, 0x1E, 0x87, 0xE9, 0xCE, 0x55, 0x28, 0xDF,
+    0x8C, 0xA1, 0x89, 0x0D, 0xBF, 0xE6, 0x42, 0x68, 0x41, 0x99, 0x2D, 0x0F, 0xB0, 0x54, 0xBB, 0x16,
+];
+
+pub struct Aes128 {
+    round_keys: [[u8; 16]; ROUNDS + 1],
+    inverse_sbox: [u8; 256],
+}
+
+impl Aes128 {
+    pub fn new(key: &[u8; 16]) -> Self {
+        let mut inverse_sbox = [0u8; 256];
+        for (i, s) in SBOX.iter().enumerate() {
+            inverse_sbox[*s as usize] = i as u8;
+        }
+
+        let mut round_keys = [[0u8; 16]; ROUNDS + 1];
+        round_keys[0] = *key;
+        let mut rcon = 1u8;
+        for round in 1..=ROUNDS {
+            let previous = round_keys[round - 1];
+            let mut word = [previous[13], previous[14], previous[15], previous[12]];
+            for b in word.iter_mut() {
+                *b = SBOX[*b as usize];
+            }
+            word[0] ^= rcon;
+            rcon = xtime(rcon);
+            let key = &mut round_keys[round];
+            for i in 0..16 {
+                let w = if i < 4 { word[i] } else { key[i - 4] };
+                key[i] = previous[i] ^ w;
+            }
+        }
+
+        Self {
+            round_keys,
+            inverse_sbox,
+        }
+    }
+
+    fn add_round_key(&self, state: &mut [u8; 16], round: usize) {
+        for (s, k) in state.iter_mut().zip(self.round_keys[round].iter()) {
+            *s ^= k;
+        }
+    }
+
+    /// The state is stored column by column, so row `r` of column `c` is at `4 * c + r`.
+    fn shift_rows(state: &mut [u8; 16], inverse: bool) {
+        let copy = *state;
+        for c in 0..4 {
+            for r in 1..4 {
+                let from = if inverse {
+                    (c + 4 - r) % 4
+                } else {
+                    (c + r) % 4
+                };
+                state[4 * c + r] = copy[4 * from + r];
+            }
+        }
+    }
+
+    fn mix_columns(state: &mut [u8; 16], coefficients: [u8; 4]) {
+        for column in state.chunks_mut(4) {
+            let copy = [column[0], column[1], column[2], column[3]];
+            for r in 0..4 {
+                column[r] = (0..4).fold(0, |acc, i| acc ^ mul(copy[(r + i) % 4], coefficients[i]));
+            }
+        }
+    }
+
+    pub fn encrypt_block(&self, block: &mut [u8; 16]) {
+        self.add_round_key(block, 0);
+        for round in 1..=ROUNDS {
+            for b in block.iter_mut() {
+                *b = SBOX[*b as usize];
+            }
+            Self::shift_rows(block, false);
+            if round != ROUNDS {
+                Self::mix_columns(block, [2, 3, 1, 1]);
+            }
+            self.add_round_key(block, round);
+        }
+    }
+
+    pub fn decrypt_block(&self, block: &mut [u8; 16]) {
+        self.add_round_key(block, ROUNDS);
+        for round in (0..ROUNDS).rev() {
+            Self::shift_rows(block, true);
+            for b in block.iter_mut() {
+                *b = self.inverse_sbox[*b as usize];
+            }
+            self.add_round_key(block, round);
+            if round != 0 {
+                Self::mix_columns(block, [14, 11, 13, 9]);
+            }
+        }
+    }
+}
+
+/// Unwraps a key wrapped with `kek`, or returns None if its integrity check fails.
+pub fn key_unwrap(kek: &[u8; 16], wrapped: &[u8]) -> Option<Vec<u8>> {
+    if wrapped.len() < 24 || !wrapped.len().is_multiple_of(8) {
+        return None;
+    }
+    let aes = Aes128::new(kek);
+    let n = wrapped.len() / 8 - 1;
+    let mut a = [0u8; 8];
+    a.copy_from_slice(&wrapped[..8]);
+    let mut r: Vec<[u8; 8]> = wrapped[8..]
+        .chunks(8)
+        .map(|c| [c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]])
+        .collect();
+
+    for j in (0..6).rev() {
+        for i in (0..n).rev() {
+            let t = (n * j + i + 1) as u64;
+            let mut block = [0u8; 16];
+            for (k, b) in t.to_be_bytes().iter().enumerate() {
+                block[k] = a[k] ^ b;
+            }
+            block[8..].copy_from_slice(&r[i]);
+            aes.decrypt_block(&mut block);
+            a.copy_from_slice(&block[..8]);
+            r[i].copy_from_slice(&block[8..]);
+        }
+    }
+
+    if a == [0xA6; 8] {
+        Some(r.concat())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_vectors() {
+        // FIPS 197, C.1
+        let key = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D,
+            0x0E, 0x0F,
+        ];
+        let plaintext = [
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xAA, 0xBB, 0xCC, 0xDD,
+            0xEE, 0xFF,
+        ];
+        let aes = Aes128::new(&key);
+        let mut block = plaintext;
+        aes.encrypt_block(&mut block);
+        assert_eq!(
+            block,
+            [
+                0x69, 0xC4, 0xE0, 0xD8, 0x6A, 0x7B, 0x04, 0x30, 0xD8, 0xCD, 0xB7, 0x80, 0x70, 0xB4,
+                0xC5, 0x5A
+            ]
+        );
+        aes.decrypt_block(&mut block);
+        assert_eq!(block, plaintext);
+
+        // RFC 3394, 4.1
+        let wrapped = [
+            0x1F, 0xA6, 0x8B, 0x0A, 0x81, 0x12, 0xB4, 0x47, 0xAE, 0xF3, 0x4B, 0xD8, 0xFB, 0x5A,
+            0x7B, 0x82, 0x9D, 0x3E, 0x86, 0x23, 0x71, 0xD2, 0xCF, 0xE5,
+        ];
+        assert_eq!(key_unwrap(&key, &wrapped), Some(plaintext.to_vec()));
+        assert_eq!(key_unwrap(&plaintext, &wrapped), None);
+    }
+}
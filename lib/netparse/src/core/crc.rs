@@ -0,0 +1,26 @@
+/// Reflected polynomial of the CRC-32 used by Ethernet and 802.11 frame check sequences.
+const POLYNOMIAL: u32 = 0xEDB8_8320;
+
+/// Computes the IEEE 802.3 CRC-32 of `data`, as stored (little endian) in an FCS.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (POLYNOMIAL & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(&[]), 0);
+    }
+}
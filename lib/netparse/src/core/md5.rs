@@ -0,0 +1,89 @@
+//! MD5 and HMAC-MD5, which protect the EAPOL-Key frames of WPA networks using TKIP.
+
+const BLOCK_SIZE: usize = 64;
+
+const SHIFTS: [u32; 16] = [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+
+pub fn md5(data: &[u8]) -> [u8; 16] {
+    let k: Vec<u32> = (0..64)
+        .map(|i| ((i as f64 + 1.0).sin().abs() * 4_294_967_296.0) as u32)
+        .collect();
+    let mut h: [u32; 4] = [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % BLOCK_SIZE != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_le_bytes());
+
+    for block in message.chunks(BLOCK_SIZE) {
+        let mut m = [0u32; 16];
+        for (i, word) in block.chunks(4).enumerate() {
+            m[i] = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+        }
+
+        let [mut a, mut b, mut c, mut d] = h;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let shift = SHIFTS[i / 16 * 4 + i % 4];
+            let f = f.wrapping_add(a).wrapping_add(k[i]).wrapping_add(m[g]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(f.rotate_left(shift));
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d].iter()) {
+            *h = h.wrapping_add(*v);
+        }
+    }
+
+    let mut res = [0u8; 16];
+    for (chunk, h) in res.chunks_mut(4).zip(h.iter()) {
+        chunk.copy_from_slice(&h.to_le_bytes());
+    }
+    res
+}
+
+pub fn hmac_md5(key: &[u8], data: &[u8]) -> [u8; 16] {
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..16].copy_from_slice(&md5(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner: Vec<u8> = block.iter().map(|b| b ^ 0x36).collect();
+    inner.extend_from_slice(data);
+    let mut outer: Vec<u8> = block.iter().map(|b| b ^ 0x5C).collect();
+    outer.extend_from_slice(&md5(&inner));
+    md5(&outer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_vectors() {
+        assert_eq!(
+            md5(b"abc"),
+            [
+                0x90, 0x01, 0x50, 0x98, 0x3C, 0xD2, 0x4F, 0xB0, 0xD6, 0x96, 0x3F, 0x7D, 0x28, 0xE1,
+                0x7F, 0x72
+            ]
+        );
+        // RFC 2104
+        assert_eq!(
+            hmac_md5(b"Jefe", b"what do ya want for nothing?"),
+            [
+                0x75, 0x0C, 0x78, 0x3E, 0x6A, 0xB0, 0xB5, 0x03, 0xEA, 0xA8, 0x6E, 0x31, 0x0A, 0x5D,
+                0xB7, 0x38
+            ]
+        );
+    }
+}
@@ -0,0 +1,142 @@
+//! SHA-1 and the HMAC, PBKDF2 and PRF built on it, as used to derive WPA2 keys.
+
+const BLOCK_SIZE: usize = 64;
+
+pub fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [
+        0x6745_2301,
+        0xEFCD_AB89,
+        0x98BA_DCFE,
+        0x1032_5476,
+        0xC3D2_E1F0,
+    ];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % BLOCK_SIZE != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(BLOCK_SIZE) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, w) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ED9_EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+                _ => (b ^ c ^ d, 0xCA62_C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*w);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e].iter()) {
+            *h = h.wrapping_add(*v);
+        }
+    }
+
+    let mut res = [0u8; 20];
+    for (chunk, h) in res.chunks_mut(4).zip(h.iter()) {
+        chunk.copy_from_slice(&h.to_be_bytes());
+    }
+    res
+}
+
+/// HMAC (RFC 2104) over the concatenation of `parts`.
+pub fn hmac_sha1(key: &[u8], parts: &[&[u8]]) -> [u8; 20] {
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..20].copy_from_slice(&sha1(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner: Vec<u8> = block.iter().map(|b| b ^ 0x36).collect();
+    for part in parts {
+        inner.extend_from_slice(part);
+    }
+    let mut outer: Vec<u8> = block.iter().map(|b| b ^ 0x5C).collect();
+    outer.extend_from_slice(&sha1(&inner));
+    sha1(&outer)
+}
+
+/// PBKDF2 (RFC 2898) with HMAC-SHA1, filling `out`.
+pub fn pbkdf2_sha1(password: &[u8], salt: &[u8], iterations: u32, out: &mut [u8]) {
+    for (i, chunk) in out.chunks_mut(20).enumerate() {
+        let index = (i as u32 + 1).to_be_bytes();
+        let mut u = hmac_sha1(password, &[salt, &index]);
+        let mut t = u;
+        for _ in 1..iterations {
+            u = hmac_sha1(password, &[&u]);
+            for (t, u) in t.iter_mut().zip(u.iter()) {
+                *t ^= u;
+            }
+        }
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+}
+
+/// The pseudo-random function of IEEE 802.11 (12.7.1.2), filling `out`.
+pub fn prf(key: &[u8], label: &[u8], data: &[u8], out: &mut [u8]) {
+    for (i, chunk) in out.chunks_mut(20).enumerate() {
+        let digest = hmac_sha1(key, &[label, &[0], data, &[i as u8]]);
+        chunk.copy_from_slice(&digest[..chunk.len()]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn assert_vectors() {
+        assert_eq!(
+            sha1(b"abc").to_vec(),
+            hex("a9993e364706816aba3e25717850c26c9cd0d89d")
+        );
+        assert_eq!(
+            hmac_sha1(&[0x0B; 20], &[b"Hi There"]).to_vec(),
+            hex("b617318655057264e28bc0b6fb378c8ef146be00")
+        );
+        // IEEE 802.11 J.4: the PSK of passphrase "password" on SSID "IEEE".
+        let mut psk = [0u8; 32];
+        pbkdf2_sha1(b"password", b"IEEE", 4096, &mut psk);
+        assert_eq!(
+            psk.to_vec(),
+            hex("f42c6fc52df0ebef9ebb4b90b38a5f902e83fe1b135a70e23aed762e9710a12e")
+        );
+        // IEEE 802.11 J.3, PRF test case 1.
+        let mut out = [0u8; 64];
+        prf(&[0x0B; 20], b"prefix", b"Hi There", &mut out);
+        assert_eq!(
+            out.to_vec(),
+            hex(
+                "bcd4c650b30b9684951829e0d75f9d54b862175ed9f00606e17d8da35402ffee\
+                 75df78c3d31e0f889f012120c0862beb67753e7439ae242edb8373698356cf5a"
+            )
+        );
+    }
+}
@@ -42,6 +42,12 @@ macro_rules! implement_common {
 
         impl Eq for $name {}
 
+        impl From<$name> for $type {
+            fn from(x: $name) -> $type {
+                x.mask().0
+            }
+        }
+
         impl PartialOrd for $name {
             fn partial_cmp(&self, other: &$name) -> Option<Ordering> {
                 self.mask().0.partial_cmp(&other.mask().0)
@@ -0,0 +1,510 @@
+use crate::{
+    core::parse::{Error, ErrorKind},
+    filter::field::{Field, Kind, Value},
+    layer2::datalink::Frame,
+};
+
+use nom::{
+    branch::alt,
+    bytes::complete::{escaped_transform, is_not, tag, take_while1},
+    character::complete::{char, multispace0},
+    combinator::{all_consuming, cut, map, opt},
+    multi::many0,
+    sequence::{delimited, preceded},
+};
+use regex::bytes::Regex;
+use std::{
+    fmt,
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+type FilterResult<'a, T> = nom::IResult<&'a str, T, Error<&'a str>>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// A constant on the right hand side of a test, already converted to the type of its field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Int(u64),
+    IPv4 { addr: u32, prefix: u8 },
+    IPv6 { addr: u128, prefix: u8 },
+    Bytes(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SetItem {
+    Literal(Literal),
+    /// An inclusive range of integers, written as `low..high`.
+    Range(u64, u64),
+}
+
+#[derive(Debug)]
+pub enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Exists(Field),
+    Compare(Field, CmpOp, Literal),
+    In(Field, Vec<SetItem>),
+    Contains(Field, Vec<u8>),
+    Matches(Field, Regex),
+}
+
+/// A compiled display filter such as `ip.src == 10.0.0.0/8 && tcp.dstport in {80 443} && !arp`.
+pub struct Filter {
+    pub text: String,
+    pub expr: Expr,
+}
+
+impl fmt::Debug for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+fn failure<T>(i: &str, msg: String) -> FilterResult<'_, T> {
+    Err(nom::Err::Failure(Error::custom(i, msg)))
+}
+
+fn ws<'a, T, F>(f: F) -> impl Fn(&'a str) -> FilterResult<'a, T>
+where
+    F: Fn(&'a str) -> FilterResult<'a, T>,
+{
+    delimited(multispace0, f, multispace0)
+}
+
+fn identifier(i: &str) -> FilterResult<'_, &str> {
+    ws(take_while1(|c: char| {
+        c.is_ascii_alphanumeric() || c == '_' || c == '.'
+    }))(i)
+}
+
+fn keyword<'a>(kw: &'static str) -> impl Fn(&'a str) -> FilterResult<'a, &'a str> {
+    move |i| {
+        let (rest, word) = identifier(i)?;
+        if word == kw {
+            Ok((rest, word))
+        } else {
+            Err(nom::Err::Error(Error::custom(
+                i,
+                format!("expected '{}'", kw),
+            )))
+        }
+    }
+}
+
+fn quoted(i: &str) -> FilterResult<'_, String> {
+    ws(delimited(
+        char('"'),
+        map(
+            opt(escaped_transform(
+                is_not("\\\""),
+                '\\',
+                alt((
+                    map(char('\\'), |_| "\\"),
+                    map(char('"'), |_| "\""),
+                    map(char('n'), |_| "\n"),
+                    map(char('t'), |_| "\t"),
+                )),
+            )),
+            Option::unwrap_or_default,
+        ),
+        char('"'),
+    ))(i)
+}
+
+fn bare(i: &str) -> FilterResult<'_, &str> {
+    ws(take_while1(|c: char| {
+        c.is_ascii_alphanumeric() || c == ':' || c == '.' || c == '/' || c == '-' || c == '_'
+    }))(i)
+}
+
+fn parse_int(s: &str) -> Option<u64> {
+    if s.starts_with("0x") || s.starts_with("0X") {
+        u64::from_str_radix(&s[2..], 16).ok()
+    } else {
+        s.parse().ok()
+    }
+}
+
+fn parse_prefix(s: &str, max: u8) -> Option<(&str, u8)> {
+    let mut parts = s.splitn(2, '/');
+    let addr = parts.next()?;
+    match parts.next() {
+        Some(prefix) => prefix
+            .parse::<u8>()
+            .ok()
+            .filter(|p| *p <= max)
+            .map(|p| (addr, p)),
+        None => Some((addr, max)),
+    }
+}
+
+fn parse_bytes(s: &str) -> Option<Vec<u8>> {
+    s.split([':', '-', '.'])
+        .map(|b| {
+            if b.len() == 2 {
+                u8::from_str_radix(b, 16).ok()
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn convert(kind: Kind, s: &str) -> Option<Literal> {
+    match kind {
+        Kind::Int => parse_int(s).map(Literal::Int),
+        Kind::IPv4 => parse_prefix(s, 32).and_then(|(addr, prefix)| {
+            Ipv4Addr::from_str(addr).ok().map(|addr| Literal::IPv4 {
+                addr: u32::from(addr),
+                prefix,
+            })
+        }),
+        Kind::IPv6 => parse_prefix(s, 128).and_then(|(addr, prefix)| {
+            Ipv6Addr::from_str(addr).ok().map(|addr| Literal::IPv6 {
+                addr: u128::from(addr),
+                prefix,
+            })
+        }),
+        Kind::Ether => parse_bytes(s).filter(|b| b.len() == 6).map(Literal::Bytes),
+        Kind::Bytes => parse_bytes(s).map(Literal::Bytes),
+        Kind::Protocol => None,
+    }
+}
+
+fn literal(field: Field, kind: Kind) -> impl Fn(&str) -> FilterResult<'_, Literal> {
+    move |i| {
+        if kind == Kind::Protocol {
+            return failure(
+                i,
+                format!("'{}' can only be tested for presence", field.name()),
+            );
+        }
+        if let Ok((rest, s)) = quoted(i) {
+            return match kind {
+                Kind::Bytes => Ok((rest, Literal::Bytes(s.into_bytes()))),
+                _ => failure(i, format!("'{}' does not take a string", field.name())),
+            };
+        }
+        let (rest, s) = bare(i)?;
+        match convert(kind, s) {
+            Some(literal) => Ok((rest, literal)),
+            None => failure(
+                i,
+                format!("'{}' is not a valid value for '{}'", s, field.name()),
+            ),
+        }
+    }
+}
+
+fn bytes_literal(field: Field, kind: Kind) -> impl Fn(&str) -> FilterResult<'_, Vec<u8>> {
+    move |i| match kind {
+        Kind::Bytes | Kind::Ether => match literal(field, kind)(i)? {
+            (rest, Literal::Bytes(b)) => Ok((rest, b)),
+            _ => failure(i, format!("'{}' is not a byte sequence", field.name())),
+        },
+        _ => failure(i, format!("'{}' does not support 'contains'", field.name())),
+    }
+}
+
+fn set_item(field: Field, kind: Kind) -> impl Fn(&str) -> FilterResult<'_, SetItem> {
+    move |i| {
+        if kind == Kind::Int {
+            if let Ok((rest, s)) = bare(i) {
+                let mut parts = s.splitn(2, "..");
+                if let (Some(low), Some(high)) = (parts.next(), parts.next()) {
+                    return match (parse_int(low), parse_int(high)) {
+                        (Some(low), Some(high)) if low <= high => {
+                            Ok((rest, SetItem::Range(low, high)))
+                        }
+                        _ => failure(i, format!("'{}' is not a valid range", s)),
+                    };
+                }
+            }
+        }
+        map(literal(field, kind), SetItem::Literal)(i)
+    }
+}
+
+fn cmp_op(i: &str) -> FilterResult<'_, CmpOp> {
+    ws(alt((
+        map(tag("=="), |_| CmpOp::Eq),
+        map(tag("!="), |_| CmpOp::Ne),
+        map(tag("<="), |_| CmpOp::Le),
+        map(tag(">="), |_| CmpOp::Ge),
+        map(tag("<"), |_| CmpOp::Lt),
+        map(tag(">"), |_| CmpOp::Gt),
+        map(keyword("eq"), |_| CmpOp::Eq),
+        map(keyword("ne"), |_| CmpOp::Ne),
+        map(keyword("le"), |_| CmpOp::Le),
+        map(keyword("ge"), |_| CmpOp::Ge),
+        map(keyword("lt"), |_| CmpOp::Lt),
+        map(keyword("gt"), |_| CmpOp::Gt),
+    )))(i)
+}
+
+fn parse_test(i: &str) -> FilterResult<'_, Expr> {
+    let (i, name) = identifier(i)?;
+    let (field, kind) = match Field::from_name(name) {
+        Some(field) => field,
+        None => return failure(i, format!("unknown field '{}'", name)),
+    };
+
+    if let Ok((i, op)) = cmp_op(i) {
+        let (i, literal) = cut(literal(field, kind))(i)?;
+        return Ok((i, Expr::Compare(field, op, literal)));
+    }
+    if let Ok((i, _)) = keyword("in")(i) {
+        let (i, items) = cut(delimited(
+            ws(char('{')),
+            many0(set_item(field, kind)),
+            ws(char('}')),
+        ))(i)?;
+        return Ok((i, Expr::In(field, items)));
+    }
+    if let Ok((i, _)) = keyword("contains")(i) {
+        let (i, bytes) = cut(bytes_literal(field, kind))(i)?;
+        return Ok((i, Expr::Contains(field, bytes)));
+    }
+    if let Ok((rest, _)) = keyword("matches")(i) {
+        if kind != Kind::Bytes {
+            return failure(i, format!("'{}' does not support 'matches'", field.name()));
+        }
+        let (rest, pattern) = cut(quoted)(rest)?;
+        return match Regex::new(&pattern) {
+            Ok(re) => Ok((rest, Expr::Matches(field, re))),
+            Err(e) => failure(i, format!("invalid regular expression: {}", e)),
+        };
+    }
+
+    Ok((i, Expr::Exists(field)))
+}
+
+fn parse_primary(i: &str) -> FilterResult<'_, Expr> {
+    alt((
+        delimited(ws(char('(')), parse_or, cut(ws(char(')')))),
+        parse_test,
+    ))(i)
+}
+
+fn parse_not(i: &str) -> FilterResult<'_, Expr> {
+    alt((
+        map(
+            preceded(alt((ws(tag("!")), keyword("not"))), cut(parse_not)),
+            |e| Expr::Not(Box::new(e)),
+        ),
+        parse_primary,
+    ))(i)
+}
+
+fn parse_and(i: &str) -> FilterResult<'_, Expr> {
+    let (i, first) = parse_not(i)?;
+    let (i, rest) = many0(preceded(
+        alt((ws(tag("&&")), keyword("and"))),
+        cut(parse_not),
+    ))(i)?;
+    let res = rest
+        .into_iter()
+        .fold(first, |acc, e| Expr::And(Box::new(acc), Box::new(e)));
+    Ok((i, res))
+}
+
+fn parse_or(i: &str) -> FilterResult<'_, Expr> {
+    let (i, first) = parse_and(i)?;
+    let (i, rest) = many0(preceded(
+        alt((ws(tag("||")), keyword("or"))),
+        cut(parse_and),
+    ))(i)?;
+    let res = rest
+        .into_iter()
+        .fold(first, |acc, e| Expr::Or(Box::new(acc), Box::new(e)));
+    Ok((i, res))
+}
+
+fn mask(prefix: u8, bits: u8) -> u128 {
+    if prefix == 0 {
+        0
+    } else {
+        (!0u128) << (bits - prefix)
+    }
+}
+
+impl CmpOp {
+    fn apply<T: PartialOrd>(self, a: T, b: T) -> bool {
+        match self {
+            CmpOp::Eq => a == b,
+            CmpOp::Ne => a != b,
+            CmpOp::Lt => a < b,
+            CmpOp::Le => a <= b,
+            CmpOp::Gt => a > b,
+            CmpOp::Ge => a >= b,
+        }
+    }
+}
+
+impl Literal {
+    /// Compares a field value against this literal.
+    /// Address literals with a prefix length match any address within the subnet.
+    fn compare(&self, op: CmpOp, value: &Value) -> bool {
+        match (value, self) {
+            (Value::Int(v), Literal::Int(l)) => op.apply(v, l),
+            (Value::IPv4(v), Literal::IPv4 { addr, prefix }) => match op {
+                CmpOp::Eq | CmpOp::Ne => {
+                    let mask = mask(*prefix, 32) as u32;
+                    op.apply(v & mask, addr & mask)
+                }
+                _ => op.apply(v, addr),
+            },
+            (Value::IPv6(v), Literal::IPv6 { addr, prefix }) => match op {
+                CmpOp::Eq | CmpOp::Ne => {
+                    let mask = mask(*prefix, 128);
+                    op.apply(v & mask, addr & mask)
+                }
+                _ => op.apply(v, addr),
+            },
+            (Value::Bytes(v), Literal::Bytes(l)) => op.apply(*v, &l[..]),
+            _ => false,
+        }
+    }
+}
+
+impl SetItem {
+    fn contains(&self, value: &Value) -> bool {
+        match (self, value) {
+            (SetItem::Range(low, high), Value::Int(v)) => low <= v && v <= high,
+            (SetItem::Literal(l), value) => l.compare(CmpOp::Eq, value),
+            _ => false,
+        }
+    }
+}
+
+impl Expr {
+    pub fn matches(&self, frame: &Frame) -> bool {
+        match self {
+            Expr::Or(a, b) => a.matches(frame) || b.matches(frame),
+            Expr::And(a, b) => a.matches(frame) && b.matches(frame),
+            Expr::Not(e) => !e.matches(frame),
+            Expr::Exists(field) => !field.values(frame).is_empty(),
+            // Like Wireshark, `a != b` is the negation of `a == b`, so it holds only when no occurrence is equal.
+            Expr::Compare(field, CmpOp::Ne, literal) => !field
+                .values(frame)
+                .iter()
+                .any(|v| literal.compare(CmpOp::Eq, v)),
+            Expr::Compare(field, op, literal) => {
+                field.values(frame).iter().any(|v| literal.compare(*op, v))
+            }
+            Expr::In(field, items) => field
+                .values(frame)
+                .iter()
+                .any(|v| items.iter().any(|item| item.contains(v))),
+            Expr::Contains(field, needle) => field.values(frame).iter().any(|v| match v {
+                Value::Bytes(haystack) => {
+                    needle.is_empty() || haystack.windows(needle.len()).any(|w| w == &needle[..])
+                }
+                _ => false,
+            }),
+            Expr::Matches(field, re) => field.values(frame).iter().any(|v| match v {
+                Value::Bytes(haystack) => re.is_match(haystack),
+                _ => false,
+            }),
+        }
+    }
+}
+
+impl Filter {
+    /// Compiles a display filter, returning a message pointing at the offending column on failure.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match all_consuming(parse_or)(text) {
+            Ok((_, expr)) => Ok(Self {
+                text: text.to_string(),
+                expr,
+            }),
+            Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => {
+                let custom = e.errors.iter().find_map(|(i, kind)| match kind {
+                    ErrorKind::Custom(msg) => Some((*i, msg.clone())),
+                    _ => None,
+                });
+                let (i, msg) = match custom {
+                    Some(custom) => custom,
+                    None => match e.errors.first() {
+                        Some((i, _)) => (*i, "syntax error".to_string()),
+                        None => (text, "syntax error".to_string()),
+                    },
+                };
+                let column = text.len() - i.len() + 1;
+                Err(format!("column {}: {}", column, msg))
+            }
+            Err(nom::Err::Incomplete(_)) => Err("incomplete filter".to_string()),
+        }
+    }
+
+    pub fn matches(&self, frame: &Frame) -> bool {
+        self.expr.matches(frame)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer2::ethernet;
+
+    /// An mDNS query from 192.168.1.67:5353 to 224.0.0.251:5353.
+    const TEST_FRAME: &[u8] = &[
+        0x01, 0x00, 0x5E, 0x00, 0x00, 0xFB, 0x58, 0x00, 0xE3, 0x1D, 0x1E, 0x6B, 0x08, 0x00, 0x45,
+        0x00, 0x00, 0x3D, 0x62, 0xB8, 0x00, 0x00, 0x01, 0x11, 0xB4, 0x11, 0xC0, 0xA8, 0x01, 0x43,
+        0xE0, 0x00, 0x00, 0xFB, 0x14, 0xE9, 0x14, 0xE9, 0x00, 0x29, 0xAE, 0x6D, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x04, 0x5F, 0x69, 0x70, 0x70, 0x04,
+        0x5F, 0x74, 0x63, 0x70, 0x05, 0x6C, 0x6F, 0x63, 0x61, 0x6C, 0x00, 0x00, 0x0C, 0x80, 0x01,
+    ];
+
+    fn matches(filter: &str) -> bool {
+        let frame = Frame::Ethernet(ethernet::Frame::parse(TEST_FRAME).unwrap().1);
+        Filter::parse(filter).unwrap().matches(&frame)
+    }
+
+    #[test]
+    fn assert_field_comparisons() {
+        assert!(matches(
+            "ip.src == 192.168.0.0/16 && udp.dstport in {53 5353}"
+        ));
+        assert!(matches("eth.src == 58:00:e3:1d:1e:6b and ip.ttl < 2"));
+        assert!(matches("udp.port in {5000..6000} && !arp && !tcp"));
+        assert!(!matches("ip.addr != 224.0.0.251"));
+        assert!(!matches("ip.src == 10.0.0.0/8 || tcp"));
+    }
+
+    #[test]
+    fn assert_payload_operators() {
+        assert!(matches("udp.payload contains \"_ipp\""));
+        assert!(matches("udp.payload contains 05:6c:6f:63:61:6c"));
+        assert!(matches("udp.payload matches \"_tcp.local\""));
+        assert!(!matches("udp.payload matches \"^GET\""));
+    }
+
+    #[test]
+    fn assert_invalid_filters() {
+        assert!(Filter::parse("ip.source == 1.2.3.4").is_err());
+        assert!(Filter::parse("ip.src == 1.2.3").is_err());
+        assert!(Filter::parse("tcp == 1").is_err());
+        assert!(Filter::parse("(udp").is_err());
+        assert!(Filter::parse("udp &&").is_err());
+    }
+}
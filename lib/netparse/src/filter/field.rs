@@ -0,0 +1,375 @@
+use crate::{
+    core::ux::u1,
+    layer2::{
+        datalink::{EtherType, Frame, Payload},
+        wifi::dot11::Dot11Addr,
+    },
+    layer3::{
+        icmp,
+        ip::{ip, tcp, udp},
+    },
+};
+
+/// The type of the values a field produces, which decides how literals compared against it are read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    /// A protocol name such as `tcp`, which can only be tested for presence.
+    Protocol,
+    Int,
+    IPv4,
+    IPv6,
+    Ether,
+    Bytes,
+}
+
+/// A single value extracted from a frame.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value<'a> {
+    Protocol,
+    Int(u64),
+    IPv4(u32),
+    IPv6(u128),
+    Bytes(&'a [u8]),
+}
+
+/// A named field that can be referenced from a display filter, e.g. `ip.src` or `tcp.port`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Eth,
+    EthSrc,
+    EthDst,
+    EthAddr,
+    EthType,
+    Wlan,
+    WlanRa,
+    WlanTa,
+    WlanSa,
+    WlanDa,
+    WlanBssid,
+    WlanAddr,
+    Arp,
+    ArpOpcode,
+    ArpSrcHw,
+    ArpDstHw,
+    ArpSrcIp,
+    ArpDstIp,
+    Ip,
+    IpSrc,
+    IpDst,
+    IpAddr,
+    IpTtl,
+    IpProto,
+    IpLen,
+    IpId,
+    IPv6,
+    IPv6Src,
+    IPv6Dst,
+    IPv6Addr,
+    IPv6Hlim,
+    IPv6Nxt,
+    IPv6Plen,
+    Tcp,
+    TcpSrcPort,
+    TcpDstPort,
+    TcpPort,
+    TcpSeq,
+    TcpAck,
+    TcpWindowSize,
+    TcpFlagSyn,
+    TcpFlagAck,
+    TcpFlagFin,
+    TcpFlagReset,
+    TcpFlagPush,
+    TcpFlagUrg,
+    TcpPayload,
+    Udp,
+    UdpSrcPort,
+    UdpDstPort,
+    UdpPort,
+    UdpLength,
+    UdpPayload,
+    Icmp,
+    IcmpType,
+    IcmpCode,
+    IcmpPayload,
+}
+
+const FIELDS: &[(&str, Field, Kind)] = &[
+    ("eth", Field::Eth, Kind::Protocol),
+    ("eth.src", Field::EthSrc, Kind::Ether),
+    ("eth.dst", Field::EthDst, Kind::Ether),
+    ("eth.addr", Field::EthAddr, Kind::Ether),
+    ("eth.type", Field::EthType, Kind::Int),
+    ("wlan", Field::Wlan, Kind::Protocol),
+    ("wlan.ra", Field::WlanRa, Kind::Ether),
+    ("wlan.ta", Field::WlanTa, Kind::Ether),
+    ("wlan.sa", Field::WlanSa, Kind::Ether),
+    ("wlan.da", Field::WlanDa, Kind::Ether),
+    ("wlan.bssid", Field::WlanBssid, Kind::Ether),
+    ("wlan.addr", Field::WlanAddr, Kind::Ether),
+    ("arp", Field::Arp, Kind::Protocol),
+    ("arp.opcode", Field::ArpOpcode, Kind::Int),
+    ("arp.src.hw_mac", Field::ArpSrcHw, Kind::Ether),
+    ("arp.dst.hw_mac", Field::ArpDstHw, Kind::Ether),
+    ("arp.src.proto_ipv4", Field::ArpSrcIp, Kind::IPv4),
+    ("arp.dst.proto_ipv4", Field::ArpDstIp, Kind::IPv4),
+    ("ip", Field::Ip, Kind::Protocol),
+    ("ip.src", Field::IpSrc, Kind::IPv4),
+    ("ip.dst", Field::IpDst, Kind::IPv4),
+    ("ip.addr", Field::IpAddr, Kind::IPv4),
+    ("ip.ttl", Field::IpTtl, Kind::Int),
+    ("ip.proto", Field::IpProto, Kind::Int),
+    ("ip.len", Field::IpLen, Kind::Int),
+    ("ip.id", Field::IpId, Kind::Int),
+    ("ipv6", Field::IPv6, Kind::Protocol),
+    ("ipv6.src", Field::IPv6Src, Kind::IPv6),
+    ("ipv6.dst", Field::IPv6Dst, Kind::IPv6),
+    ("ipv6.addr", Field::IPv6Addr, Kind::IPv6),
+    ("ipv6.hlim", Field::IPv6Hlim, Kind::Int),
+    ("ipv6.nxt", Field::IPv6Nxt, Kind::Int),
+    ("ipv6.plen", Field::IPv6Plen, Kind::Int),
+    ("tcp", Field::Tcp, Kind::Protocol),
+    ("tcp.srcport", Field::TcpSrcPort, Kind::Int),
+    ("tcp.dstport", Field::TcpDstPort, Kind::Int),
+    ("tcp.port", Field::TcpPort, Kind::Int),
+    ("tcp.seq", Field::TcpSeq, Kind::Int),
+    ("tcp.ack", Field::TcpAck, Kind::Int),
+    ("tcp.window_size", Field::TcpWindowSize, Kind::Int),
+    ("tcp.flags.syn", Field::TcpFlagSyn, Kind::Int),
+    ("tcp.flags.ack", Field::TcpFlagAck, Kind::Int),
+    ("tcp.flags.fin", Field::TcpFlagFin, Kind::Int),
+    ("tcp.flags.reset", Field::TcpFlagReset, Kind::Int),
+    ("tcp.flags.push", Field::TcpFlagPush, Kind::Int),
+    ("tcp.flags.urg", Field::TcpFlagUrg, Kind::Int),
+    ("tcp.payload", Field::TcpPayload, Kind::Bytes),
+    ("udp", Field::Udp, Kind::Protocol),
+    ("udp.srcport", Field::UdpSrcPort, Kind::Int),
+    ("udp.dstport", Field::UdpDstPort, Kind::Int),
+    ("udp.port", Field::UdpPort, Kind::Int),
+    ("udp.length", Field::UdpLength, Kind::Int),
+    ("udp.payload", Field::UdpPayload, Kind::Bytes),
+    ("icmp", Field::Icmp, Kind::Protocol),
+    ("icmp.type", Field::IcmpType, Kind::Int),
+    ("icmp.code", Field::IcmpCode, Kind::Int),
+    ("icmp.payload", Field::IcmpPayload, Kind::Bytes),
+];
+
+fn ip_payload(frame: &Frame) -> Option<&ip::Payload> {
+    match frame.payload() {
+        Some(Payload::IPv4(ref packet)) => Some(&packet.payload),
+        Some(Payload::IPv6(ref packet)) => Some(&packet.payload),
+        _ => None,
+    }
+}
+
+fn tcp(frame: &Frame) -> Option<&tcp::Packet> {
+    match ip_payload(frame) {
+        Some(ip::Payload::TCP(ref packet)) => Some(packet),
+        _ => None,
+    }
+}
+
+fn udp(frame: &Frame) -> Option<&udp::Datagram> {
+    match ip_payload(frame) {
+        Some(ip::Payload::UDP(ref datagram)) => Some(datagram),
+        _ => None,
+    }
+}
+
+fn icmp(frame: &Frame) -> Option<&icmp::Packet> {
+    match ip_payload(frame) {
+        Some(ip::Payload::ICMP(ref packet)) => Some(packet),
+        _ => None,
+    }
+}
+
+fn protocol_number(protocol: &Option<ip::Protocol>) -> Option<u64> {
+    match protocol {
+        Some(ip::Protocol::ICMP) => Some(1),
+        Some(ip::Protocol::TCP) => Some(6),
+        Some(ip::Protocol::UDP) => Some(17),
+        _ => None,
+    }
+}
+
+fn icmp_type_code(typ: &icmp::Type) -> (u8, u8) {
+    use icmp::{DestinationUnreachable, TimeExceeded, Type};
+    match typ {
+        Type::EchoReply => (0, 0),
+        Type::DestinationUnreachable(DestinationUnreachable::HostUnreachable) => (3, 1),
+        Type::DestinationUnreachable(DestinationUnreachable::Other(code)) => (3, *code),
+        Type::EchoRequest => (8, 0),
+        Type::TimeExceeded(TimeExceeded::TTLExpired) => (11, 0),
+        Type::TimeExceeded(TimeExceeded::Other(code)) => (11, *code),
+        Type::Other(typ, code) => (*typ, *code),
+    }
+}
+
+fn dot11_addrs(frame: &Frame) -> Vec<&Dot11Addr> {
+    match frame {
+        Frame::Dot11(ref frame) => {
+            let mut res = vec![&frame.addr1];
+            res.extend(frame.addr2.iter());
+            res.extend(frame.addr3.iter());
+            res.extend(frame.addr4.iter());
+            res
+        }
+        _ => vec![],
+    }
+}
+
+fn flag(set: bool) -> Value<'static> {
+    Value::Int(set as u64)
+}
+
+impl Field {
+    pub fn from_name(name: &str) -> Option<(Self, Kind)> {
+        FIELDS
+            .iter()
+            .find(|(n, _, _)| *n == name)
+            .map(|(_, field, kind)| (*field, *kind))
+    }
+
+    pub fn name(self) -> &'static str {
+        FIELDS
+            .iter()
+            .find(|(_, field, _)| *field == self)
+            .map(|(name, _, _)| *name)
+            .unwrap_or("")
+    }
+
+    fn ports(src: u16, dst: u16) -> Vec<Value<'static>> {
+        vec![Value::Int(src as u64), Value::Int(dst as u64)]
+    }
+
+    /// Extracts every occurrence of this field from the frame.
+    /// Fields such as `ip.addr` or `tcp.port` yield more than one value.
+    pub fn values<'a>(self, frame: &'a Frame) -> Vec<Value<'a>> {
+        use Field::*;
+
+        let eth = match frame {
+            Frame::Ethernet(ref frame) => Some(frame),
+            _ => None,
+        };
+        let arp = match frame.payload() {
+            Some(Payload::ARP(ref packet)) => Some(packet),
+            _ => None,
+        };
+        let ipv4 = match frame.payload() {
+            Some(Payload::IPv4(ref packet)) => Some(packet),
+            _ => None,
+        };
+        let ipv6 = match frame.payload() {
+            Some(Payload::IPv6(ref packet)) => Some(packet),
+            _ => None,
+        };
+
+        let res: Option<Vec<Value>> = match self {
+            Eth => eth.map(|_| vec![Value::Protocol]),
+            EthSrc => eth.map(|f| vec![Value::Bytes(&f.src.0)]),
+            EthDst => eth.map(|f| vec![Value::Bytes(&f.dst.0)]),
+            EthAddr => eth.map(|f| vec![Value::Bytes(&f.src.0), Value::Bytes(&f.dst.0)]),
+            EthType => eth
+                .and_then(|f| match f.ether_type {
+                    Some(EtherType::IPv4) => Some(0x0800),
+                    Some(EtherType::IPv6) => Some(0x86dd),
+                    Some(EtherType::ARP) => Some(0x0806),
+                    Some(EtherType::EAPOL) => Some(0x888e),
+                    _ => None,
+                })
+                .map(|t| vec![Value::Int(t)]),
+
+            Wlan => match frame {
+                Frame::Dot11(_) => Some(vec![Value::Protocol]),
+                _ => None,
+            },
+            WlanRa | WlanTa | WlanSa | WlanDa | WlanBssid | WlanAddr => Some(
+                dot11_addrs(frame)
+                    .into_iter()
+                    .filter_map(|addr| match (self, addr) {
+                        (WlanRa, Dot11Addr::ReceiverAddress(a))
+                        | (WlanTa, Dot11Addr::TransmitterAddress(a))
+                        | (WlanSa, Dot11Addr::SourceAddress(a))
+                        | (WlanDa, Dot11Addr::DestinationAddress(a))
+                        | (WlanBssid, Dot11Addr::BSSID(a)) => Some(Value::Bytes(&a.0)),
+                        (WlanAddr, Dot11Addr::ReceiverAddress(a))
+                        | (WlanAddr, Dot11Addr::TransmitterAddress(a))
+                        | (WlanAddr, Dot11Addr::SourceAddress(a))
+                        | (WlanAddr, Dot11Addr::DestinationAddress(a))
+                        | (WlanAddr, Dot11Addr::BSSID(a)) => Some(Value::Bytes(&a.0)),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+
+            Arp => arp.map(|_| vec![Value::Protocol]),
+            ArpOpcode => arp
+                .and_then(|p| p.operation)
+                .map(|op| vec![Value::Int(op as u64)]),
+            ArpSrcHw => arp.map(|p| vec![Value::Bytes(&p.sender_hw_addr.0)]),
+            ArpDstHw => arp.map(|p| vec![Value::Bytes(&p.target_hw_addr.0)]),
+            ArpSrcIp => arp.map(|p| vec![Value::IPv4(u32::from_be_bytes(p.sender_ip_addr.0))]),
+            ArpDstIp => arp.map(|p| vec![Value::IPv4(u32::from_be_bytes(p.target_ip_addr.0))]),
+
+            Ip => ipv4.map(|_| vec![Value::Protocol]),
+            IpSrc => ipv4.map(|p| vec![Value::IPv4(u32::from_be_bytes(p.src.0))]),
+            IpDst => ipv4.map(|p| vec![Value::IPv4(u32::from_be_bytes(p.dst.0))]),
+            IpAddr => ipv4.map(|p| {
+                vec![
+                    Value::IPv4(u32::from_be_bytes(p.src.0)),
+                    Value::IPv4(u32::from_be_bytes(p.dst.0)),
+                ]
+            }),
+            IpTtl => ipv4.map(|p| vec![Value::Int(p.ttl as u64)]),
+            IpProto => ipv4
+                .and_then(|p| protocol_number(&p.protocol))
+                .map(|p| vec![Value::Int(p)]),
+            IpLen => ipv4.map(|p| vec![Value::Int(p.length as u64)]),
+            IpId => ipv4.map(|p| vec![Value::Int(p.identification as u64)]),
+
+            IPv6 => ipv6.map(|_| vec![Value::Protocol]),
+            IPv6Src => ipv6.map(|p| vec![Value::IPv6(u128::from_be_bytes(p.src.0))]),
+            IPv6Dst => ipv6.map(|p| vec![Value::IPv6(u128::from_be_bytes(p.dst.0))]),
+            IPv6Addr => ipv6.map(|p| {
+                vec![
+                    Value::IPv6(u128::from_be_bytes(p.src.0)),
+                    Value::IPv6(u128::from_be_bytes(p.dst.0)),
+                ]
+            }),
+            IPv6Hlim => ipv6.map(|p| vec![Value::Int(p.ttl as u64)]),
+            IPv6Nxt => ipv6
+                .and_then(|p| protocol_number(&p.protocol))
+                .map(|p| vec![Value::Int(p)]),
+            IPv6Plen => ipv6.map(|p| vec![Value::Int(p.payload_len as u64)]),
+
+            Tcp => tcp(frame).map(|_| vec![Value::Protocol]),
+            TcpSrcPort => tcp(frame).map(|p| vec![Value::Int(p.src_port as u64)]),
+            TcpDstPort => tcp(frame).map(|p| vec![Value::Int(p.dst_port as u64)]),
+            TcpPort => tcp(frame).map(|p| Self::ports(p.src_port, p.dst_port)),
+            TcpSeq => tcp(frame).map(|p| vec![Value::Int(p.seq_num as u64)]),
+            TcpAck => tcp(frame).map(|p| vec![Value::Int(p.ack_num as u64)]),
+            TcpWindowSize => tcp(frame).map(|p| vec![Value::Int(p.window_size as u64)]),
+            TcpFlagSyn => tcp(frame).map(|p| vec![flag(p.syn == u1::new(1))]),
+            TcpFlagAck => tcp(frame).map(|p| vec![flag(p.ack == u1::new(1))]),
+            TcpFlagFin => tcp(frame).map(|p| vec![flag(p.fin == u1::new(1))]),
+            TcpFlagReset => tcp(frame).map(|p| vec![flag(p.rst == u1::new(1))]),
+            TcpFlagPush => tcp(frame).map(|p| vec![flag(p.psh == u1::new(1))]),
+            TcpFlagUrg => tcp(frame).map(|p| vec![flag(p.urg == u1::new(1))]),
+            TcpPayload => tcp(frame).map(|p| vec![Value::Bytes(&p.payload.0)]),
+
+            Udp => udp(frame).map(|_| vec![Value::Protocol]),
+            UdpSrcPort => udp(frame).map(|d| vec![Value::Int(d.src_port as u64)]),
+            UdpDstPort => udp(frame).map(|d| vec![Value::Int(d.dst_port as u64)]),
+            UdpPort => udp(frame).map(|d| Self::ports(d.src_port, d.dst_port)),
+            UdpLength => udp(frame).map(|d| vec![Value::Int(d.len as u64)]),
+            UdpPayload => udp(frame).map(|d| vec![Value::Bytes(&d.payload.0)]),
+
+            Icmp => icmp(frame).map(|_| vec![Value::Protocol]),
+            IcmpType => icmp(frame).map(|p| vec![Value::Int(icmp_type_code(&p.typ).0 as u64)]),
+            IcmpCode => icmp(frame).map(|p| vec![Value::Int(icmp_type_code(&p.typ).1 as u64)]),
+            IcmpPayload => icmp(frame).map(|p| vec![Value::Bytes(&p.payload.0)]),
+        };
+
+        res.unwrap_or_default()
+    }
+}
@@ -1,7 +1,10 @@
 use crate::{
     core::parse,
     layer2::arp,
-    layer2::{ethernet, wifi::dot11},
+    layer2::{
+        eapol, ethernet, loopback, ppp, sll, sll2, stp,
+        wifi::{data::LLCHeader, dot11, radiotap},
+    },
     layer3::ip::ipv4,
     layer3::ip::ipv6,
 };
@@ -9,16 +12,66 @@ use crate::{
 use derive_try_from_primitive::*;
 use nom::{bytes::complete::take, combinator::map, error::context, number::complete::be_u16};
 use serde::{Deserialize, Serialize};
-use std::fmt::{self, Debug};
+use std::{
+    fmt::{self, Debug},
+    time::Duration,
+};
 
-/// A datalink frame - ethernet or 802.11x.
+/// A datalink frame - ethernet, 802.11x, a Linux cooked capture header,
+/// or one of the headers used by loopback and tunnel interfaces.
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Frame {
     Dot11(dot11::Frame),
     Ethernet(ethernet::Frame),
+    LinuxSLL(sll::Frame),
+    LinuxSLL2(sll2::Frame),
+    /// A bare IPv4 or IPv6 packet without any link-layer header.
+    Raw(Payload),
+    Loopback(loopback::Frame),
+    PPP(ppp::Frame),
+}
+
+impl Frame {
+    /// The network layer payload carried by this frame, if any was decoded.
+    pub fn payload(&self) -> Option<&Payload> {
+        match self {
+            Frame::Ethernet(ref frame) => frame.payload.as_ref(),
+            Frame::LinuxSLL(ref frame) => frame.payload.as_ref(),
+            Frame::LinuxSLL2(ref frame) => frame.payload.as_ref(),
+            Frame::Raw(ref payload) => Some(payload),
+            Frame::Loopback(ref frame) => frame.payload.as_ref(),
+            Frame::PPP(ref frame) => frame.payload.as_ref(),
+            Frame::Dot11(ref frame) => frame.payload(),
+        }
+    }
+}
+
+/// A decoded frame along with the metadata recorded by the capture layer.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CapturedPacket {
+    /// Time of capture, as an offset from the UNIX epoch.
+    pub timestamp: Duration,
+    /// Number of bytes actually captured, which is less than `len` if the snapshot length cut the packet short.
+    pub caplen: u32,
+    /// Length of the packet on the wire.
+    pub len: u32,
+    /// Index of the interface the packet was captured on, taken from the header of SLL2 frames.
+    /// Other frames read through libpcap, which does not report it, are always on interface 0.
+    pub interface: u32,
+    /// The LINKTYPE_* value of the capture, e.g. 1 for ethernet or 127 for radiotap.
+    pub link_type: i32,
+    /// The radio information of 802.11 frames captured in monitor mode.
+    pub radiotap: Option<radiotap::RadioTapHeader>,
+    pub frame: Frame,
+}
+
+impl CapturedPacket {
+    pub fn is_truncated(&self) -> bool {
+        self.caplen < self.len
+    }
 }
 
-#[derive(PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 pub struct Addr(pub [u8; 6]);
 
 impl fmt::Display for Addr {
@@ -45,6 +98,11 @@ impl Addr {
         res
     }
 
+    /// Whether this is a broadcast or multicast address, from its individual/group bit.
+    pub fn is_group(&self) -> bool {
+        self.0[0] & 1 != 0
+    }
+
     pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
         context("MAC address", map(take(6_usize), Self::new))(i)
     }
@@ -55,15 +113,53 @@ pub enum Payload {
     IPv4(ipv4::Packet),
     IPv6(ipv6::Packet),
     ARP(arp::Packet),
+    EAPOL(eapol::Frame),
+    STP(stp::BPDU),
     Unknown,
 }
 
+impl Payload {
+    /// Parses the network layer packet identified by an EtherType, as found in ethernet, Linux cooked and SNAP headers.
+    pub fn parse<'a>(
+        i: parse::Input<'a>,
+        ether_type: &Option<EtherType>,
+    ) -> parse::ParseResult<'a, Self> {
+        match ether_type {
+            Some(EtherType::IPv4) => map(ipv4::Packet::parse, Payload::IPv4)(i),
+            Some(EtherType::IPv6) => map(ipv6::Packet::parse, Payload::IPv6)(i),
+            Some(EtherType::ARP) => map(arp::Packet::parse, Payload::ARP)(i),
+            Some(EtherType::EAPOL) => map(eapol::Frame::parse, Payload::EAPOL)(i),
+            _ => Ok((i, Payload::Unknown)),
+        }
+    }
+
+    /// Parses the packet that follows an LLC header without SNAP, as in 802.3 frames, identified by its SAPs.
+    pub fn parse_llc<'a>(i: parse::Input<'a>, llc: &LLCHeader) -> parse::ParseResult<'a, Self> {
+        match (llc.dsap, llc.ssap) {
+            (0x42, 0x42) => map(stp::BPDU::parse, Payload::STP)(i),
+            _ => Ok((i, Payload::Unknown)),
+        }
+    }
+
+    /// Parses a bare IP packet, telling IPv4 from IPv6 by its version nibble.
+    pub fn parse_ip(i: parse::Input) -> parse::ParseResult<Self> {
+        context("Raw IP", |i: parse::Input| {
+            match i.first().map(|b| b >> 4) {
+                Some(4) => map(ipv4::Packet::parse, Payload::IPv4)(i),
+                Some(6) => map(ipv6::Packet::parse, Payload::IPv6)(i),
+                _ => Ok((i, Payload::Unknown)),
+            }
+        })(i)
+    }
+}
+
 #[derive(TryFromPrimitive, PartialEq, Eq, Serialize, Deserialize, Debug)]
 #[repr(u16)]
 pub enum EtherType {
     IPv4 = 0x0800,
     IPv6 = 0x86dd,
     ARP = 0x0806,
+    EAPOL = 0x888e,
     Unknown,
 }
 
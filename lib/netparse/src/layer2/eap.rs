@@ -0,0 +1,303 @@
+use crate::core::{blob::Blob, parse};
+
+use custom_debug_derive::*;
+use derive_try_from_primitive::*;
+use nom::{
+    bytes::complete::take,
+    combinator::map,
+    error::context,
+    number::complete::{be_u16, be_u24, be_u32, be_u8},
+    sequence::tuple,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Code {
+    Request = 1,
+    Response = 2,
+    Success = 3,
+    Failure = 4,
+    Initiate = 5,
+    Finish = 6,
+}
+
+/// The authentication method of a request or response.
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Type {
+    Identity = 1,
+    Notification = 2,
+    Nak = 3,
+    MD5Challenge = 4,
+    OneTimePassword = 5,
+    GenericTokenCard = 6,
+    TLS = 13,
+    LEAP = 17,
+    SIM = 18,
+    TTLS = 21,
+    AKA = 23,
+    PEAP = 25,
+    MSCHAPv2 = 26,
+    FAST = 43,
+    AKAPrime = 50,
+    PWD = 52,
+    TEAP = 55,
+    Expanded = 254,
+}
+
+/// An EAP packet (RFC 3748), as carried by EAPOL.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct Packet {
+    pub code: Option<Code>,
+    #[debug(format = "{}")]
+    pub identifier: u8,
+    #[debug(format = "{}")]
+    pub len: u16,
+    /// Only requests and responses have a type.
+    pub typ: Option<Type>,
+    pub data: Data,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Data {
+    Identity(String),
+    Notification(String),
+    /// The methods the peer would rather use.
+    Nak(Vec<u8>),
+    MD5Challenge(MD5Challenge),
+    /// EAP-TLS, and the methods tunneling TLS: TTLS, PEAP and FAST.
+    TLS(TLS),
+    MSCHAPv2(MSCHAPv2),
+    Expanded(Expanded),
+    Unknown(Blob),
+    /// Success and failure packets carry no data.
+    Empty,
+}
+
+impl Packet {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("EAP packet", |i| {
+            let (i, (code, identifier, len)) =
+                tuple((map(be_u8, Code::try_from), be_u8, be_u16))(i)?;
+            let (i, body) = take((len as usize).saturating_sub(4))(i)?;
+            let (typ, data) = match code {
+                Some(Code::Request) | Some(Code::Response) if !body.is_empty() => {
+                    let typ = Type::try_from(body[0]);
+                    let data = Data::parse(typ, &body[1..])
+                        .map(|(_, d)| d)
+                        .unwrap_or_else(|_| Data::Unknown(Blob::new(&body[1..])));
+                    (typ, data)
+                }
+                _ if body.is_empty() => (None, Data::Empty),
+                _ => (None, Data::Unknown(Blob::new(body))),
+            };
+            let res = Self {
+                code,
+                identifier,
+                len,
+                typ,
+                data,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+impl fmt::Display for Packet {
+    /// e.g. `Request Identity` or `Response PEAP Client Hello`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "{:?}", code)?,
+            None => write!(f, "Unknown code")?,
+        }
+        if let Some(typ) = self.typ {
+            write!(f, " {:?}", typ)?;
+        }
+        match self.data {
+            Data::Identity(ref identity) if !identity.is_empty() => write!(f, " {}", identity),
+            Data::TLS(ref tls) => {
+                if tls.start() {
+                    write!(f, " Start")?;
+                }
+                for message in tls.handshake_types() {
+                    write!(f, " {:?}", message)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Data {
+    fn parse(typ: Option<Type>, i: parse::Input) -> parse::ParseResult<Self> {
+        let string = |i: parse::Input| String::from_utf8_lossy(i).to_string();
+        match typ {
+            Some(Type::Identity) => Ok((&i[i.len()..], Data::Identity(string(i)))),
+            Some(Type::Notification) => Ok((&i[i.len()..], Data::Notification(string(i)))),
+            Some(Type::Nak) => Ok((&i[i.len()..], Data::Nak(i.to_vec()))),
+            Some(Type::MD5Challenge) => map(MD5Challenge::parse, Data::MD5Challenge)(i),
+            Some(Type::TLS) | Some(Type::TTLS) | Some(Type::PEAP) | Some(Type::FAST) => {
+                map(TLS::parse, Data::TLS)(i)
+            }
+            Some(Type::MSCHAPv2) => map(MSCHAPv2::parse, Data::MSCHAPv2)(i),
+            Some(Type::Expanded) => map(Expanded::parse, Data::Expanded)(i),
+            _ => Ok((&i[i.len()..], Data::Unknown(Blob::new(i)))),
+        }
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct MD5Challenge {
+    pub value: Blob,
+    pub name: String,
+}
+
+impl MD5Challenge {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("EAP-MD5 challenge", |i| {
+            let (i, size) = be_u8(i)?;
+            let (i, value) = map(take(size), Blob::new)(i)?;
+            let name = String::from_utf8_lossy(i).to_string();
+            Ok((&i[i.len()..], Self { value, name }))
+        })(i)
+    }
+}
+
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum HandshakeType {
+    HelloRequest = 0,
+    ClientHello = 1,
+    ServerHello = 2,
+    NewSessionTicket = 4,
+    EncryptedExtensions = 8,
+    Certificate = 11,
+    ServerKeyExchange = 12,
+    CertificateRequest = 13,
+    ServerHelloDone = 14,
+    CertificateVerify = 15,
+    ClientKeyExchange = 16,
+    Finished = 20,
+}
+
+/// The TLS data of EAP-TLS and of the tunneled methods, which use the low bits of the flags for their version.
+/// A TLS message may be fragmented over several packets, the first of which gives its total length.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct TLS {
+    #[debug(format = "0x{:02X}")]
+    pub flags: u8,
+    pub message_length: Option<u32>,
+    pub data: Blob,
+}
+
+impl TLS {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("EAP-TLS data", |i| {
+            let (i, flags) = be_u8(i)?;
+            let (i, message_length) = if flags & 0x80 != 0 {
+                map(be_u32, Some)(i)?
+            } else {
+                (i, None)
+            };
+            let (i, data) = map(take(i.len()), Blob::new)(i)?;
+            let res = Self {
+                flags,
+                message_length,
+                data,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    pub fn more_fragments(&self) -> bool {
+        self.flags & 0x40 != 0
+    }
+
+    /// Set by the server to start the TLS exchange.
+    pub fn start(&self) -> bool {
+        self.flags & 0x20 != 0
+    }
+
+    /// The version of PEAP, TTLS or FAST.
+    pub fn version(&self) -> u8 {
+        self.flags & 0b111
+    }
+
+    /// The handshake messages in the cleartext TLS records at the start of the data.
+    /// Records after a ChangeCipherSpec are encrypted and not looked into.
+    pub fn handshake_types(&self) -> Vec<HandshakeType> {
+        let mut res = vec![];
+        let mut records = &self.data.0[..];
+        while records.len() >= 5 {
+            let (content_type, len) = (records[0], u16::from_be_bytes([records[3], records[4]]));
+            let record = match records.get(5..5 + len as usize) {
+                Some(record) => record,
+                None => &records[5..],
+            };
+            match content_type {
+                // ChangeCipherSpec
+                20 => break,
+                // Handshake
+                22 => {
+                    let mut messages = record;
+                    while messages.len() >= 4 {
+                        if let Some(typ) = HandshakeType::try_from(messages[0]) {
+                            res.push(typ);
+                        }
+                        let len = u32::from_be_bytes([0, messages[1], messages[2], messages[3]]);
+                        messages = messages.get(4 + len as usize..).unwrap_or(&[]);
+                    }
+                }
+                _ => {}
+            }
+            records = records.get(5 + len as usize..).unwrap_or(&[]);
+        }
+        res
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct MSCHAPv2 {
+    /// Challenge, Response, Success, Failure or Change-Password, from 1 to 7.
+    #[debug(format = "{}")]
+    pub opcode: u8,
+    pub data: Blob,
+}
+
+impl MSCHAPv2 {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("EAP-MSCHAPv2", |i| {
+            let (i, opcode) = be_u8(i)?;
+            let (i, data) = map(take(i.len()), Blob::new)(i)?;
+            Ok((i, Self { opcode, data }))
+        })(i)
+    }
+}
+
+/// A method defined by a vendor, such as the WPS exchanges of the Wi-Fi Alliance.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct Expanded {
+    #[debug(format = "0x{:06X}")]
+    pub vendor_id: u32,
+    #[debug(format = "{}")]
+    pub vendor_type: u32,
+    pub data: Blob,
+}
+
+impl Expanded {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("EAP expanded type", |i| {
+            let (i, (vendor_id, vendor_type)) = tuple((be_u24, be_u32))(i)?;
+            let (i, data) = map(take(i.len()), Blob::new)(i)?;
+            let res = Self {
+                vendor_id,
+                vendor_type,
+                data,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
@@ -0,0 +1,325 @@
+use crate::{
+    core::{blob::Blob, parse},
+    layer2::eap,
+};
+
+use custom_debug_derive::*;
+use derive_try_from_primitive::*;
+use nom::{
+    bytes::complete::take,
+    combinator::{all_consuming, map},
+    error::context,
+    number::complete::{be_u16, be_u64, be_u8},
+    sequence::tuple,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum PacketType {
+    EAPPacket = 0,
+    Start = 1,
+    Logoff = 2,
+    Key = 3,
+    EncapsulatedASFAlert = 4,
+}
+
+/// An 802.1X authentication frame (EtherType 0x888E), as sent over ethernet or in 802.11 data frames.
+#[derive(Serialize, Deserialize, CustomDebug)]
+pub struct Frame {
+    #[debug(format = "{}")]
+    pub version: u8,
+    pub packet_type: Option<PacketType>,
+    #[debug(format = "{}")]
+    pub len: u16,
+    pub body: Body,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Body {
+    EAP(eap::Packet),
+    Key(KeyDescriptor),
+    /// EAPOL-Start and EAPOL-Logoff have no body.
+    Empty,
+    /// The body of a packet type we do not decode, or that failed to parse.
+    Unknown(Blob),
+}
+
+impl Frame {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("EAPOL frame", |i| {
+            let (i, version) = be_u8(i)?;
+            let (i, packet_type) = map(be_u8, PacketType::try_from)(i)?;
+            let (i, len) = be_u16(i)?;
+            let (i, body) = take(len)(i)?;
+            let decoded = match packet_type {
+                Some(PacketType::EAPPacket) => map(eap::Packet::parse, Body::EAP)(body).ok(),
+                Some(PacketType::Key) => {
+                    all_consuming(map(KeyDescriptor::parse, Body::Key))(body).ok()
+                }
+                Some(PacketType::Start) | Some(PacketType::Logoff) if body.is_empty() => {
+                    Some((body, Body::Empty))
+                }
+                _ => None,
+            };
+            let body = match decoded {
+                Some((_, body)) => body,
+                None => Body::Unknown(Blob::new(body)),
+            };
+
+            let res = Self {
+                version,
+                packet_type,
+                len,
+                body,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+impl fmt::Display for Frame {
+    /// e.g. `EAP Response Identity alice` or `Key (2/4)`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.body {
+            Body::EAP(ref packet) => write!(f, "EAP {}", packet),
+            Body::Key(ref key) => match key.handshake_message() {
+                Some(message) => write!(f, "Key ({})", message),
+                None => write!(f, "Key"),
+            },
+            _ => match self.packet_type {
+                Some(packet_type) => write!(f, "{:?}", packet_type),
+                None => write!(f, "unknown"),
+            },
+        }
+    }
+}
+
+/// The Key Information field of an EAPOL-Key frame.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct KeyInformation(pub u16);
+
+impl fmt::Debug for KeyInformation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{:04X}", self.0)
+    }
+}
+
+impl KeyInformation {
+    /// 1 for RC4 and HMAC-MD5, 2 for AES key wrap and HMAC-SHA1, 3 for AES-CMAC, 0 when the AKM decides.
+    pub fn descriptor_version(self) -> u8 {
+        (self.0 & 0b111) as u8
+    }
+
+    /// A pairwise key, rather than a group key.
+    pub fn pairwise(self) -> bool {
+        self.0 & 0x0008 != 0
+    }
+
+    pub fn install(self) -> bool {
+        self.0 & 0x0040 != 0
+    }
+
+    /// Set by the authenticator when it expects a reply.
+    pub fn ack(self) -> bool {
+        self.0 & 0x0080 != 0
+    }
+
+    pub fn mic(self) -> bool {
+        self.0 & 0x0100 != 0
+    }
+
+    pub fn secure(self) -> bool {
+        self.0 & 0x0200 != 0
+    }
+
+    pub fn error(self) -> bool {
+        self.0 & 0x0400 != 0
+    }
+
+    pub fn request(self) -> bool {
+        self.0 & 0x0800 != 0
+    }
+
+    pub fn encrypted_key_data(self) -> bool {
+        self.0 & 0x1000 != 0
+    }
+}
+
+/// The messages of the 4-way and group key handshakes.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum HandshakeMessage {
+    Message1,
+    Message2,
+    Message3,
+    Message4,
+    Group1,
+    Group2,
+}
+
+impl fmt::Display for HandshakeMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let label = match self {
+            HandshakeMessage::Message1 => "1/4",
+            HandshakeMessage::Message2 => "2/4",
+            HandshakeMessage::Message3 => "3/4",
+            HandshakeMessage::Message4 => "4/4",
+            HandshakeMessage::Group1 => "group 1/2",
+            HandshakeMessage::Group2 => "group 2/2",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// An EAPOL-Key frame body, with the layout of the RSN and WPA key descriptors.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct KeyDescriptor {
+    /// 2 for RSN, 254 for WPA.
+    #[debug(format = "{}")]
+    pub descriptor_type: u8,
+    pub key_information: KeyInformation,
+    #[debug(format = "{}")]
+    pub key_length: u16,
+    #[debug(format = "{}")]
+    pub replay_counter: u64,
+    pub nonce: Blob,
+    pub key_iv: Blob,
+    pub key_rsc: Blob,
+    pub mic: Blob,
+    pub key_data: Blob,
+}
+
+impl KeyDescriptor {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("EAPOL-Key descriptor", |i: parse::Input| {
+            let (i, (descriptor_type, key_information, key_length, replay_counter)) =
+                tuple((be_u8, map(be_u16, KeyInformation), be_u16, be_u64))(i)?;
+            let (i, (nonce, key_iv, key_rsc, _)) = tuple((
+                map(take(32_usize), Blob::new),
+                map(take(16_usize), Blob::new),
+                map(take(8_usize), Blob::new),
+                take(8_usize),
+            ))(i)?;
+            // The MIC is 16 bytes long, except for the AKMs that use SHA-384 (24 bytes) and for FILS,
+            // which has none. The AKM is not known here, so pick the length for which the key data fits.
+            let mic_len = [16, 24, 0]
+                .iter()
+                .copied()
+                .find(|&n| {
+                    i.len() >= n + 2
+                        && u16::from_be_bytes([i[n], i[n + 1]]) as usize == i.len() - n - 2
+                })
+                .unwrap_or(16);
+            let (i, mic) = map(take(mic_len), Blob::new)(i)?;
+            let (i, key_data_len) = be_u16(i)?;
+            let (i, key_data) = map(take(key_data_len), Blob::new)(i)?;
+
+            let res = Self {
+                descriptor_type,
+                key_information,
+                key_length,
+                replay_counter,
+                nonce,
+                key_iv,
+                key_rsc,
+                mic,
+                key_data,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    /// Which handshake message this is, from its key information bits.
+    /// Message 2 and 4 of the 4-way handshake only differ in that message 4 has no key data.
+    pub fn handshake_message(&self) -> Option<HandshakeMessage> {
+        let info = self.key_information;
+        if info.request() || info.error() {
+            return None;
+        }
+        let res = match (info.pairwise(), info.ack(), info.mic()) {
+            (true, true, false) => HandshakeMessage::Message1,
+            (true, true, true) if info.install() => HandshakeMessage::Message3,
+            (true, false, true) if self.key_data.0.is_empty() => HandshakeMessage::Message4,
+            (true, false, true) => HandshakeMessage::Message2,
+            (false, true, true) => HandshakeMessage::Group1,
+            (false, false, true) => HandshakeMessage::Group2,
+            _ => return None,
+        };
+        Some(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The length of the fields from the descriptor type to the reserved field before the MIC.
+    const FIXED_SIZE: usize = 1 + 2 + 2 + 8 + 32 + 16 + 8 + 8;
+
+    fn key_frame(key_information: u16, mic: bool, key_data: &[u8]) -> Vec<u8> {
+        let len = FIXED_SIZE + 16 + 2 + key_data.len();
+        let mut frame = vec![0x02, 0x03];
+        frame.extend_from_slice(&(len as u16).to_be_bytes());
+        frame.push(0x02);
+        frame.extend_from_slice(&key_information.to_be_bytes());
+        frame.extend_from_slice(&[0x00, 0x10, 0, 0, 0, 0, 0, 0, 0, 0x01]);
+        frame.extend_from_slice(&[0xAA; 32]);
+        frame.extend_from_slice(&[0; 16 + 8 + 8]);
+        frame.extend_from_slice(&[if mic { 0x55 } else { 0 }; 16]);
+        frame.extend_from_slice(&(key_data.len() as u16).to_be_bytes());
+        frame.extend_from_slice(key_data);
+        frame
+    }
+
+    #[test]
+    fn assert_four_way_handshake() {
+        let rsn = [0x30, 0x02, 0x01, 0x00];
+        let messages = [
+            (key_frame(0x008A, false, &[]), "Key (1/4)"),
+            (key_frame(0x010A, true, &rsn), "Key (2/4)"),
+            (key_frame(0x13CA, true, &[0; 24]), "Key (3/4)"),
+            (key_frame(0x030A, true, &[]), "Key (4/4)"),
+        ];
+        for (frame, label) in messages.iter() {
+            let (rest, frame) = Frame::parse(frame).unwrap();
+            assert!(rest.is_empty());
+            assert_eq!(frame.to_string(), *label);
+        }
+
+        let (_, frame) = Frame::parse(&key_frame(0x010A, true, &rsn)).unwrap();
+        let key = match frame.body {
+            Body::Key(key) => key,
+            b => panic!("expected a key descriptor, got {:?}", b),
+        };
+        assert_eq!(key.replay_counter, 1);
+        assert_eq!(key.key_information.descriptor_version(), 2);
+        assert_eq!(key.mic.0, vec![0x55; 16]);
+        assert_eq!(key.key_data.0, rsn.to_vec());
+    }
+
+    #[test]
+    fn assert_eap() {
+        let identity: &[u8] = &[
+            0x01, 0x00, 0x00, 0x0A, // EAP, length 10
+            0x02, 0x01, 0x00, 0x0A, 0x01, // Response, identifier 1, Identity
+            b'a', b'l', b'i', b'c', b'e',
+        ];
+        let (_, frame) = Frame::parse(identity).unwrap();
+        assert_eq!(frame.to_string(), "EAP Response Identity alice");
+
+        let client_hello: &[u8] = &[
+            0x01, 0x00, 0x00, 0x13, // EAP, length 19
+            0x02, 0x02, 0x00, 0x13, 0x19, 0x81, // Response PEAP, length included, version 1
+            0x00, 0x00, 0x00, 0x09, // TLS message length
+            0x16, 0x03, 0x01, 0x00, 0x04, // handshake record
+            0x01, 0x00, 0x00, 0x00, // client hello
+        ];
+        let (_, frame) = Frame::parse(client_hello).unwrap();
+        assert_eq!(frame.to_string(), "EAP Response PEAP ClientHello");
+
+        let (_, frame) = Frame::parse(&[0x01, 0x01, 0x00, 0x00]).unwrap();
+        assert!(matches!(frame.body, Body::Empty));
+        assert_eq!(frame.to_string(), "Start");
+    }
+}
@@ -1,18 +1,32 @@
 use crate::{
     core::parse,
-    layer2::{arp, datalink},
-    layer3::ip::{ipv4, ipv6},
+    layer2::{
+        datalink,
+        wifi::data::{LLCHeader, SNAPHeader},
+    },
 };
 
 use custom_debug_derive::*;
-use nom::{combinator::map, error::context, sequence::tuple};
+use nom::{
+    bytes::complete::take, combinator::peek, error::context, number::complete::be_u16,
+    sequence::tuple,
+};
 use serde::{Deserialize, Serialize};
 
+/// Type field values up to this one are the length of an IEEE 802.3 frame rather than an EtherType.
+const MAX_LENGTH: u16 = 1500;
+
 #[derive(Serialize, Deserialize, CustomDebug)]
 pub struct Frame {
     pub dst: datalink::Addr,
     pub src: datalink::Addr,
+    /// The EtherType of Ethernet II frames; `None` for 802.3 frames, whose SNAP header may have one.
     pub ether_type: Option<datalink::EtherType>,
+    /// The length of the LLC data of 802.3 frames, which excludes the padding of short frames.
+    #[debug(format = "{:?}")]
+    pub length: Option<u16>,
+    pub llc: Option<LLCHeader>,
+    pub snap: Option<SNAPHeader>,
     pub payload: Option<datalink::Payload>,
 }
 
@@ -20,24 +34,53 @@ impl Frame {
     pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
         context("Ethernet frame", |i| {
             let (i, (dst, src)) = tuple((datalink::Addr::parse, datalink::Addr::parse))(i)?;
+            let (_, type_or_length) = peek(be_u16)(i)?;
+            if type_or_length <= MAX_LENGTH {
+                return Self::parse_802_3(i, dst, src);
+            }
             let (i, ether_type) = datalink::EtherType::parse(i)?;
-            let (i, payload) = match ether_type {
-                Some(datalink::EtherType::IPv4) => {
-                    map(ipv4::Packet::parse, datalink::Payload::IPv4)(i)?
-                }
-                Some(datalink::EtherType::IPv6) => {
-                    map(ipv6::Packet::parse, datalink::Payload::IPv6)(i)?
-                }
-                Some(datalink::EtherType::ARP) => {
-                    map(arp::Packet::parse, datalink::Payload::ARP)(i)?
-                }
-                _ => (i, datalink::Payload::Unknown),
-            };
+            let (i, payload) = datalink::Payload::parse(i, &ether_type)?;
 
             let res = Self {
                 dst,
                 src,
                 ether_type,
+                length: None,
+                llc: None,
+                snap: None,
+                payload: Some(payload),
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    /// Parses an IEEE 802.3 frame: a length, then LLC data, usually SNAP encapsulated.
+    fn parse_802_3(
+        i: parse::Input,
+        dst: datalink::Addr,
+        src: datalink::Addr,
+    ) -> parse::ParseResult<Self> {
+        context("802.3 frame", |i| {
+            let (i, length) = be_u16(i)?;
+            // The capture may have been cut short by the snapshot length.
+            let (i, data) = take((length as usize).min(i.len()))(i)?;
+            let (data, llc) = LLCHeader::parse(data)?;
+            let (snap, payload) = if llc.is_snap() {
+                let (data, snap) = SNAPHeader::parse(data)?;
+                let (_, payload) = datalink::Payload::parse(data, &snap.ether_type)?;
+                (Some(snap), payload)
+            } else {
+                let (_, payload) = datalink::Payload::parse_llc(data, &llc)?;
+                (None, payload)
+            };
+
+            let res = Self {
+                dst,
+                src,
+                ether_type: None,
+                length: Some(length),
+                llc: Some(llc),
+                snap,
                 payload: Some(payload),
             };
             Ok((i, res))
@@ -68,6 +111,32 @@ mod tests {
         assert_eq!(frame.ether_type.unwrap(), datalink::EtherType::IPv4);
     }
 
+    #[test]
+    fn assert_802_3_frame() {
+        let mut frame = vec![0x01, 0x80, 0xC2, 0x00, 0x00, 0x00];
+        frame.extend_from_slice(&[0x00, 0x1B, 0x2C, 0x3D, 0x4E, 0x5F]);
+        frame.extend_from_slice(&[0x00, 0x26, 0x42, 0x42, 0x03]);
+        // A configuration BPDU from the root bridge
+        frame.extend_from_slice(&[0x00, 0x00, 0x00, 0x00, 0x00]);
+        frame.extend_from_slice(&[0x80, 0x00, 0x00, 0x1B, 0x2C, 0x3D, 0x4E, 0x5F]);
+        frame.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+        frame.extend_from_slice(&[0x80, 0x00, 0x00, 0x1B, 0x2C, 0x3D, 0x4E, 0x5F]);
+        frame.extend_from_slice(&[0x80, 0x01, 0x00, 0x00, 0x14, 0x00, 0x02, 0x00, 0x0F, 0x00]);
+        frame.extend_from_slice(&[0x00; 8]);
+
+        let (padding, frame) = Frame::parse(&frame).unwrap();
+        assert_eq!(padding, &[0x00; 8]);
+        assert_eq!(frame.length, Some(38));
+        assert!(frame.ether_type.is_none());
+        match frame.payload {
+            Some(datalink::Payload::STP(ref bpdu)) => assert_eq!(
+                bpdu.to_string(),
+                "STP root 32768/0/00:1B:2C:3D:4E:5F cost 0 port 0x8001"
+            ),
+            ref p => panic!("expected a BPDU, got {:?}", p),
+        }
+    }
+
     #[test]
     #[should_panic]
     fn assert_invalid_frame() {
@@ -0,0 +1,132 @@
+use crate::{
+    core::parse,
+    layer2::datalink,
+    layer3::ip::{ipv4, ipv6},
+};
+
+use custom_debug_derive::*;
+use derive_try_from_primitive::*;
+use nom::{
+    combinator::map,
+    error::context,
+    number::complete::{be_u32, le_u32},
+};
+use serde::{Deserialize, Serialize};
+
+/// The AF_* value of the packet, which differs between BSDs for IPv6.
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum AddressFamily {
+    Inet = 2,
+    Inet6NetBSD = 24,
+    Inet6FreeBSD = 28,
+    Inet6Darwin = 30,
+}
+
+/// The 4 byte address family header of BSD loopback and tunnel devices (LINKTYPE_NULL and LINKTYPE_LOOP).
+#[derive(Serialize, Deserialize, CustomDebug)]
+pub struct Frame {
+    pub family: Option<AddressFamily>,
+    pub payload: Option<datalink::Payload>,
+}
+
+impl Frame {
+    /// LINKTYPE_NULL, where the family is in the byte order of the host that captured it.
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("BSD loopback", |i| {
+            // Families are small, so a value whose low half is empty was written big endian.
+            let (i, family) = map(le_u32, |f| if f & 0xFFFF == 0 { f.swap_bytes() } else { f })(i)?;
+            Self::parse_payload(i, family)
+        })(i)
+    }
+
+    /// LINKTYPE_LOOP, used by OpenBSD, where the family is always in network byte order.
+    pub fn parse_loop(i: parse::Input) -> parse::ParseResult<Self> {
+        context("OpenBSD loopback", |i| {
+            let (i, family) = be_u32(i)?;
+            Self::parse_payload(i, family)
+        })(i)
+    }
+
+    fn parse_payload(i: parse::Input, family: u32) -> parse::ParseResult<Self> {
+        let family = AddressFamily::try_from(family);
+        let (i, payload) = match family {
+            Some(AddressFamily::Inet) => map(ipv4::Packet::parse, datalink::Payload::IPv4)(i)?,
+            Some(_) => map(ipv6::Packet::parse, datalink::Payload::IPv6)(i)?,
+            None => (i, datalink::Payload::Unknown),
+        };
+
+        let res = Self {
+            family,
+            payload: Some(payload),
+        };
+        Ok((i, res))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An ICMP echo request from 127.0.0.1 to itself.
+    const IPV4_PACKET: &[u8] = &[
+        0x45, 0x00, 0x00, 0x1C, 0x00, 0x01, 0x00, 0x00, 0x40, 0x01, 0x7C, 0xDE, 0x7F, 0x00, 0x00,
+        0x01, 0x7F, 0x00, 0x00, 0x01, 0x08, 0x00, 0xF7, 0xFE, 0x00, 0x01, 0x00, 0x00,
+    ];
+
+    /// An empty IPv6 packet (no next header) from ::1 to itself.
+    const IPV6_PACKET: &[u8] = &[
+        0x60, 0x00, 0x00, 0x00, 0x00, 0x00, 0x3B, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+    ];
+
+    fn with_family(family: [u8; 4], packet: &[u8]) -> Vec<u8> {
+        let mut frame = family.to_vec();
+        frame.extend_from_slice(packet);
+        frame
+    }
+
+    #[test]
+    fn assert_null_host_order() {
+        // Written by a little endian host, then by a big endian one.
+        for family in [[0x02, 0x00, 0x00, 0x00], [0x00, 0x00, 0x00, 0x02]].iter() {
+            let packet = with_family(*family, IPV4_PACKET);
+            let (i, frame) = Frame::parse(&packet).unwrap();
+            assert!(i.is_empty());
+            assert_eq!(frame.family, Some(AddressFamily::Inet));
+            match frame.payload {
+                Some(datalink::Payload::IPv4(_)) => {}
+                _ => panic!("expected an IPv4 payload"),
+            }
+        }
+
+        let (_, frame) = Frame::parse(&with_family([0x1E, 0x00, 0x00, 0x00], IPV6_PACKET)).unwrap();
+        assert_eq!(frame.family, Some(AddressFamily::Inet6Darwin));
+        match frame.payload {
+            Some(datalink::Payload::IPv6(_)) => {}
+            _ => panic!("expected an IPv6 payload"),
+        }
+    }
+
+    #[test]
+    fn assert_loop_network_order() {
+        let packet = with_family([0x00, 0x00, 0x00, 0x18], IPV6_PACKET);
+        let (i, frame) = Frame::parse_loop(&packet).unwrap();
+        assert!(i.is_empty());
+        assert_eq!(frame.family, Some(AddressFamily::Inet6NetBSD));
+        match frame.payload {
+            Some(datalink::Payload::IPv6(_)) => {}
+            _ => panic!("expected an IPv6 payload"),
+        }
+
+        // A little endian family is not swapped, unlike with LINKTYPE_NULL.
+        let (_, frame) =
+            Frame::parse_loop(&with_family([0x02, 0x00, 0x00, 0x00], IPV4_PACKET)).unwrap();
+        assert_eq!(frame.family, None);
+        match frame.payload {
+            Some(datalink::Payload::Unknown) => {}
+            _ => panic!("expected an unknown payload"),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::{
+    core::parse,
+    layer2::datalink,
+    layer3::ip::{ipv4, ipv6},
+};
+
+use custom_debug_derive::*;
+use derive_try_from_primitive::*;
+use nom::{
+    bytes::complete::tag, combinator::map, combinator::opt, error::context, number::complete::be_u8,
+};
+use serde::{Deserialize, Serialize};
+
+/// The HDLC address and control bytes that may precede the protocol field.
+pub static HDLC_HEADER: &[u8] = &[0xFF, 0x03];
+
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u16)]
+pub enum Protocol {
+    IPv4 = 0x0021,
+    IPv6 = 0x0057,
+    IPCP = 0x8021,
+    IPv6CP = 0x8057,
+    CCP = 0x80FD,
+    LCP = 0xC021,
+    PAP = 0xC023,
+    CHAP = 0xC223,
+}
+
+impl Protocol {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Option<Self>> {
+        context("PPP protocol", |i| {
+            let (i, first) = be_u8(i)?;
+            // With protocol field compression, protocols below 0x100 are sent as a single odd byte.
+            if first & 1 == 1 {
+                Ok((i, Self::try_from(first as u16)))
+            } else {
+                map(be_u8, |second| {
+                    Self::try_from((first as u16) << 8 | second as u16)
+                })(i)
+            }
+        })(i)
+    }
+}
+
+/// A PPP frame (LINKTYPE_PPP), as seen on dial-up, PPPoE and some VPN interfaces.
+#[derive(Serialize, Deserialize, CustomDebug)]
+pub struct Frame {
+    /// Whether the frame started with the HDLC address and control bytes.
+    pub hdlc: bool,
+    pub protocol: Option<Protocol>,
+    pub payload: Option<datalink::Payload>,
+}
+
+impl Frame {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("PPP frame", |i| {
+            let (i, hdlc) = opt(tag(HDLC_HEADER))(i)?;
+            let (i, protocol) = Protocol::parse(i)?;
+            let (i, payload) = match protocol {
+                Some(Protocol::IPv4) => map(ipv4::Packet::parse, datalink::Payload::IPv4)(i)?,
+                Some(Protocol::IPv6) => map(ipv6::Packet::parse, datalink::Payload::IPv6)(i)?,
+                _ => (i, datalink::Payload::Unknown),
+            };
+
+            let res = Self {
+                hdlc: hdlc.is_some(),
+                protocol,
+                payload: Some(payload),
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_protocol_field() {
+        let (_, hdlc) = Frame::parse(&[0xFF, 0x03, 0xC0, 0x21, 0x01]).unwrap();
+        assert!(hdlc.hdlc);
+        assert_eq!(hdlc.protocol, Some(Protocol::LCP));
+
+        let echo_request = &[
+            0x21, 0x45, 0x00, 0x00, 0x1C, 0x00, 0x01, 0x00, 0x00, 0x40, 0x01, 0xF9, 0x60, 0xC0,
+            0xA8, 0x01, 0x43, 0xC0, 0xA8, 0x01, 0x01, 0x08, 0x00, 0xF7, 0xFE, 0x00, 0x01, 0x00,
+            0x00,
+        ];
+        let (_, compressed) = Frame::parse(echo_request).unwrap();
+        assert!(!compressed.hdlc);
+        assert_eq!(compressed.protocol, Some(Protocol::IPv4));
+        match compressed.payload {
+            Some(datalink::Payload::IPv4(_)) => {}
+            _ => panic!("expected an IPv4 payload"),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::{
+    core::{blob::Blob, parse},
+    layer2::datalink,
+};
+
+use custom_debug_derive::*;
+use derive_try_from_primitive::*;
+use nom::{
+    bytes::complete::take, combinator::map, error::context, number::complete::be_u16,
+    sequence::tuple,
+};
+use serde::{Deserialize, Serialize};
+use std::cmp::min;
+
+/// Size of the link-layer address field, of which only the first `addr_len` bytes are meaningful.
+pub static SLL_ADDR_SIZE: usize = 8;
+
+/// Where the packet was headed, relative to the capturing host.
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u16)]
+pub enum PacketType {
+    /// Sent to us by somebody else.
+    Host = 0,
+    /// Broadcast by somebody else.
+    Broadcast,
+    /// Multicast, but not broadcast, by somebody else.
+    Multicast,
+    /// Sent to somebody else by somebody else.
+    OtherHost,
+    /// Sent by us.
+    Outgoing,
+}
+
+impl PacketType {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Option<Self>> {
+        context("SLL packet type", map(be_u16, Self::try_from))(i)
+    }
+}
+
+/// Parses the address field, keeping the `addr_len` bytes that are in use.
+pub fn parse_addr(i: parse::Input, addr_len: usize) -> parse::ParseResult<Blob> {
+    context(
+        "SLL link-layer address",
+        map(take(SLL_ADDR_SIZE), |addr: parse::Input| {
+            Blob::new(&addr[..min(addr_len, SLL_ADDR_SIZE)])
+        }),
+    )(i)
+}
+
+/// The 16 byte pseudo-header libpcap puts in front of packets captured on the Linux "any" device (LINKTYPE_LINUX_SLL).
+#[derive(Serialize, Deserialize, CustomDebug)]
+pub struct Frame {
+    pub packet_type: Option<PacketType>,
+    /// The ARPHRD_* type of the device the packet was captured on.
+    #[debug(format = "{}")]
+    pub arphrd_type: u16,
+    #[debug(format = "{}")]
+    pub addr_len: u16,
+    pub addr: Blob,
+    pub protocol: Option<datalink::EtherType>,
+    pub payload: Option<datalink::Payload>,
+}
+
+impl Frame {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("Linux cooked capture", |i| {
+            let (i, (packet_type, arphrd_type, addr_len)) =
+                tuple((PacketType::parse, be_u16, be_u16))(i)?;
+            let (i, addr) = parse_addr(i, addr_len as usize)?;
+            let (i, protocol) = datalink::EtherType::parse(i)?;
+            let (i, payload) = datalink::Payload::parse(i, &protocol)?;
+
+            let res = Self {
+                packet_type,
+                arphrd_type,
+                addr_len,
+                addr,
+                protocol,
+                payload: Some(payload),
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An outgoing ICMP echo request captured on the "any" device.
+    const TEST_FRAME: &[u8] = &[
+        0x00, 0x04, 0x00, 0x01, 0x00, 0x06, 0x58, 0x00, 0xE3, 0x1D, 0x1E, 0x6B, 0x00, 0x00, 0x08,
+        0x00, 0x45, 0x00, 0x00, 0x1C, 0x00, 0x01, 0x00, 0x00, 0x40, 0x01, 0xF9, 0x60, 0xC0, 0xA8,
+        0x01, 0x43, 0xC0, 0xA8, 0x01, 0x01, 0x08, 0x00, 0xF7, 0xFE, 0x00, 0x01, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn assert_valid_frame() {
+        let frame = Frame::parse(TEST_FRAME).unwrap().1;
+
+        assert_eq!(frame.packet_type, Some(PacketType::Outgoing));
+        assert_eq!(frame.arphrd_type, 1);
+        assert_eq!(frame.addr.0, &TEST_FRAME[6..12]);
+        assert_eq!(frame.protocol, Some(datalink::EtherType::IPv4));
+        match frame.payload {
+            Some(datalink::Payload::IPv4(_)) => {}
+            _ => panic!("expected an IPv4 payload"),
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{
+    core::{blob::Blob, parse},
+    layer2::{datalink, sll},
+};
+
+use custom_debug_derive::*;
+use nom::{
+    combinator::map,
+    error::context,
+    number::complete::{be_u16, be_u32, be_u8},
+    sequence::tuple,
+};
+use serde::{Deserialize, Serialize};
+
+/// The 20 byte version 2 of the Linux cooked header (LINKTYPE_LINUX_SLL2), which moves the protocol first and adds the interface index.
+#[derive(Serialize, Deserialize, CustomDebug)]
+pub struct Frame {
+    pub protocol: Option<datalink::EtherType>,
+    #[debug(skip)]
+    pub reserved: u16,
+    #[debug(format = "{}")]
+    pub interface_index: u32,
+    /// The ARPHRD_* type of the device the packet was captured on.
+    #[debug(format = "{}")]
+    pub arphrd_type: u16,
+    pub packet_type: Option<sll::PacketType>,
+    #[debug(format = "{}")]
+    pub addr_len: u8,
+    pub addr: Blob,
+    pub payload: Option<datalink::Payload>,
+}
+
+impl Frame {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("Linux cooked capture v2", |i| {
+            let (i, protocol) = datalink::EtherType::parse(i)?;
+            let (i, (reserved, interface_index, arphrd_type)) = tuple((be_u16, be_u32, be_u16))(i)?;
+            let (i, packet_type) = map(be_u8, |t| sll::PacketType::try_from(t as u16))(i)?;
+            let (i, addr_len) = be_u8(i)?;
+            let (i, addr) = sll::parse_addr(i, addr_len as usize)?;
+            let (i, payload) = datalink::Payload::parse(i, &protocol)?;
+
+            let res = Self {
+                protocol,
+                reserved,
+                interface_index,
+                arphrd_type,
+                packet_type,
+                addr_len,
+                addr,
+                payload: Some(payload),
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An outgoing ICMP echo request captured on interface 2 through the "any" device.
+    const TEST_FRAME: &[u8] = &[
+        0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x01, 0x04, 0x06, 0x58, 0x00, 0xE3,
+        0x1D, 0x1E, 0x6B, 0x00, 0x00, 0x45, 0x00, 0x00, 0x1C, 0x00, 0x01, 0x00, 0x00, 0x40, 0x01,
+        0xF9, 0x60, 0xC0, 0xA8, 0x01, 0x43, 0xC0, 0xA8, 0x01, 0x01, 0x08, 0x00, 0xF7, 0xFE, 0x00,
+        0x01, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn assert_valid_frame() {
+        let (i, frame) = Frame::parse(TEST_FRAME).unwrap();
+
+        assert!(i.is_empty());
+        assert_eq!(frame.protocol, Some(datalink::EtherType::IPv4));
+        assert_eq!(frame.interface_index, 2);
+        assert_eq!(frame.arphrd_type, 1);
+        assert_eq!(frame.packet_type, Some(sll::PacketType::Outgoing));
+        assert_eq!(frame.addr.0, &TEST_FRAME[12..18]);
+        match frame.payload {
+            Some(datalink::Payload::IPv4(_)) => {}
+            _ => panic!("expected an IPv4 payload"),
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use crate::{core::parse, layer2::datalink::Addr};
+
+use custom_debug_derive::*;
+use derive_try_from_primitive::*;
+use nom::{
+    bytes::complete::take,
+    combinator::{cond, map},
+    error::context,
+    multi::count,
+    number::complete::{be_u16, be_u32, be_u8},
+    sequence::tuple,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Version {
+    STP = 0,
+    RSTP = 2,
+    MSTP = 3,
+}
+
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum BPDUType {
+    Configuration = 0x00,
+    /// Rapid and multiple spanning tree BPDUs, which share the configuration fields.
+    RST = 0x02,
+    TopologyChangeNotification = 0x80,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PortRole {
+    /// Unknown in RSTP, or the master port of an MSTI.
+    Master,
+    AlternateOrBackup,
+    Root,
+    Designated,
+}
+
+/// The flags of a BPDU (IEEE 802.1D 9.3.3). Plain STP only uses the topology change bits;
+/// the others were added by RSTP.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Flags(pub u8);
+
+impl fmt::Debug for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{:02X}", self.0)
+    }
+}
+
+impl Flags {
+    pub fn topology_change(&self) -> bool {
+        self.0 & 0x01 != 0
+    }
+
+    pub fn proposal(&self) -> bool {
+        self.0 & 0x02 != 0
+    }
+
+    pub fn port_role(&self) -> PortRole {
+        match (self.0 >> 2) & 0b11 {
+            0 => PortRole::Master,
+            1 => PortRole::AlternateOrBackup,
+            2 => PortRole::Root,
+            _ => PortRole::Designated,
+        }
+    }
+
+    pub fn learning(&self) -> bool {
+        self.0 & 0x10 != 0
+    }
+
+    pub fn forwarding(&self) -> bool {
+        self.0 & 0x20 != 0
+    }
+
+    pub fn agreement(&self) -> bool {
+        self.0 & 0x40 != 0
+    }
+
+    pub fn topology_change_ack(&self) -> bool {
+        self.0 & 0x80 != 0
+    }
+}
+
+/// A bridge priority followed by its MAC address. The lowest one wins the root election.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct BridgeId {
+    /// The priority in the top 4 bits, then the system ID extension, usually the VLAN or MSTI.
+    pub priority: u16,
+    pub addr: Addr,
+}
+
+impl BridgeId {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("STP bridge identifier", |i| {
+            let (i, (priority, addr)) = tuple((be_u16, Addr::parse))(i)?;
+            Ok((i, Self { priority, addr }))
+        })(i)
+    }
+
+    pub fn system_id_extension(&self) -> u16 {
+        self.priority & 0x0FFF
+    }
+}
+
+impl fmt::Display for BridgeId {
+    /// e.g. `32768/1/00:1B:2C:3D:4E:5F`, like switches print it.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}",
+            self.priority & 0xF000,
+            self.system_id_extension(),
+            self.addr
+        )
+    }
+}
+
+impl fmt::Debug for BridgeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// The fields of configuration and RST BPDUs. Times are in 1/256 of a second.
+#[derive(Serialize, Deserialize, CustomDebug)]
+pub struct Configuration {
+    pub flags: Flags,
+    pub root: BridgeId,
+    #[debug(format = "{}")]
+    pub root_path_cost: u32,
+    pub bridge: BridgeId,
+    #[debug(format = "0x{:04X}")]
+    pub port_id: u16,
+    #[debug(format = "{}")]
+    pub message_age: u16,
+    #[debug(format = "{}")]
+    pub max_age: u16,
+    #[debug(format = "{}")]
+    pub hello_time: u16,
+    #[debug(format = "{}")]
+    pub forward_delay: u16,
+}
+
+impl Configuration {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("STP configuration", |i| {
+            let (i, flags) = map(be_u8, Flags)(i)?;
+            let (i, (root, root_path_cost, bridge, port_id)) =
+                tuple((BridgeId::parse, be_u32, BridgeId::parse, be_u16))(i)?;
+            let (i, (message_age, max_age, hello_time, forward_delay)) =
+                tuple((be_u16, be_u16, be_u16, be_u16))(i)?;
+            let res = Self {
+                flags,
+                root,
+                root_path_cost,
+                bridge,
+                port_id,
+                message_age,
+                max_age,
+                hello_time,
+                forward_delay,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// The spanning tree of one MST instance (MSTI), as carried in MST BPDUs.
+#[derive(Serialize, Deserialize, CustomDebug)]
+pub struct MSTIConfiguration {
+    pub flags: Flags,
+    pub regional_root: BridgeId,
+    #[debug(format = "{}")]
+    pub internal_root_path_cost: u32,
+    /// The top 4 bits of the bridge priority.
+    #[debug(format = "0x{:02X}")]
+    pub bridge_priority: u8,
+    /// The top 4 bits of the port priority.
+    #[debug(format = "0x{:02X}")]
+    pub port_priority: u8,
+    #[debug(format = "{}")]
+    pub remaining_hops: u8,
+}
+
+impl MSTIConfiguration {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("MSTI configuration message", |i| {
+            let (i, flags) = map(be_u8, Flags)(i)?;
+            let (i, (regional_root, internal_root_path_cost)) =
+                tuple((BridgeId::parse, be_u32))(i)?;
+            let (i, (bridge_priority, port_priority, remaining_hops)) =
+                tuple((be_u8, be_u8, be_u8))(i)?;
+            let res = Self {
+                flags,
+                regional_root,
+                internal_root_path_cost,
+                bridge_priority,
+                port_priority,
+                remaining_hops,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// The fields MSTP adds after those of RSTP (IEEE 802.1Q 14.6). Bridges with the same configuration
+/// name, revision and digest of their VLAN to instance mapping are in the same MST region.
+#[derive(Serialize, Deserialize, CustomDebug)]
+pub struct MultipleSpanningTree {
+    #[debug(format = "{}")]
+    pub format_selector: u8,
+    pub configuration_name: String,
+    #[debug(format = "{}")]
+    pub revision: u16,
+    #[debug(format = "{:02X?}")]
+    pub digest: [u8; 16],
+    #[debug(format = "{}")]
+    pub cist_internal_root_path_cost: u32,
+    pub cist_bridge: BridgeId,
+    #[debug(format = "{}")]
+    pub cist_remaining_hops: u8,
+    pub instances: Vec<MSTIConfiguration>,
+}
+
+impl MultipleSpanningTree {
+    /// The length of the fields that come before the MSTI configuration messages.
+    const FIXED_LEN: u16 = 64;
+
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("MST BPDU", |i| {
+            let (i, version3_len) = be_u16(i)?;
+            let (i, format_selector) = be_u8(i)?;
+            let (i, configuration_name) = map(take(32_usize), |name: parse::Input| {
+                let len = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+                String::from_utf8_lossy(&name[..len]).into_owned()
+            })(i)?;
+            let (i, revision) = be_u16(i)?;
+            let (i, digest) = map(take(16_usize), |d: parse::Input| {
+                let mut digest = [0u8; 16];
+                digest.copy_from_slice(d);
+                digest
+            })(i)?;
+            let (i, (cist_internal_root_path_cost, cist_bridge, cist_remaining_hops)) =
+                tuple((be_u32, BridgeId::parse, be_u8))(i)?;
+            let instance_count = version3_len.saturating_sub(Self::FIXED_LEN) / 16;
+            let (i, instances) = count(MSTIConfiguration::parse, instance_count as usize)(i)?;
+            let res = Self {
+                format_selector,
+                configuration_name,
+                revision,
+                digest,
+                cist_internal_root_path_cost,
+                cist_bridge,
+                cist_remaining_hops,
+                instances,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// A spanning tree bridge protocol data unit, sent to the LLC SAP 0x42.
+#[derive(Serialize, Deserialize, CustomDebug)]
+pub struct BPDU {
+    #[debug(format = "0x{:04X}")]
+    pub protocol_id: u16,
+    pub version: Option<Version>,
+    pub bpdu_type: Option<BPDUType>,
+    /// Absent from topology change notifications.
+    pub configuration: Option<Configuration>,
+    pub mst: Option<MultipleSpanningTree>,
+}
+
+impl BPDU {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("STP BPDU", |i| {
+            let (i, (protocol_id, version, bpdu_type)) = tuple((
+                be_u16,
+                map(be_u8, Version::try_from),
+                map(be_u8, BPDUType::try_from),
+            ))(i)?;
+            let has_configuration = matches!(
+                bpdu_type,
+                Some(BPDUType::Configuration) | Some(BPDUType::RST)
+            );
+            let (i, configuration) = cond(has_configuration, Configuration::parse)(i)?;
+            // The Version 1 Length of RSTP, always 0.
+            let rst = bpdu_type == Some(BPDUType::RST);
+            let (i, _) = cond(rst, be_u8)(i)?;
+            let mstp = rst && version == Some(Version::MSTP) && !i.is_empty();
+            let (i, mst) = cond(mstp, MultipleSpanningTree::parse)(i)?;
+            let res = Self {
+                protocol_id,
+                version,
+                bpdu_type,
+                configuration,
+                mst,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+impl fmt::Display for BPDU {
+    /// e.g. `RSTP root 32768/0/00:1B:2C:3D:4E:5F cost 4 port 0x8001 Designated forwarding`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.version {
+            Some(version) => write!(f, "{:?}", version)?,
+            None => write!(f, "STP")?,
+        }
+        let config = match self.configuration {
+            Some(ref config) => config,
+            None => return write!(f, " topology change notification"),
+        };
+        write!(
+            f,
+            " root {} cost {} port 0x{:04X}",
+            config.root, config.root_path_cost, config.port_id
+        )?;
+        let flags = config.flags;
+        if self.bpdu_type == Some(BPDUType::RST) {
+            let state = match (flags.learning(), flags.forwarding()) {
+                (_, true) => "forwarding",
+                (true, false) => "learning",
+                _ => "discarding",
+            };
+            write!(f, " {:?} {}", flags.port_role(), state)?;
+            if flags.proposal() {
+                write!(f, " proposal")?;
+            }
+            if flags.agreement() {
+                write!(f, " agreement")?;
+            }
+        }
+        if flags.topology_change() {
+            write!(f, " TC")?;
+        }
+        if flags.topology_change_ack() {
+            write!(f, " TCA")?;
+        }
+        if let Some(ref mst) = self.mst {
+            write!(
+                f,
+                " region \"{}\" rev {} {} MSTIs",
+                mst.configuration_name,
+                mst.revision,
+                mst.instances.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_rst_bpdu() {
+        let bpdu: &[u8] = &[
+            0x00, 0x00, 0x02, 0x02, // RSTP, RST BPDU
+            0x3C, // designated, learning and forwarding
+            0x80, 0x00, 0x00, 0x1B, 0x2C, 0x3D, 0x4E, 0x5F, // root
+            0x00, 0x00, 0x00, 0x04, // root path cost
+            0x80, 0x01, 0x00, 0x1B, 0x2C, 0x3D, 0x4E, 0x60, // bridge
+            0x80, 0x01, // port
+            0x01, 0x00, 0x14, 0x00, 0x02, 0x00, 0x0F, 0x00, // 1, 20, 2 and 15 seconds
+            0x00, // version 1 length
+        ];
+        let (rest, bpdu) = BPDU::parse(bpdu).unwrap();
+        assert!(rest.is_empty());
+        let config = bpdu.configuration.as_ref().unwrap();
+        assert_eq!(config.flags.port_role(), PortRole::Designated);
+        assert_eq!(config.bridge.system_id_extension(), 1);
+        assert_eq!(config.hello_time, 2 * 256);
+        assert_eq!(
+            bpdu.to_string(),
+            "RSTP root 32768/0/00:1B:2C:3D:4E:5F cost 4 port 0x8001 Designated forwarding"
+        );
+    }
+}
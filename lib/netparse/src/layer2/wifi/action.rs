@@ -0,0 +1,1065 @@
+use crate::{
+    core::{blob::Blob, parse::*},
+    layer2::{
+        datalink::Addr,
+        wifi::{
+            management::{CommonFieldsElement, Element, Reason, Status},
+            mesh::{self, MeshPeering, MultihopAction},
+        },
+    },
+};
+
+use custom_debug_derive::*;
+use derive_try_from_primitive::*;
+use nom::{
+    bytes::complete::take,
+    combinator::{all_consuming, map},
+    error::context,
+    multi::many0,
+    number::complete::{le_i8, le_u16, le_u32, le_u8},
+    sequence::tuple,
+};
+use serde::{Deserialize, Serialize};
+
+/// The category of an action frame, its first byte.
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Category {
+    SpectrumManagement = 0,
+    QoS = 1,
+    DLS = 2,
+    BlockAck = 3,
+    Public = 4,
+    RadioMeasurement = 5,
+    FastBSSTransition = 6,
+    HT = 7,
+    SAQuery = 8,
+    ProtectedDualOfPublic = 9,
+    WNM = 10,
+    UnprotectedWNM = 11,
+    TDLS = 12,
+    Mesh = 13,
+    Multihop = 14,
+    SelfProtected = 15,
+    DMG = 16,
+    FastSessionTransfer = 18,
+    RobustAVStreaming = 19,
+    UnprotectedDMG = 20,
+    VHT = 21,
+    FILS = 26,
+    HE = 30,
+    ProtectedHE = 31,
+    VendorSpecificProtected = 126,
+    VendorSpecific = 127,
+}
+
+/// The body of an action frame: a category, an action code within it, and the fields of that action.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct ActionFrameBody {
+    pub category: Option<Category>,
+    #[debug(format = "{}")]
+    pub action_code: u8,
+    pub action: Action,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Action {
+    MeasurementRequest(DialogElements),
+    MeasurementReport(DialogElements),
+    TPCRequest(DialogElements),
+    TPCReport(DialogElements),
+    ChannelSwitch(Vec<Element>),
+    ADDTSRequest(DialogElements),
+    ADDTSResponse(ADDTSResponse),
+    DELTS(DELTS),
+    ADDBARequest(ADDBARequest),
+    ADDBAResponse(ADDBAResponse),
+    DELBA(DELBA),
+    RadioMeasurementRequest(RadioMeasurementRequest),
+    RadioMeasurementReport(DialogElements),
+    LinkMeasurementRequest(LinkMeasurementRequest),
+    NeighborReportRequest(DialogElements),
+    NeighborReportResponse(DialogElements),
+    BSSTransitionQuery(BSSTransitionQuery),
+    BSSTransitionRequest(BSSTransitionRequest),
+    BSSTransitionResponse(BSSTransitionResponse),
+    SAQueryRequest(SAQuery),
+    SAQueryResponse(SAQuery),
+    GASInitialRequest(GASInitialRequest),
+    GASInitialResponse(GASInitialResponse),
+    GASComebackRequest(DialogElements),
+    GASComebackResponse(GASComebackResponse),
+    FTRequest(FastTransition),
+    FTResponse(FastTransition),
+    FTConfirm(FastTransition),
+    FTAck(FastTransition),
+    MeshLinkMetricReport(Vec<Element>),
+    /// HWMP path requests, replies and errors.
+    MeshPathSelection(Vec<Element>),
+    MeshGateAnnouncement(Vec<Element>),
+    ProxyUpdate(MultihopAction),
+    ProxyUpdateConfirmation(MultihopAction),
+    MeshPeeringOpen(MeshPeering),
+    MeshPeeringConfirm(MeshPeering),
+    MeshPeeringClose(Vec<Element>),
+    /// The fields of an action we do not decode.
+    Unknown(Blob),
+}
+
+impl ActionFrameBody {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 Management Frame: action", |i| {
+            let (i, (category, action_code)) = tuple((map(le_u8, Category::try_from), le_u8))(i)?;
+            let (i, body) = take(i.len())(i)?;
+            let action = match Self::parse_action(category, action_code, body) {
+                Ok((_, action)) => action,
+                Err(_) => Action::Unknown(Blob::new(body)),
+            };
+            let res = Self {
+                category,
+                action_code,
+                action,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    fn parse_action(category: Option<Category>, code: u8, i: Input) -> ParseResult<Action> {
+        use Action as A;
+        use Category as C;
+
+        let category = match category {
+            Some(category) => category,
+            None => return Ok((&i[i.len()..], A::Unknown(Blob::new(i)))),
+        };
+        match (category, code) {
+            (C::SpectrumManagement, 0) => map(DialogElements::parse, A::MeasurementRequest)(i),
+            (C::SpectrumManagement, 1) => map(DialogElements::parse, A::MeasurementReport)(i),
+            (C::SpectrumManagement, 2) => map(DialogElements::parse, A::TPCRequest)(i),
+            (C::SpectrumManagement, 3) => map(DialogElements::parse, A::TPCReport)(i),
+            (C::SpectrumManagement, 4) => map(Element::parse_optional_fields, A::ChannelSwitch)(i),
+            (C::QoS, 0) => map(DialogElements::parse, A::ADDTSRequest)(i),
+            (C::QoS, 1) => map(ADDTSResponse::parse, A::ADDTSResponse)(i),
+            (C::QoS, 2) => map(DELTS::parse, A::DELTS)(i),
+            (C::BlockAck, 0) => map(ADDBARequest::parse, A::ADDBARequest)(i),
+            (C::BlockAck, 1) => map(ADDBAResponse::parse, A::ADDBAResponse)(i),
+            (C::BlockAck, 2) => map(DELBA::parse, A::DELBA)(i),
+            (C::RadioMeasurement, 0) => {
+                map(RadioMeasurementRequest::parse, A::RadioMeasurementRequest)(i)
+            }
+            (C::RadioMeasurement, 1) => map(DialogElements::parse, A::RadioMeasurementReport)(i),
+            (C::RadioMeasurement, 2) => {
+                map(LinkMeasurementRequest::parse, A::LinkMeasurementRequest)(i)
+            }
+            (C::RadioMeasurement, 4) => map(DialogElements::parse, A::NeighborReportRequest)(i),
+            (C::RadioMeasurement, 5) => map(DialogElements::parse, A::NeighborReportResponse)(i),
+            (C::WNM, 6) => map(BSSTransitionQuery::parse, A::BSSTransitionQuery)(i),
+            (C::WNM, 7) => map(BSSTransitionRequest::parse, A::BSSTransitionRequest)(i),
+            (C::WNM, 8) => map(BSSTransitionResponse::parse, A::BSSTransitionResponse)(i),
+            (C::SAQuery, 0) => map(SAQuery::parse, A::SAQueryRequest)(i),
+            (C::SAQuery, 1) => map(SAQuery::parse, A::SAQueryResponse)(i),
+            (C::Public, 10) | (C::ProtectedDualOfPublic, 10) => {
+                map(GASInitialRequest::parse, A::GASInitialRequest)(i)
+            }
+            (C::Public, 11) | (C::ProtectedDualOfPublic, 11) => {
+                map(GASInitialResponse::parse, A::GASInitialResponse)(i)
+            }
+            (C::Public, 12) | (C::ProtectedDualOfPublic, 12) => {
+                map(DialogElements::parse, A::GASComebackRequest)(i)
+            }
+            (C::Public, 13) | (C::ProtectedDualOfPublic, 13) => {
+                map(GASComebackResponse::parse, A::GASComebackResponse)(i)
+            }
+            (C::FastBSSTransition, 1) => map(FastTransition::parse_request, A::FTRequest)(i),
+            (C::FastBSSTransition, 2) => map(FastTransition::parse_response, A::FTResponse)(i),
+            (C::FastBSSTransition, 3) => map(FastTransition::parse_request, A::FTConfirm)(i),
+            (C::FastBSSTransition, 4) => map(FastTransition::parse_response, A::FTAck)(i),
+            (C::Mesh, 0) => map(Element::parse_optional_fields, A::MeshLinkMetricReport)(i),
+            (C::Mesh, 1) => map(Element::parse_optional_fields, A::MeshPathSelection)(i),
+            (C::Mesh, 2) => map(Element::parse_optional_fields, A::MeshGateAnnouncement)(i),
+            (C::Multihop, 0) => map(MultihopAction::parse, A::ProxyUpdate)(i),
+            (C::Multihop, 1) => map(MultihopAction::parse, A::ProxyUpdateConfirmation)(i),
+            (C::SelfProtected, 1) => map(MeshPeering::parse_open, A::MeshPeeringOpen)(i),
+            (C::SelfProtected, 2) => map(MeshPeering::parse_confirm, A::MeshPeeringConfirm)(i),
+            (C::SelfProtected, 3) => map(Element::parse_optional_fields, A::MeshPeeringClose)(i),
+            _ => Ok((&i[i.len()..], A::Unknown(Blob::new(i)))),
+        }
+    }
+
+    /// A short name for the action, such as "ADDBA Request".
+    pub fn name(&self) -> String {
+        use Action::*;
+        let name = match self.action {
+            MeasurementRequest(_) => "Measurement Request",
+            MeasurementReport(_) => "Measurement Report",
+            TPCRequest(_) => "TPC Request",
+            TPCReport(_) => "TPC Report",
+            ChannelSwitch(_) => "Channel Switch Announcement",
+            ADDTSRequest(_) => "ADDTS Request",
+            ADDTSResponse(_) => "ADDTS Response",
+            DELTS(_) => "DELTS",
+            ADDBARequest(_) => "ADDBA Request",
+            ADDBAResponse(_) => "ADDBA Response",
+            DELBA(_) => "DELBA",
+            RadioMeasurementRequest(_) => "Radio Measurement Request",
+            RadioMeasurementReport(_) => "Radio Measurement Report",
+            LinkMeasurementRequest(_) => "Link Measurement Request",
+            NeighborReportRequest(_) => "Neighbor Report Request",
+            NeighborReportResponse(_) => "Neighbor Report Response",
+            BSSTransitionQuery(_) => "BSS Transition Query",
+            BSSTransitionRequest(_) => "BSS Transition Request",
+            BSSTransitionResponse(_) => "BSS Transition Response",
+            SAQueryRequest(_) => "SA Query Request",
+            SAQueryResponse(_) => "SA Query Response",
+            GASInitialRequest(_) => "GAS Initial Request",
+            GASInitialResponse(_) => "GAS Initial Response",
+            GASComebackRequest(_) => "GAS Comeback Request",
+            GASComebackResponse(_) => "GAS Comeback Response",
+            FTRequest(_) => "FT Request",
+            FTResponse(_) => "FT Response",
+            FTConfirm(_) => "FT Confirm",
+            FTAck(_) => "FT Ack",
+            MeshLinkMetricReport(_) => "Mesh Link Metric Report",
+            MeshPathSelection(ref elements) => {
+                let paths = mesh::path_selection_summary(elements);
+                return format!("Mesh Path Selection {}", paths.join(", "))
+                    .trim_end()
+                    .to_string();
+            }
+            MeshGateAnnouncement(_) => "Mesh Gate Announcement",
+            ProxyUpdate(_) => "Proxy Update",
+            ProxyUpdateConfirmation(_) => "Proxy Update Confirmation",
+            MeshPeeringOpen(_) => "Mesh Peering Open",
+            MeshPeeringConfirm(_) => "Mesh Peering Confirm",
+            MeshPeeringClose(_) => "Mesh Peering Close",
+            Unknown(_) => {
+                return match self.category {
+                    Some(category) => format!("{:?} action {}", category, self.action_code),
+                    None => format!("Unknown category, action {}", self.action_code),
+                }
+            }
+        };
+        name.to_string()
+    }
+}
+
+/// A dialog token followed by elements, the layout shared by most request and report actions.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct DialogElements {
+    #[debug(format = "{}")]
+    pub dialog_token: u8,
+    pub elements: Vec<Element>,
+}
+
+impl DialogElements {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        let (i, dialog_token) = le_u8(i)?;
+        let (i, elements) = Element::parse_optional_fields(i)?;
+        Ok((
+            i,
+            Self {
+                dialog_token,
+                elements,
+            },
+        ))
+    }
+
+    /// The BSSs listed by the Neighbor Report elements of a neighbor report response.
+    pub fn neighbors(&self) -> Vec<&NeighborReport> {
+        neighbors(&self.elements)
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct ADDTSResponse {
+    #[debug(format = "{}")]
+    pub dialog_token: u8,
+    pub status_code: Status,
+    pub elements: Vec<Element>,
+}
+
+impl ADDTSResponse {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 ADDTS Response", |i| {
+            let (i, dialog_token) = le_u8(i)?;
+            let (i, status_code) = Status::parse(i)?;
+            let (i, elements) = Element::parse_optional_fields(i)?;
+            let res = Self {
+                dialog_token,
+                status_code,
+                elements,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct DELTS {
+    #[debug(format = "{:02X?}")]
+    pub ts_info: [u8; 3],
+    pub reason_code: Reason,
+}
+
+impl DELTS {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 DELTS", |i| {
+            let (i, ts_info) = map(take(3_usize), |t: Input| [t[0], t[1], t[2]])(i)?;
+            let (i, reason_code) = Reason::parse(i)?;
+            Ok((
+                i,
+                Self {
+                    ts_info,
+                    reason_code,
+                },
+            ))
+        })(i)
+    }
+
+    pub fn tid(&self) -> u8 {
+        self.ts_info[0] >> 1 & 0b1111
+    }
+}
+
+/// The Block Ack Parameter Set of ADDBA requests and responses.
+#[derive(CustomDebug, Serialize, Deserialize, Clone, Copy)]
+pub struct BlockAckParameters(#[debug(format = "0x{:04X}")] pub u16);
+
+impl BlockAckParameters {
+    pub fn amsdu_supported(self) -> bool {
+        self.0 & 0x0001 != 0
+    }
+
+    /// Immediate, rather than delayed, block ack.
+    pub fn immediate(self) -> bool {
+        self.0 & 0x0002 != 0
+    }
+
+    pub fn tid(self) -> u8 {
+        (self.0 >> 2 & 0b1111) as u8
+    }
+
+    pub fn buffer_size(self) -> u16 {
+        self.0 >> 6
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct ADDBARequest {
+    #[debug(format = "{}")]
+    pub dialog_token: u8,
+    pub parameters: BlockAckParameters,
+    /// In time units of 1024µs, 0 for no timeout.
+    #[debug(format = "{}")]
+    pub timeout: u16,
+    #[debug(format = "{}")]
+    pub starting_sequence_number: u16,
+}
+
+impl ADDBARequest {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 ADDBA Request", |i| {
+            let (i, (dialog_token, parameters, timeout, ssc)) =
+                tuple((le_u8, map(le_u16, BlockAckParameters), le_u16, le_u16))(i)?;
+            let res = Self {
+                dialog_token,
+                parameters,
+                timeout,
+                starting_sequence_number: ssc >> 4,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct ADDBAResponse {
+    #[debug(format = "{}")]
+    pub dialog_token: u8,
+    pub status_code: Status,
+    pub parameters: BlockAckParameters,
+    #[debug(format = "{}")]
+    pub timeout: u16,
+}
+
+impl ADDBAResponse {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 ADDBA Response", |i| {
+            let (i, dialog_token) = le_u8(i)?;
+            let (i, status_code) = Status::parse(i)?;
+            let (i, (parameters, timeout)) = tuple((map(le_u16, BlockAckParameters), le_u16))(i)?;
+            let res = Self {
+                dialog_token,
+                status_code,
+                parameters,
+                timeout,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct DELBA {
+    #[debug(format = "0x{:04X}")]
+    pub parameters: u16,
+    pub reason_code: Reason,
+}
+
+impl DELBA {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 DELBA", |i| {
+            let (i, parameters) = le_u16(i)?;
+            let (i, reason_code) = Reason::parse(i)?;
+            Ok((
+                i,
+                Self {
+                    parameters,
+                    reason_code,
+                },
+            ))
+        })(i)
+    }
+
+    /// Whether the originator of the block ack agreement, rather than its recipient, tears it down.
+    pub fn initiator(&self) -> bool {
+        self.parameters & 0x0800 != 0
+    }
+
+    pub fn tid(&self) -> u8 {
+        (self.parameters >> 12) as u8
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct RadioMeasurementRequest {
+    #[debug(format = "{}")]
+    pub dialog_token: u8,
+    #[debug(format = "{}")]
+    pub repetitions: u16,
+    pub elements: Vec<Element>,
+}
+
+impl RadioMeasurementRequest {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 Radio Measurement Request", |i| {
+            let (i, (dialog_token, repetitions)) = tuple((le_u8, le_u16))(i)?;
+            let (i, elements) = Element::parse_optional_fields(i)?;
+            let res = Self {
+                dialog_token,
+                repetitions,
+                elements,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct LinkMeasurementRequest {
+    #[debug(format = "{}")]
+    pub dialog_token: u8,
+    /// In dBm.
+    #[debug(format = "{}")]
+    pub transmit_power: i8,
+    #[debug(format = "{}")]
+    pub max_transmit_power: i8,
+    pub elements: Vec<Element>,
+}
+
+impl LinkMeasurementRequest {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 Link Measurement Request", |i| {
+            let (i, (dialog_token, transmit_power, max_transmit_power)) =
+                tuple((le_u8, le_i8, le_i8))(i)?;
+            let (i, elements) = Element::parse_optional_fields(i)?;
+            let res = Self {
+                dialog_token,
+                transmit_power,
+                max_transmit_power,
+                elements,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct BSSTransitionQuery {
+    #[debug(format = "{}")]
+    pub dialog_token: u8,
+    #[debug(format = "{}")]
+    pub reason: u8,
+    pub candidates: Vec<Element>,
+}
+
+impl BSSTransitionQuery {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 BSS Transition Management Query", |i| {
+            let (i, (dialog_token, reason)) = tuple((le_u8, le_u8))(i)?;
+            let (i, candidates) = Element::parse_optional_fields(i)?;
+            let res = Self {
+                dialog_token,
+                reason,
+                candidates,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// An AP steering a station towards other BSSs.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct BSSTransitionRequest {
+    #[debug(format = "{}")]
+    pub dialog_token: u8,
+    #[debug(format = "0x{:02X}")]
+    pub mode: u8,
+    /// Beacon intervals until the station is disassociated, when disassociation is imminent.
+    #[debug(format = "{}")]
+    pub disassociation_timer: u16,
+    /// Beacon intervals for which the candidate list is valid.
+    #[debug(format = "{}")]
+    pub validity_interval: u8,
+    pub termination_duration: Option<Blob>,
+    pub session_information_url: Option<String>,
+    pub candidates: Vec<Element>,
+}
+
+impl BSSTransitionRequest {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 BSS Transition Management Request", |i| {
+            let (i, (dialog_token, mode, disassociation_timer, validity_interval)) =
+                tuple((le_u8, le_u8, le_u16, le_u8))(i)?;
+            let mut res = Self {
+                dialog_token,
+                mode,
+                disassociation_timer,
+                validity_interval,
+                termination_duration: None,
+                session_information_url: None,
+                candidates: vec![],
+            };
+            let mut i = i;
+            if res.bss_termination_included() {
+                // The BSS Termination Duration subelement: ID, length, TSF and duration.
+                let (rest, duration) = take(12_usize)(i)?;
+                res.termination_duration = Some(Blob::new(duration));
+                i = rest;
+            }
+            if res.ess_disassociation_imminent() {
+                let (rest, len) = le_u8(i)?;
+                let (rest, url) = take(len)(rest)?;
+                res.session_information_url = Some(String::from_utf8_lossy(url).to_string());
+                i = rest;
+            }
+            let (i, candidates) = Element::parse_optional_fields(i)?;
+            res.candidates = candidates;
+            Ok((i, res))
+        })(i)
+    }
+
+    pub fn preferred_candidate_list(&self) -> bool {
+        self.mode & 0x01 != 0
+    }
+
+    pub fn abridged(&self) -> bool {
+        self.mode & 0x02 != 0
+    }
+
+    pub fn disassociation_imminent(&self) -> bool {
+        self.mode & 0x04 != 0
+    }
+
+    pub fn bss_termination_included(&self) -> bool {
+        self.mode & 0x08 != 0
+    }
+
+    pub fn ess_disassociation_imminent(&self) -> bool {
+        self.mode & 0x10 != 0
+    }
+
+    pub fn neighbors(&self) -> Vec<&NeighborReport> {
+        neighbors(&self.candidates)
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct BSSTransitionResponse {
+    #[debug(format = "{}")]
+    pub dialog_token: u8,
+    /// 0 when the station accepts the transition.
+    #[debug(format = "{}")]
+    pub status: u8,
+    #[debug(format = "{}")]
+    pub termination_delay: u8,
+    pub target_bssid: Option<Addr>,
+    pub candidates: Vec<Element>,
+}
+
+impl BSSTransitionResponse {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 BSS Transition Management Response", |i| {
+            let (i, (dialog_token, status, termination_delay)) = tuple((le_u8, le_u8, le_u8))(i)?;
+            // The target BSSID is only present when the station accepts.
+            let (i, target_bssid) = if status == 0 {
+                map(Addr::parse, Some)(i)?
+            } else {
+                (i, None)
+            };
+            let (i, candidates) = Element::parse_optional_fields(i)?;
+            let res = Self {
+                dialog_token,
+                status,
+                termination_delay,
+                target_bssid,
+                candidates,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// SA Query requests and responses, which check that a protected association is still alive.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct SAQuery {
+    #[debug(format = "0x{:04X}")]
+    pub transaction_id: u16,
+}
+
+impl SAQuery {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 SA Query", |i| {
+            let (i, transaction_id) = le_u16(i)?;
+            Ok((i, Self { transaction_id }))
+        })(i)
+    }
+}
+
+/// The Advertisement Protocol element (ID 108) naming the protocol a GAS exchange carries.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct AdvertisementProtocol {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    #[debug(format = "{:?}")]
+    pub protocol_ids: Vec<u8>,
+}
+
+impl AdvertisementProtocol {
+    pub const ANQP: u8 = 0;
+
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 Advertisement Protocol element", |i| {
+            let (i, (id, len)) = tuple((le_u8, le_u8))(i)?;
+            let (i, body) = take(len)(i)?;
+            // Each tuple is a query response info byte followed by the protocol ID.
+            let protocol_ids = body
+                .chunks(2)
+                .filter(|t| t.len() == 2)
+                .map(|t| t[1])
+                .collect();
+            let res = Self {
+                common: CommonFieldsElement { id, len },
+                protocol_ids,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    pub fn is_anqp(&self) -> bool {
+        self.protocol_ids.first() == Some(&Self::ANQP)
+    }
+}
+
+/// The query or response carried by a GAS frame, decoded when the protocol is ANQP.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum GASQuery {
+    ANQP(Vec<ANQPElement>),
+    Other(Blob),
+}
+
+impl GASQuery {
+    fn parse<'a>(protocol: &AdvertisementProtocol, i: Input<'a>) -> ParseResult<'a, Self> {
+        let (i, len) = le_u16(i)?;
+        let (i, query) = take(len)(i)?;
+        let anqp = if protocol.is_anqp() {
+            all_consuming(many0(ANQPElement::parse))(query).ok()
+        } else {
+            None
+        };
+        let res = match anqp {
+            Some((_, elements)) => GASQuery::ANQP(elements),
+            None => GASQuery::Other(Blob::new(query)),
+        };
+        Ok((i, res))
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct GASInitialRequest {
+    #[debug(format = "{}")]
+    pub dialog_token: u8,
+    pub advertisement_protocol: AdvertisementProtocol,
+    pub query: GASQuery,
+}
+
+impl GASInitialRequest {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 GAS Initial Request", |i| {
+            let (i, dialog_token) = le_u8(i)?;
+            let (i, advertisement_protocol) = AdvertisementProtocol::parse(i)?;
+            let (i, query) = GASQuery::parse(&advertisement_protocol, i)?;
+            let res = Self {
+                dialog_token,
+                advertisement_protocol,
+                query,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct GASInitialResponse {
+    #[debug(format = "{}")]
+    pub dialog_token: u8,
+    pub status_code: Status,
+    /// In time units of 1024µs. When not 0, the response follows in comeback responses.
+    #[debug(format = "{}")]
+    pub comeback_delay: u16,
+    pub advertisement_protocol: AdvertisementProtocol,
+    pub response: GASQuery,
+}
+
+impl GASInitialResponse {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 GAS Initial Response", |i| {
+            let (i, dialog_token) = le_u8(i)?;
+            let (i, status_code) = Status::parse(i)?;
+            let (i, comeback_delay) = le_u16(i)?;
+            let (i, advertisement_protocol) = AdvertisementProtocol::parse(i)?;
+            let (i, response) = GASQuery::parse(&advertisement_protocol, i)?;
+            let res = Self {
+                dialog_token,
+                status_code,
+                comeback_delay,
+                advertisement_protocol,
+                response,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// A fragment of a GAS response. Fragments are not reassembled, so the response is kept raw.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct GASComebackResponse {
+    #[debug(format = "{}")]
+    pub dialog_token: u8,
+    pub status_code: Status,
+    #[debug(format = "0x{:02X}")]
+    pub fragment_id: u8,
+    #[debug(format = "{}")]
+    pub comeback_delay: u16,
+    pub advertisement_protocol: AdvertisementProtocol,
+    pub response: Blob,
+}
+
+impl GASComebackResponse {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 GAS Comeback Response", |i| {
+            let (i, dialog_token) = le_u8(i)?;
+            let (i, status_code) = Status::parse(i)?;
+            let (i, (fragment_id, comeback_delay)) = tuple((le_u8, le_u16))(i)?;
+            let (i, advertisement_protocol) = AdvertisementProtocol::parse(i)?;
+            let (i, len) = le_u16(i)?;
+            let (i, response) = map(take(len), Blob::new)(i)?;
+            let res = Self {
+                dialog_token,
+                status_code,
+                fragment_id,
+                comeback_delay,
+                advertisement_protocol,
+                response,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    /// Set on every fragment but the last.
+    pub fn more_fragments(&self) -> bool {
+        self.fragment_id & 0x80 != 0
+    }
+}
+
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u16)]
+pub enum ANQPInfoID {
+    QueryList = 256,
+    CapabilityList = 257,
+    VenueName = 258,
+    EmergencyCallNumber = 259,
+    NetworkAuthenticationType = 260,
+    RoamingConsortium = 261,
+    IPAddressTypeAvailability = 262,
+    NAIRealm = 263,
+    CellularNetwork = 264,
+    GeoLocation = 265,
+    CivicLocation = 266,
+    LocationPublicURI = 267,
+    DomainName = 268,
+    EmergencyAlertURI = 269,
+    TDLSCapability = 270,
+    EmergencyNAI = 271,
+    NeighborReport = 272,
+    VenueURL = 277,
+    AdviceOfCharge = 278,
+    LocalContent = 279,
+    NetworkAuthenticationTypeWithTimestamp = 280,
+    VendorSpecific = 56797,
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct ANQPElement {
+    pub info_id: Option<ANQPInfoID>,
+    #[debug(format = "{}")]
+    pub raw_info_id: u16,
+    pub content: ANQPContent,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ANQPContent {
+    /// The info IDs of a query list or capability list.
+    InfoIDs(Vec<u16>),
+    DomainNames(Vec<String>),
+    Unknown(Blob),
+}
+
+impl ANQPElement {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("ANQP element", |i| {
+            let (i, (raw_info_id, len)) = tuple((le_u16, le_u16))(i)?;
+            let (i, body) = take(len)(i)?;
+            let info_id = ANQPInfoID::try_from(raw_info_id);
+            let decoded = match info_id {
+                Some(ANQPInfoID::QueryList) | Some(ANQPInfoID::CapabilityList) => {
+                    all_consuming(map(Self::info_ids, ANQPContent::InfoIDs))(body).ok()
+                }
+                Some(ANQPInfoID::DomainName) => {
+                    all_consuming(map(many0(Self::domain_name), ANQPContent::DomainNames))(body)
+                        .ok()
+                }
+                _ => None,
+            };
+            let content = match decoded {
+                Some((_, content)) => content,
+                None => ANQPContent::Unknown(Blob::new(body)),
+            };
+            let res = Self {
+                info_id,
+                raw_info_id,
+                content,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    fn info_ids(i: Input) -> ParseResult<Vec<u16>> {
+        many0(le_u16)(i)
+    }
+
+    fn domain_name(i: Input) -> ParseResult<String> {
+        let (i, len) = le_u8(i)?;
+        map(take(len), |n: Input| String::from_utf8_lossy(n).to_string())(i)
+    }
+}
+
+/// The fields shared by the four fast BSS transition actions over the DS.
+/// Requests and confirms carry no status code.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct FastTransition {
+    pub sta_addr: Addr,
+    pub target_ap_addr: Addr,
+    pub status_code: Option<Status>,
+    pub elements: Vec<Element>,
+}
+
+impl FastTransition {
+    pub fn parse_request(i: Input) -> ParseResult<Self> {
+        context("802.11 FT Request", |i| {
+            let (i, (sta_addr, target_ap_addr)) = tuple((Addr::parse, Addr::parse))(i)?;
+            let (i, elements) = Element::parse_optional_fields(i)?;
+            let res = Self {
+                sta_addr,
+                target_ap_addr,
+                status_code: None,
+                elements,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    pub fn parse_response(i: Input) -> ParseResult<Self> {
+        context("802.11 FT Response", |i| {
+            let (i, (sta_addr, target_ap_addr)) = tuple((Addr::parse, Addr::parse))(i)?;
+            let (i, status_code) = Status::parse(i)?;
+            let (i, elements) = Element::parse_optional_fields(i)?;
+            let res = Self {
+                sta_addr,
+                target_ap_addr,
+                status_code: Some(status_code),
+                elements,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// The Neighbor Report element (ID 52), describing a BSS a station may roam to.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct NeighborReport {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    pub bssid: Addr,
+    #[debug(format = "0x{:08X}")]
+    pub bssid_info: u32,
+    #[debug(format = "{}")]
+    pub operating_class: u8,
+    #[debug(format = "{}")]
+    pub channel: u8,
+    #[debug(format = "{}")]
+    pub phy_type: u8,
+    pub subelements: Blob,
+}
+
+impl NeighborReport {
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Management Frame Neighbor Report", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (i, body) = take(len)(i)?;
+            let (subelements, (bssid, bssid_info, operating_class, channel, phy_type)) =
+                tuple((Addr::parse, le_u32, le_u8, le_u8, le_u8))(body)?;
+            let res = Self {
+                common,
+                bssid,
+                bssid_info,
+                operating_class,
+                channel,
+                phy_type,
+                subelements: Blob::new(subelements),
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    /// 1 when the AP cannot be reached, 2 when unknown and 3 when reachable.
+    pub fn reachability(&self) -> u8 {
+        (self.bssid_info & 0b11) as u8
+    }
+
+    /// Whether the BSS has the same security as the current one.
+    pub fn security(&self) -> bool {
+        self.bssid_info & 0x04 != 0
+    }
+
+    /// Whether the BSS is in the same mobility domain, so that fast transition applies.
+    pub fn mobility_domain(&self) -> bool {
+        self.bssid_info & 0x400 != 0
+    }
+
+    /// The BSS Transition Candidate Preference subelement, where 255 is the most preferred.
+    pub fn preference(&self) -> Option<u8> {
+        let mut i = &self.subelements.0[..];
+        while i.len() >= 2 {
+            let (id, len) = (i[0], i[1] as usize);
+            let body = i.get(2..2 + len)?;
+            if id == 3 {
+                return body.first().copied();
+            }
+            i = &i[2 + len..];
+        }
+        None
+    }
+}
+
+/// Every Neighbor Report element among `elements`.
+pub fn neighbors(elements: &[Element]) -> Vec<&NeighborReport> {
+    elements
+        .iter()
+        .filter_map(|e| match e {
+            Element::NeighborReport(n) => Some(n),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_actions() {
+        let addba: &[u8] = &[
+            0x03, 0x00, 0x07, // Block Ack, ADDBA Request, dialog token 7
+            0x1B, 0x10, // A-MSDU, immediate, TID 6, 64 buffers
+            0x00, 0x00, 0x50, 0x01, // no timeout, starting sequence 21
+        ];
+        let (_, body) = ActionFrameBody::parse(addba).unwrap();
+        assert_eq!(body.category, Some(Category::BlockAck));
+        assert_eq!(body.name(), "ADDBA Request");
+        match body.action {
+            Action::ADDBARequest(ref req) => {
+                assert!(req.parameters.amsdu_supported() && req.parameters.immediate());
+                assert_eq!(req.parameters.tid(), 6);
+                assert_eq!(req.parameters.buffer_size(), 64);
+                assert_eq!(req.starting_sequence_number, 21);
+            }
+            ref a => panic!("expected an ADDBA request, got {:?}", a),
+        }
+
+        let btm: &[u8] = &[
+            0x0A, 0x07, 0x01, // WNM, BSS Transition Request, dialog token 1
+            0x05, 0x0A, 0x00, 0xFF, // candidate list, disassociation imminent in 10 TBTTs
+            52, 16, 0x02, 0x11, 0x22, 0x33, 0x44, 0x55, // neighbor report
+            0x0F, 0x04, 0x00, 0x00, 0x73, 0x24, 0x07, // reachable, FT, channel 36
+            0x03, 0x01, 0xFF, // candidate preference 255
+        ];
+        let (_, body) = ActionFrameBody::parse(btm).unwrap();
+        let req = match body.action {
+            Action::BSSTransitionRequest(req) => req,
+            a => panic!("expected a BSS transition request, got {:?}", a),
+        };
+        assert!(req.preferred_candidate_list() && req.disassociation_imminent());
+        assert_eq!(req.disassociation_timer, 10);
+        let neighbors = req.neighbors();
+        assert_eq!(neighbors.len(), 1);
+        assert_eq!(neighbors[0].channel, 36);
+        assert_eq!(neighbors[0].reachability(), 3);
+        assert!(neighbors[0].mobility_domain());
+        assert_eq!(neighbors[0].preference(), Some(255));
+
+        let gas: &[u8] = &[
+            0x04, 0x0A, 0x02, // Public, GAS Initial Request, dialog token 2
+            108, 2, 0x7F, 0x00, // ANQP
+            0x08, 0x00, 0x00, 0x01, 0x04, 0x00, 0x07, 0x01, 0x0C, 0x01, // query list
+        ];
+        let (_, body) = ActionFrameBody::parse(gas).unwrap();
+        match body.action {
+            Action::GASInitialRequest(GASInitialRequest {
+                query: GASQuery::ANQP(ref elements),
+                ..
+            }) => {
+                assert_eq!(elements[0].info_id, Some(ANQPInfoID::QueryList));
+                match elements[0].content {
+                    ANQPContent::InfoIDs(ref ids) => assert_eq!(ids, &vec![263, 268]),
+                    ref c => panic!("expected info IDs, got {:?}", c),
+                }
+            }
+            ref a => panic!("expected an ANQP query, got {:?}", a),
+        }
+
+        let unknown: &[u8] = &[0x7F, 0x00, 0x17, 0x35];
+        let (_, body) = ActionFrameBody::parse(unknown).unwrap();
+        assert_eq!(body.name(), "VendorSpecific action 0");
+    }
+}
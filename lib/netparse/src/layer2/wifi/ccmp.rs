@@ -0,0 +1,180 @@
+//! CCMP (IEEE 802.11 12.5.3): AES in CCM mode, the cipher suite of WPA2.
+
+use crate::core::aes::Aes128;
+
+/// The CCMP header that starts the frame body: the packet number and key ID.
+pub const HEADER_SIZE: usize = 8;
+pub const MIC_SIZE: usize = 8;
+
+/// The 48-bit packet number of a CCMP header, which is also the replay counter of the key.
+pub fn packet_number(header: &[u8]) -> u64 {
+    [
+        header[7], header[6], header[5], header[4], header[1], header[0],
+    ]
+    .iter()
+    .fold(0, |acc, b| acc << 8 | *b as u64)
+}
+
+/// The key ID of a CCMP or TKIP header, which tells which of the group keys the frame uses.
+pub fn key_id(header: &[u8]) -> u8 {
+    header[3] >> 6
+}
+
+/// The nonce and additional authentication data of a frame, from its MAC header.
+fn nonce_and_aad(mac_header: &[u8], pn: u64) -> ([u8; 13], Vec<u8>) {
+    let (fc0, fc1) = (mac_header[0], mac_header[1]);
+    let management = fc0 & 0b1100 == 0;
+    let qos = fc0 & 0b1100 == 0b1000 && fc0 & 0x80 != 0;
+    let has_addr4 = fc1 & 0b11 == 0b11;
+    let qos_offset = if has_addr4 { 30 } else { 24 };
+    let tid = if qos {
+        mac_header[qos_offset] & 0x0F
+    } else {
+        0
+    };
+
+    let mut nonce = [0u8; 13];
+    nonce[0] = tid | if management { 0x10 } else { 0 };
+    nonce[1..7].copy_from_slice(&mac_header[10..16]);
+    nonce[7..].copy_from_slice(&pn.to_be_bytes()[2..]);
+
+    // The subtype of data frames, and the flags that may change on retransmission, are masked out.
+    let mut aad = Vec::with_capacity(30);
+    aad.push(if management { fc0 } else { fc0 & 0x8F });
+    aad.push(fc1 & if qos { 0x47 } else { 0xC7 } | 0x40);
+    aad.extend_from_slice(&mac_header[4..22]);
+    aad.extend_from_slice(&[mac_header[22] & 0x0F, 0]);
+    if has_addr4 {
+        aad.extend_from_slice(&mac_header[24..30]);
+    }
+    if qos {
+        aad.extend_from_slice(&[tid, 0]);
+    }
+    (nonce, aad)
+}
+
+/// The CTR mode key stream for the nonce, XORed into `data`.
+fn apply_key_stream(aes: &Aes128, nonce: &[u8; 13], data: &mut [u8]) {
+    for (i, chunk) in data.chunks_mut(16).enumerate() {
+        let key_stream = counter_block(aes, nonce, i as u16 + 1);
+        for (b, k) in chunk.iter_mut().zip(key_stream.iter()) {
+            *b ^= k;
+        }
+    }
+}
+
+fn counter_block(aes: &Aes128, nonce: &[u8; 13], counter: u16) -> [u8; 16] {
+    let mut block = [0u8; 16];
+    block[0] = 0x01;
+    block[1..14].copy_from_slice(nonce);
+    block[14..].copy_from_slice(&counter.to_be_bytes());
+    aes.encrypt_block(&mut block);
+    block
+}
+
+/// The MIC of a plaintext: its CBC-MAC over B0, the length-prefixed AAD and the plaintext, each
+/// padded to a block, then encrypted with the first counter block.
+fn mic(aes: &Aes128, nonce: &[u8; 13], aad: &[u8], plaintext: &[u8]) -> [u8; MIC_SIZE] {
+    let mut mac = [0u8; 16];
+    mac[0] = 0x59;
+    mac[1..14].copy_from_slice(nonce);
+    mac[14..].copy_from_slice(&(plaintext.len() as u16).to_be_bytes());
+    aes.encrypt_block(&mut mac);
+    let mut header = (aad.len() as u16).to_be_bytes().to_vec();
+    header.extend_from_slice(aad);
+    for chunk in header.chunks(16).chain(plaintext.chunks(16)) {
+        for (m, b) in mac.iter_mut().zip(chunk.iter()) {
+            *m ^= b;
+        }
+        aes.encrypt_block(&mut mac);
+    }
+
+    let s0 = counter_block(aes, nonce, 0);
+    let mut res = [0u8; MIC_SIZE];
+    for (r, (m, s)) in res.iter_mut().zip(mac.iter().zip(s0.iter())) {
+        *r = m ^ s;
+    }
+    res
+}
+
+/// Decrypts the body of a frame protected with the temporal key `tk`, checking its MIC.
+/// `mac_header` is the header of the MPDU, and `body` the frame body that follows, without FCS.
+pub fn decrypt(tk: &[u8; 16], mac_header: &[u8], body: &[u8]) -> Option<Vec<u8>> {
+    if body.len() < HEADER_SIZE + MIC_SIZE || mac_header.len() < 24 {
+        return None;
+    }
+    let (nonce, aad) = nonce_and_aad(mac_header, packet_number(body));
+    let aes = Aes128::new(tk);
+    let (ciphertext, received) = body[HEADER_SIZE..].split_at(body.len() - HEADER_SIZE - MIC_SIZE);
+
+    let mut plaintext = ciphertext.to_vec();
+    apply_key_stream(&aes, &nonce, &mut plaintext);
+    if mic(&aes, &nonce, &aad, &plaintext) == received {
+        Some(plaintext)
+    } else {
+        None
+    }
+}
+
+/// Protects `plaintext` with the temporal key `tk`, returning the frame body that follows
+/// `mac_header`: the CCMP header for packet number `pn` and key `key_id`, the ciphertext and the MIC.
+pub fn encrypt(tk: &[u8; 16], mac_header: &[u8], pn: u64, key_id: u8, plaintext: &[u8]) -> Vec<u8> {
+    let pn_bytes = pn.to_le_bytes();
+    let mut body = vec![pn_bytes[0], pn_bytes[1], 0, 0x20 | key_id << 6];
+    body.extend_from_slice(&pn_bytes[2..6]);
+
+    let (nonce, aad) = nonce_and_aad(mac_header, pn);
+    let aes = Aes128::new(tk);
+    let mic = mic(&aes, &nonce, &aad, plaintext);
+    let mut ciphertext = plaintext.to_vec();
+    apply_key_stream(&aes, &nonce, &mut ciphertext);
+    body.extend_from_slice(&ciphertext);
+    body.extend_from_slice(&mic);
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(s: &str) -> Vec<u8> {
+        s.split_whitespace()
+            .map(|b| u8::from_str_radix(b, 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn assert_vector() {
+        // IEEE 802.11 M.6.4
+        let mut tk = [0u8; 16];
+        tk.copy_from_slice(&hex("c9 7c 1f 67 ce 37 11 85 51 4a 8a 19 f2 bd d5 2f"));
+        let mpdu = hex(
+            "08 48 c3 2c 0f d2 e1 28 a5 7c 50 30 f1 84 44 08 ab ae a5 b8 fc ba 80 33 \
+             0c e7 00 20 76 97 03 b5 f3 d0 a2 fe 9a 3d bf 23 42 a6 43 e4 32 46 e8 0c \
+             3c 04 d0 19 78 45 ce 0b 16 f9 76 23",
+        );
+        let (header, body) = mpdu.split_at(24);
+        assert_eq!(packet_number(body), 0xB503_9776_E70C);
+        assert_eq!(
+            decrypt(&tk, header, body),
+            Some(hex(
+                "f8 ba 1a 55 d0 2f 85 ae 96 7b b6 2f b6 cd a8 eb 7e 78 a0 50"
+            ))
+        );
+
+        assert_eq!(
+            encrypt(
+                &tk,
+                header,
+                0xB503_9776_E70C,
+                0,
+                &hex("f8 ba 1a 55 d0 2f 85 ae 96 7b b6 2f b6 cd a8 eb 7e 78 a0 50")
+            ),
+            body
+        );
+
+        let mut tampered = body.to_vec();
+        tampered[10] ^= 1;
+        assert_eq!(decrypt(&tk, header, &tampered), None);
+    }
+}
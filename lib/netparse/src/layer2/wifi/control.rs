@@ -0,0 +1,533 @@
+use crate::{
+    core::{blob::Blob, parse},
+    layer2::{
+        datalink::Addr,
+        wifi::dot11::{FrameControl, HTControl, Subtype},
+    },
+};
+
+use custom_debug_derive::*;
+use derive_try_from_primitive::*;
+use nom::{
+    bytes::complete::take,
+    combinator::{map, peek},
+    error::context,
+    multi::count,
+    number::complete::{le_u16, le_u32, le_u64, le_u8},
+    sequence::tuple,
+};
+use serde::{Deserialize, Serialize};
+
+/// The body of a control frame, following its addresses.
+/// RTS, CTS, ACK and CF-End frames have no body.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ControlFrameBody {
+    BlockAckRequest(BlockAckRequest),
+    BlockAck(BlockAck),
+    Trigger(Trigger),
+    NDPAnnouncement(NDPAnnouncement),
+    /// The feedback segments a beamformer asks to be sent again.
+    BeamformingReportPoll(u8),
+    ControlWrapper(ControlWrapper),
+    /// The body of a subtype we do not decode, such as DMG control frame extensions.
+    Unknown(Blob),
+}
+
+impl ControlFrameBody {
+    pub fn parse<'a>(subtype: &Subtype, i: parse::Input<'a>) -> parse::ParseResult<'a, Self> {
+        context(
+            "802.11 Control Frame body",
+            |i: parse::Input<'a>| match subtype {
+                Subtype::BAR => map(BlockAckRequest::parse, ControlFrameBody::BlockAckRequest)(i),
+                Subtype::BA => map(BlockAck::parse, ControlFrameBody::BlockAck)(i),
+                Subtype::Trigger => map(Trigger::parse, ControlFrameBody::Trigger)(i),
+                Subtype::VHT_OR_HE_NDP_Announcement => {
+                    map(NDPAnnouncement::parse, ControlFrameBody::NDPAnnouncement)(i)
+                }
+                Subtype::BeamformingReportPoll => {
+                    map(le_u8, ControlFrameBody::BeamformingReportPoll)(i)
+                }
+                Subtype::ControlWrapper => {
+                    map(ControlWrapper::parse, ControlFrameBody::ControlWrapper)(i)
+                }
+                _ => map(take(i.len()), |b| ControlFrameBody::Unknown(Blob::new(b)))(i),
+            },
+        )(i)
+    }
+}
+
+/// The variants of block ack and block ack request frames.
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum BlockAckType {
+    Basic = 0,
+    ExtendedCompressed = 1,
+    Compressed = 2,
+    MultiTID = 3,
+    GCR = 6,
+    GLKGCR = 10,
+    MultiSTA = 11,
+}
+
+/// The BA Control and BAR Control fields.
+#[derive(CustomDebug, Serialize, Deserialize, Clone, Copy)]
+pub struct BlockAckControl(#[debug(format = "0x{:04X}")] pub u16);
+
+impl BlockAckControl {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        map(le_u16, Self)(i)
+    }
+
+    /// Set when the block ack is not to be acknowledged.
+    pub fn no_ack(self) -> bool {
+        self.0 & 0x0001 != 0
+    }
+
+    pub fn block_ack_type(self) -> Option<BlockAckType> {
+        BlockAckType::try_from((self.0 >> 1 & 0b1111) as u8)
+    }
+
+    /// The TID, or for multi-TID variants the number of TIDs minus one.
+    pub fn tid_info(self) -> u8 {
+        (self.0 >> 12) as u8
+    }
+}
+
+/// A Starting Sequence Control field and the TID it applies to.
+#[derive(CustomDebug, Serialize, Deserialize, Clone, Copy)]
+pub struct BlockAckStart {
+    #[debug(format = "{}")]
+    pub tid: u8,
+    #[debug(format = "{}")]
+    pub starting_sequence_number: u16,
+    #[debug(format = "{}")]
+    pub fragment_number: u8,
+}
+
+impl BlockAckStart {
+    fn parse(tid: u8) -> impl Fn(parse::Input) -> parse::ParseResult<Self> {
+        move |i| {
+            let (i, ssc) = le_u16(i)?;
+            let res = Self {
+                tid,
+                starting_sequence_number: ssc >> 4,
+                fragment_number: (ssc & 0b1111) as u8,
+            };
+            Ok((i, res))
+        }
+    }
+
+    /// The Per TID Info field of multi-TID variants carries the TID, followed by the Starting Sequence Control.
+    fn parse_per_tid(i: parse::Input) -> parse::ParseResult<Self> {
+        let (i, per_tid_info) = le_u16(i)?;
+        Self::parse((per_tid_info >> 12) as u8)(i)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum BlockAckRequestInfo {
+    /// Basic, compressed and extended compressed requests, for the TID of the control field.
+    Single(BlockAckStart),
+    MultiTID(Vec<BlockAckStart>),
+    GCR {
+        start: BlockAckStart,
+        group_address: Addr,
+    },
+    Unknown(Blob),
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct BlockAckRequest {
+    pub control: BlockAckControl,
+    pub info: BlockAckRequestInfo,
+}
+
+impl BlockAckRequest {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("802.11 Block Ack Request", |i| {
+            use BlockAckType::*;
+
+            let (i, control) = BlockAckControl::parse(i)?;
+            let tid = control.tid_info();
+            let (i, info) = match control.block_ack_type() {
+                Some(Basic) | Some(Compressed) | Some(ExtendedCompressed) => {
+                    map(BlockAckStart::parse(tid), BlockAckRequestInfo::Single)(i)?
+                }
+                Some(MultiTID) => map(
+                    count(BlockAckStart::parse_per_tid, tid as usize + 1),
+                    BlockAckRequestInfo::MultiTID,
+                )(i)?,
+                Some(GCR) => {
+                    let (i, (start, group_address)) =
+                        tuple((BlockAckStart::parse(tid), Addr::parse))(i)?;
+                    (
+                        i,
+                        BlockAckRequestInfo::GCR {
+                            start,
+                            group_address,
+                        },
+                    )
+                }
+                _ => map(take(i.len()), |b| {
+                    BlockAckRequestInfo::Unknown(Blob::new(b))
+                })(i)?,
+            };
+            Ok((i, Self { control, info }))
+        })(i)
+    }
+}
+
+/// A Block Ack Bitmap and the sequence number of its first bit.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct BlockAckBitmap {
+    pub start: BlockAckStart,
+    pub bitmap: Blob,
+}
+
+impl BlockAckBitmap {
+    fn parse(start: BlockAckStart, len: usize, i: parse::Input) -> parse::ParseResult<Self> {
+        let (i, bitmap) = map(take(len), Blob::new)(i)?;
+        Ok((i, Self { start, bitmap }))
+    }
+
+    /// Compressed bitmaps are 8 bytes long, except in HE and EHT where the fragment number tells their size.
+    fn compressed_len(start: &BlockAckStart) -> usize {
+        match start.fragment_number >> 1 & 0b11 {
+            1 => 32,
+            2 => 16,
+            3 => 128,
+            _ => 8,
+        }
+    }
+
+    /// Whether the MSDU with sequence number `seq` was received, in a compressed bitmap of one bit per MSDU.
+    pub fn acked(&self, seq: u16) -> bool {
+        let offset = (seq.wrapping_sub(self.start.starting_sequence_number) & 0xFFF) as usize;
+        match self.bitmap.0.get(offset / 8) {
+            Some(byte) => byte & (1 << (offset % 8)) != 0,
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum BlockAckInfo {
+    /// A bitmap of 16 bits per MSDU, one for each fragment.
+    Basic(BlockAckBitmap),
+    Compressed(BlockAckBitmap),
+    ExtendedCompressed {
+        bitmap: BlockAckBitmap,
+        /// The reorder buffer capacity of the recipient.
+        rbufcap: u8,
+    },
+    MultiTID(Vec<BlockAckBitmap>),
+    GCR {
+        bitmap: BlockAckBitmap,
+        group_address: Addr,
+    },
+    /// Multi-STA block acks and unknown variants.
+    Unknown(Blob),
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct BlockAck {
+    pub control: BlockAckControl,
+    pub info: BlockAckInfo,
+}
+
+impl BlockAck {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("802.11 Block Ack", |i| {
+            use BlockAckType::*;
+
+            let (i, control) = BlockAckControl::parse(i)?;
+            let tid = control.tid_info();
+            let (i, info) = match control.block_ack_type() {
+                Some(Basic) => {
+                    let (i, start) = BlockAckStart::parse(tid)(i)?;
+                    let (i, bitmap) = BlockAckBitmap::parse(start, 128, i)?;
+                    (i, BlockAckInfo::Basic(bitmap))
+                }
+                Some(Compressed) => {
+                    let (i, start) = BlockAckStart::parse(tid)(i)?;
+                    let len = BlockAckBitmap::compressed_len(&start);
+                    let (i, bitmap) = BlockAckBitmap::parse(start, len, i)?;
+                    (i, BlockAckInfo::Compressed(bitmap))
+                }
+                Some(ExtendedCompressed) => {
+                    let (i, start) = BlockAckStart::parse(tid)(i)?;
+                    let (i, bitmap) = BlockAckBitmap::parse(start, 8, i)?;
+                    let (i, rbufcap) = le_u8(i)?;
+                    (i, BlockAckInfo::ExtendedCompressed { bitmap, rbufcap })
+                }
+                Some(MultiTID) => {
+                    let per_tid = |i| {
+                        let (i, start) = BlockAckStart::parse_per_tid(i)?;
+                        BlockAckBitmap::parse(start, 8, i)
+                    };
+                    map(count(per_tid, tid as usize + 1), BlockAckInfo::MultiTID)(i)?
+                }
+                Some(GCR) => {
+                    let (i, start) = BlockAckStart::parse(tid)(i)?;
+                    let (i, group_address) = Addr::parse(i)?;
+                    let (i, bitmap) = BlockAckBitmap::parse(start, 8, i)?;
+                    (
+                        i,
+                        BlockAckInfo::GCR {
+                            bitmap,
+                            group_address,
+                        },
+                    )
+                }
+                _ => map(take(i.len()), |b| BlockAckInfo::Unknown(Blob::new(b)))(i)?,
+            };
+            Ok((i, Self { control, info }))
+        })(i)
+    }
+}
+
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum TriggerType {
+    Basic = 0,
+    BeamformingReportPoll = 1,
+    MUBAR = 2,
+    MURTS = 3,
+    BufferStatusReportPoll = 4,
+    GCRMUBAR = 5,
+    BandwidthQueryReportPoll = 6,
+    NDPFeedbackReportPoll = 7,
+}
+
+/// An HE Trigger frame, soliciting uplink transmissions from the stations of its User Info fields.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct Trigger {
+    #[debug(format = "0x{:016X}")]
+    pub common_info: u64,
+    pub trigger_type: Option<TriggerType>,
+    pub user_info: Vec<TriggerUserInfo>,
+}
+
+impl Trigger {
+    /// The AID12 that starts the padding after the last User Info field.
+    const PADDING_AID: u16 = 4095;
+
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("802.11 Trigger", |i| {
+            let (mut i, common_info) = le_u64(i)?;
+            let trigger_type = TriggerType::try_from((common_info & 0b1111) as u8);
+            let mut user_info = vec![];
+            while i.len() >= 5 {
+                let (_, aid) = peek(le_u16)(i)?;
+                if aid & 0xFFF == Self::PADDING_AID {
+                    break;
+                }
+                let (rest, info) = TriggerUserInfo::parse(trigger_type, i)?;
+                user_info.push(info);
+                i = rest;
+            }
+            let (i, _) = take(i.len())(i)?;
+            let res = Self {
+                common_info,
+                trigger_type,
+                user_info,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    /// The length of the solicited HE TB PPDUs, in the L-SIG length.
+    pub fn ul_length(&self) -> u16 {
+        (self.common_info >> 4 & 0xFFF) as u16
+    }
+
+    /// Another trigger frame follows in the same TXOP.
+    pub fn more_tf(&self) -> bool {
+        self.common_info & 1 << 16 != 0
+    }
+
+    pub fn cs_required(&self) -> bool {
+        self.common_info & 1 << 17 != 0
+    }
+
+    pub fn ul_bandwidth_mhz(&self) -> u16 {
+        20 << (self.common_info >> 18 & 0b11)
+    }
+}
+
+/// The User Info field addressed to one station, with the trigger dependent part kept raw.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct TriggerUserInfo {
+    #[debug(format = "0x{:010X}")]
+    pub info: u64,
+    pub trigger_dependent: Blob,
+}
+
+impl TriggerUserInfo {
+    fn parse(trigger_type: Option<TriggerType>, i: parse::Input) -> parse::ParseResult<Self> {
+        let (i, (low, high)) = tuple((le_u32, le_u8))(i)?;
+        let dependent_len = match trigger_type {
+            Some(TriggerType::Basic) | Some(TriggerType::BeamformingReportPoll) => 1,
+            // A BAR Control and BAR Information, as in a block ack request.
+            Some(TriggerType::MUBAR) => {
+                let (rest, _) = BlockAckRequest::parse(i)?;
+                i.len() - rest.len()
+            }
+            _ => 0,
+        };
+        let (i, trigger_dependent) = map(take(dependent_len), Blob::new)(i)?;
+        let res = Self {
+            info: u64::from(high) << 32 | u64::from(low),
+            trigger_dependent,
+        };
+        Ok((i, res))
+    }
+
+    pub fn aid(&self) -> u16 {
+        (self.info & 0xFFF) as u16
+    }
+
+    pub fn ru_allocation(&self) -> u8 {
+        (self.info >> 12) as u8
+    }
+
+    pub fn ul_mcs(&self) -> u8 {
+        (self.info >> 21 & 0b1111) as u8
+    }
+
+    pub fn ss_allocation(&self) -> u8 {
+        (self.info >> 26 & 0b11_1111) as u8
+    }
+
+    /// The expected receive power at the AP, encoded as -110 dBm plus the value.
+    pub fn target_rssi(&self) -> u8 {
+        (self.info >> 32 & 0b111_1111) as u8
+    }
+}
+
+/// A VHT or HE NDP Announcement, telling the listed stations that a sounding NDP follows.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct NDPAnnouncement {
+    #[debug(format = "0x{:02X}")]
+    pub sounding_dialog_token: u8,
+    /// Two bytes per station in VHT announcements, four in HE ones.
+    #[debug(format = "{:08X?}")]
+    pub sta_info: Vec<u32>,
+}
+
+impl NDPAnnouncement {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("802.11 NDP Announcement", |i| {
+            let (i, sounding_dialog_token) = le_u8(i)?;
+            let n = if sounding_dialog_token & 0b10 != 0 {
+                4
+            } else {
+                2
+            };
+            let (i, body) = take(i.len() - i.len() % n)(i)?;
+            let sta_info = body
+                .chunks(n)
+                .map(|c| c.iter().rev().fold(0, |acc, &b| acc << 8 | u32::from(b)))
+                .collect();
+            let res = Self {
+                sounding_dialog_token,
+                sta_info,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    pub fn is_he(&self) -> bool {
+        self.sounding_dialog_token & 0b10 != 0
+    }
+
+    pub fn dialog_token(&self) -> u8 {
+        self.sounding_dialog_token >> 2
+    }
+
+    /// The association IDs of the stations asked to send feedback.
+    pub fn aids(&self) -> Vec<u16> {
+        let mask = if self.is_he() { 0x7FF } else { 0xFFF };
+        self.sta_info.iter().map(|s| (s & mask) as u16).collect()
+    }
+}
+
+/// A control frame carried inside a Control Wrapper, so that it can include an HT Control field.
+/// The carried frame has lost its Frame Control, Duration and first address, which the wrapper provides.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct ControlWrapper {
+    pub carried_frame_control: FrameControl,
+    pub ht_control: HTControl,
+    pub carried_frame: Blob,
+}
+
+impl ControlWrapper {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("802.11 Control Wrapper", |i| {
+            let (i, (carried_frame_control, ht_control)) =
+                tuple((FrameControl::parse, HTControl::parse))(i)?;
+            let (i, carried_frame) = map(take(i.len()), Blob::new)(i)?;
+            let res = Self {
+                carried_frame_control,
+                ht_control,
+                carried_frame,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer2::wifi::dot11::{Dot11Addr, Frame, FrameBody};
+
+    #[test]
+    fn assert_block_ack() {
+        let ba: &[u8] = &[
+            0x94, 0x00, 0x00, 0x00, // BA
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x01, // RA
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x02, // TA
+            0x05, 0x50, // compressed, TID 5
+            0x20, 0x01, // starting sequence 18
+            0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80, // 18, 20 and 81
+        ];
+        let (_, frame) = Frame::parse(ba, false).unwrap();
+        assert!(matches!(
+            frame.addr2,
+            Some(Dot11Addr::TransmitterAddress(_))
+        ));
+        let ba = match frame.frame_body {
+            FrameBody::Control(ControlFrameBody::BlockAck(ba)) => ba,
+            b => panic!("expected a block ack, got {:?}", b),
+        };
+        assert_eq!(ba.control.block_ack_type(), Some(BlockAckType::Compressed));
+        let bitmap = match ba.info {
+            BlockAckInfo::Compressed(bitmap) => bitmap,
+            i => panic!("expected a compressed bitmap, got {:?}", i),
+        };
+        assert_eq!(bitmap.start.tid, 5);
+        assert_eq!(bitmap.start.starting_sequence_number, 18);
+        let acked: Vec<u16> = (0..4096).filter(|&s| bitmap.acked(s)).collect();
+        assert_eq!(acked, vec![18, 20, 81]);
+
+        let trigger: &[u8] = &[
+            0x24, 0x00, 0x00, 0x00, // Trigger
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x01, // RA
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x02, // TA
+            0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, // basic, 80 MHz
+            0x05, 0x30, 0x00, 0x00, 0x00, 0x00, // AID 5, RU 3
+            0xFF, 0xFF, // padding
+        ];
+        let (_, frame) = Frame::parse(trigger, false).unwrap();
+        let trigger = match frame.frame_body {
+            FrameBody::Control(ControlFrameBody::Trigger(t)) => t,
+            b => panic!("expected a trigger, got {:?}", b),
+        };
+        assert_eq!(trigger.trigger_type, Some(TriggerType::Basic));
+        assert_eq!(trigger.ul_bandwidth_mhz(), 80);
+        assert_eq!(trigger.user_info.len(), 1);
+        assert_eq!(trigger.user_info[0].aid(), 5);
+        assert_eq!(trigger.user_info[0].ru_allocation(), 3);
+    }
+}
@@ -1,40 +1,56 @@
 use crate::{
     core::{blob::Blob, parse},
-    layer2::datalink::*,
+    layer2::{datalink::*, wifi::mesh::MeshControl},
 };
 
-use super::dot11::SEQ_CONTROL_SIZE;
 use custom_debug_derive::*;
-use nom::{bytes::complete::take, error::context, number::complete::be_u8};
+use nom::{
+    bytes::complete::take,
+    combinator::map,
+    error::context,
+    multi::many0,
+    number::complete::{be_u16, be_u8},
+    sequence::tuple,
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(CustomDebug, Serialize, Deserialize)]
 pub struct DataFrameBody {
-    // pub llc: Option<LLCHeader>,
-    // pub snap: Option<SNAPHeader>,
-    pub payload: Blob,
+    /// Present in the frames of a mesh BSS, ahead of the LLC header.
+    pub mesh_control: Option<MeshControl>,
+    pub llc: Option<LLCHeader>,
+    pub snap: Option<SNAPHeader>,
+    pub payload: Option<Payload>,
+    /// Whatever was not decoded into `payload`: the whole body when it is not LLC/SNAP encapsulated,
+    /// or the bytes of a network layer packet that could not be parsed.
+    pub data: Blob,
 }
 
 impl DataFrameBody {
     pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
         context("802.11 Data Frame: Data frame body", |i: parse::Input| {
-            // let (i, llc) = LLCHeader::parse(i)?;
-            // let (i, snap) = SNAPHeader::parse(i)?;
-            // let (i, payload) = match snap.ether_type {
-            //     Some(EtherType::IPv4) => map(ipv4::Packet::parse, Payload::IPv4)(i)?,
-            //     Some(EtherType::IPv6) => map(ipv6::Packet::parse, Payload::IPv6)(i)?,
-            //     Some(EtherType::ARP) => map(arp::Packet::parse, Payload::ARP)(i)?,
-            //     _ => (i, Payload::Unknown),
-            // };
-            let len = i.len().checked_sub(SEQ_CONTROL_SIZE - 1);
-            let payload = match len {
-                Some(len) => Blob::new(&i[..len]),
-                None => Blob::new(i),
+            let (i, body) = take(i.len())(i)?;
+            let headers = match LLCHeader::parse(body) {
+                Ok((rest, llc)) if llc.is_snap() => match SNAPHeader::parse(rest) {
+                    Ok((rest, snap)) => Some((llc, snap, rest)),
+                    Err(_) => None,
+                },
+                _ => None,
+            };
+            // A body too short for its SNAP header is kept raw, like any other that is not LLC/SNAP.
+            let (llc, snap, payload, data) = match headers {
+                Some((llc, snap, rest)) => match Payload::parse(rest, &snap.ether_type) {
+                    Ok((rest, payload)) => (Some(llc), Some(snap), Some(payload), rest),
+                    Err(_) => (Some(llc), Some(snap), None, rest),
+                },
+                None => (None, None, None, body),
             };
             let res = Self {
-                // llc: Some(llc),
-                // snap: Some(snap),
+                mesh_control: None,
+                llc,
+                snap,
                 payload,
+                data: Blob::new(data),
             };
 
             Ok((i, res))
@@ -42,6 +58,51 @@ impl DataFrameBody {
     }
 }
 
+impl DataFrameBody {
+    /// Parses the body of a mesh data frame, which starts with a Mesh Control field.
+    pub fn parse_mesh(i: parse::Input) -> parse::ParseResult<Self> {
+        context("802.11 Data Frame: Mesh data frame body", |i| {
+            let (i, mesh_control) = MeshControl::parse(i)?;
+            let (i, mut res) = Self::parse(i)?;
+            res.mesh_control = Some(mesh_control);
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// One MSDU of an A-MSDU, with the addresses it would have in an ethernet header.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct AMSDUSubframe {
+    pub da: Addr,
+    pub sa: Addr,
+    #[debug(format = "{}")]
+    pub len: u16,
+    pub body: DataFrameBody,
+}
+
+impl AMSDUSubframe {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("802.11 A-MSDU subframe", |i| {
+            let (i, (da, sa, len)) = tuple((Addr::parse, Addr::parse, be_u16))(i)?;
+            let (i, msdu) = take(len)(i)?;
+            let (_, body) = DataFrameBody::parse(msdu)?;
+            // Every subframe but the last is padded to a multiple of 4 bytes.
+            let padding = (4 - (14 + len as usize) % 4) % 4;
+            let (i, _) = take(padding.min(i.len()))(i)?;
+
+            let res = Self { da, sa, len, body };
+            Ok((i, res))
+        })(i)
+    }
+
+    /// Splits the body of an A-MSDU into its subframes.
+    pub fn parse_all(i: parse::Input) -> parse::ParseResult<Vec<Self>> {
+        let (i, subframes) = take(i.len())(i)?;
+        let (_, res) = many0(Self::parse)(subframes)?;
+        Ok((i, res))
+    }
+}
+
 #[derive(CustomDebug, Serialize, Deserialize)]
 pub struct LLCHeader {
     #[debug(format = "0x{:02X}")]
@@ -54,7 +115,7 @@ pub struct LLCHeader {
 
 impl LLCHeader {
     pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
-        context("802.11 LLC Header", |i| {
+        context("LLC Header", |i| {
             let (i, dsap) = be_u8(i)?;
             let (i, ssap) = be_u8(i)?;
             let (i, ctrl) = be_u8(i)?;
@@ -64,21 +125,58 @@ impl LLCHeader {
             Ok((i, res))
         })(i)
     }
+
+    /// An unnumbered information frame between SNAP SAPs, which is followed by a SNAP header.
+    pub fn is_snap(&self) -> bool {
+        self.dsap == 0xAA && self.ssap == 0xAA && self.ctrl == 0x03
+    }
 }
 
 #[derive(CustomDebug, Serialize, Deserialize)]
 pub struct SNAPHeader {
+    #[debug(format = "{:02X?}")]
+    pub oui: [u8; 3],
     pub ether_type: Option<EtherType>,
 }
 
 impl SNAPHeader {
     pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
-        context("802.11 SNAP Header", |i| {
-            let (i, _) = take(3_usize)(i)?;
+        context("SNAP Header", |i| {
+            let (i, oui) = map(take(3_usize), |o: parse::Input| [o[0], o[1], o[2]])(i)?;
             let (i, ether_type) = EtherType::parse(i)?;
 
-            let res = Self { ether_type };
+            let res = Self { oui, ether_type };
             Ok((i, res))
         })(i)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_snap_arp() {
+        let body: &[u8] = &[
+            0xAA, 0xAA, 0x03, 0x00, 0x00, 0x00, 0x08, 0x06, // LLC/SNAP, ARP
+            0x00, 0x01, 0x08, 0x00, 0x06, 0x04, 0x00, 0x01, // who-has
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0xC0, 0xA8, 0x00, 0x01, //
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0xA8, 0x00, 0x02,
+        ];
+        let (rest, frame) = DataFrameBody::parse(body).unwrap();
+        assert!(rest.is_empty());
+        assert!(frame.llc.unwrap().is_snap());
+        assert_eq!(frame.snap.unwrap().ether_type, Some(EtherType::ARP));
+        assert!(matches!(frame.payload, Some(Payload::ARP(_))));
+        assert!(frame.data.0.is_empty());
+
+        let (_, frame) = DataFrameBody::parse(&body[3..]).unwrap();
+        assert!(frame.payload.is_none());
+        assert_eq!(frame.data.0.len(), body.len() - 3);
+
+        // Truncated in the middle of the SNAP header
+        let (_, frame) = DataFrameBody::parse(&body[..5]).unwrap();
+        assert!(frame.llc.is_none() && frame.snap.is_none());
+        assert_eq!(frame.data.0, &body[..5]);
+    }
+}
@@ -0,0 +1,454 @@
+//! Offline decryption of WPA and WPA2 personal networks.
+//!
+//! The keys of a station are derived from the 4-way handshake it went through with its access point,
+//! so only the traffic of stations whose handshake was captured can be decrypted.
+
+use crate::{
+    core::{aes, md5::hmac_md5, rc4::Rc4, sha1},
+    layer2::{
+        datalink::Addr,
+        eapol::{self, HandshakeMessage, KeyDescriptor},
+        wifi::{
+            ccmp,
+            dot11::{self, FrameBody, Type, FCS_SIZE},
+            tkip,
+        },
+    },
+};
+
+use std::collections::HashMap;
+
+/// The LLC/SNAP header of an EAPOL frame in the body of a data frame.
+const EAPOL_SNAP: [u8; 8] = [0xAA, 0xAA, 0x03, 0x00, 0x00, 0x00, 0x88, 0x8E];
+
+/// The offset of the MIC in an EAPOL-Key frame, counting its 4-byte EAPOL header.
+const MIC_OFFSET: usize = 4 + 1 + 2 + 2 + 8 + 32 + 16 + 8 + 8;
+const MIC_SIZE: usize = 16;
+
+/// What a network is secured with: its passphrase, or the PSK derived from it.
+pub enum Credentials {
+    Passphrase { passphrase: String, ssid: String },
+    PSK([u8; 32]),
+}
+
+impl Credentials {
+    /// Parses a PSK given as 64 hex digits.
+    pub fn from_hex_psk(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if s.len() != 64 || !s.is_ascii() {
+            return None;
+        }
+        let mut psk = [0u8; 32];
+        for (i, b) in psk.iter_mut().enumerate() {
+            *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).ok()?;
+        }
+        Some(Credentials::PSK(psk))
+    }
+
+    /// The pairwise master key, which for a personal network is the PSK.
+    pub fn pmk(&self) -> [u8; 32] {
+        match self {
+            Credentials::Passphrase { passphrase, ssid } => {
+                let mut pmk = [0u8; 32];
+                sha1::pbkdf2_sha1(passphrase.as_bytes(), ssid.as_bytes(), 4096, &mut pmk);
+                pmk
+            }
+            Credentials::PSK(psk) => *psk,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Cipher {
+    Tkip,
+    Ccmp,
+}
+
+/// A pairwise or group temporal key.
+#[derive(Clone, Copy)]
+struct TemporalKey {
+    cipher: Cipher,
+    key: [u8; 16],
+}
+
+impl TemporalKey {
+    /// A 16-byte group key is used with CCMP; a TKIP group key also carries its two Michael keys.
+    fn group(gtk: &[u8]) -> Option<Self> {
+        let cipher = match gtk.len() {
+            16 => Cipher::Ccmp,
+            32 => Cipher::Tkip,
+            _ => return None,
+        };
+        let mut key = [0u8; 16];
+        key.copy_from_slice(&gtk[..16]);
+        Some(Self { cipher, key })
+    }
+
+    fn decrypt(&self, mac_header: &[u8], body: &[u8]) -> Option<Vec<u8>> {
+        match self.cipher {
+            Cipher::Ccmp => ccmp::decrypt(&self.key, mac_header, body),
+            Cipher::Tkip => {
+                let mut ta = [0u8; 6];
+                ta.copy_from_slice(&mac_header[10..16]);
+                tkip::decrypt(&self.key, &ta, body)
+            }
+        }
+    }
+}
+
+/// The keys derived from the PTK: the key confirmation key, the key encryption key and the temporal key.
+#[derive(Clone, Copy)]
+struct PairwiseKeys {
+    descriptor_version: u8,
+    kck: [u8; 16],
+    kek: [u8; 16],
+    tk: TemporalKey,
+}
+
+impl PairwiseKeys {
+    /// Derives the PTK of a handshake (IEEE 802.11 12.7.1.3).
+    fn derive(
+        pmk: &[u8; 32],
+        ap: &Addr,
+        sta: &Addr,
+        anonce: &[u8],
+        snonce: &[u8],
+        descriptor_version: u8,
+    ) -> Option<Self> {
+        let cipher = match descriptor_version {
+            1 => Cipher::Tkip,
+            2 => Cipher::Ccmp,
+            _ => return None,
+        };
+        let mut data = Vec::with_capacity(6 + 6 + 32 + 32);
+        data.extend_from_slice(&ap.0.min(sta.0));
+        data.extend_from_slice(&ap.0.max(sta.0));
+        data.extend_from_slice(anonce.min(snonce));
+        data.extend_from_slice(anonce.max(snonce));
+        let mut ptk = [0u8; 64];
+        sha1::prf(pmk, b"Pairwise key expansion", &data, &mut ptk);
+
+        let mut res = Self {
+            descriptor_version,
+            kck: [0; 16],
+            kek: [0; 16],
+            tk: TemporalKey {
+                cipher,
+                key: [0; 16],
+            },
+        };
+        res.kck.copy_from_slice(&ptk[..16]);
+        res.kek.copy_from_slice(&ptk[16..32]);
+        res.tk.key.copy_from_slice(&ptk[32..48]);
+        Some(res)
+    }
+
+    /// Checks the MIC of an EAPOL-Key frame, computed over the frame with its MIC zeroed.
+    fn verify(&self, eapol: &[u8], mic: &[u8]) -> bool {
+        if eapol.len() < MIC_OFFSET + MIC_SIZE || mic.len() != MIC_SIZE {
+            return false;
+        }
+        let mut zeroed = eapol.to_vec();
+        zeroed[MIC_OFFSET..MIC_OFFSET + MIC_SIZE].copy_from_slice(&[0; MIC_SIZE]);
+        match self.descriptor_version {
+            1 => hmac_md5(&self.kck, &zeroed)[..] == *mic,
+            _ => sha1::hmac_sha1(&self.kck, &[&zeroed])[..MIC_SIZE] == *mic,
+        }
+    }
+
+    /// Decrypts the key data of message 3 or of a group message, with RC4 for WPA and AES key wrap for WPA2.
+    fn key_data(&self, key: &KeyDescriptor, message: HandshakeMessage) -> Option<Vec<u8>> {
+        let wpa_group = key.descriptor_type == 254 && message == HandshakeMessage::Group1;
+        if !key.key_information.encrypted_key_data() && !wpa_group {
+            return Some(key.key_data.0.clone());
+        }
+        match self.descriptor_version {
+            1 => {
+                let mut rc4_key = key.key_iv.0.clone();
+                rc4_key.extend_from_slice(&self.kek);
+                let mut data = key.key_data.0.clone();
+                let mut rc4 = Rc4::new(&rc4_key);
+                rc4.skip(256);
+                rc4.apply(&mut data);
+                Some(data)
+            }
+            _ => aes::key_unwrap(&self.kek, &key.key_data.0),
+        }
+    }
+
+    /// The key ID and GTK delivered by message 3 or a group message.
+    fn group_key(&self, key: &KeyDescriptor, message: HandshakeMessage) -> Option<(u8, Vec<u8>)> {
+        let data = self.key_data(key, message)?;
+        if key.descriptor_type == 254 {
+            // WPA sends the bare GTK, with its key ID in the key information.
+            if message != HandshakeMessage::Group1 {
+                return None;
+            }
+            let key_id = (key.key_information.0 >> 4) as u8 & 0b11;
+            let len = (key.key_length as usize).min(data.len());
+            return Some((key_id, data[..len].to_vec()));
+        }
+
+        // WPA2 sends it in a GTK KDE: a vendor-specific element of type 1 under the 00-0F-AC OUI.
+        let mut i = &data[..];
+        while i.len() >= 2 {
+            let (id, len) = (i[0], i[1] as usize);
+            if i.len() < 2 + len {
+                break;
+            }
+            let content = &i[2..2 + len];
+            if id == 0xDD && len >= 6 && content[..4] == [0x00, 0x0F, 0xAC, 0x01] {
+                return Some((content[4] & 0b11, content[6..].to_vec()));
+            }
+            i = &i[2 + len..];
+        }
+        None
+    }
+}
+
+#[derive(Default)]
+struct Handshake {
+    anonce: Option<Vec<u8>>,
+    keys: Option<PairwiseKeys>,
+}
+
+/// Learns keys from the handshakes seen in a capture and decrypts the data frames that use them.
+pub struct Decryptor {
+    pmks: Vec<[u8; 32]>,
+    /// Keyed by access point and station.
+    handshakes: HashMap<(Addr, Addr), Handshake>,
+    /// Keyed by access point and key ID.
+    group_keys: HashMap<(Addr, u8), TemporalKey>,
+}
+
+impl Decryptor {
+    pub fn new(credentials: &[Credentials]) -> Self {
+        Self {
+            pmks: credentials.iter().map(Credentials::pmk).collect(),
+            handshakes: HashMap::new(),
+            group_keys: HashMap::new(),
+        }
+    }
+
+    /// Decrypts the body of `frame` if it is a protected data frame whose keys are known, then looks
+    /// for handshake messages in its cleartext. `mpdu` holds the raw bytes `frame` was parsed from.
+    /// Returns whether the frame was decrypted.
+    pub fn process(&mut self, frame: &mut dot11::Frame, mpdu: &[u8]) -> bool {
+        if frame.fc.typ != Type::Data {
+            return false;
+        }
+        let header_len = frame.header_len();
+        let fcs_len = if frame.fcs.is_some() { FCS_SIZE } else { 0 };
+        let end = mpdu.len().saturating_sub(fcs_len);
+        if header_len > end {
+            return false;
+        }
+        let (header, body) = mpdu[..end].split_at(header_len);
+        let a1 = Addr::new(&header[4..]);
+        let a2 = Addr::new(&header[10..]);
+        let flags = &frame.fc.flags;
+        let (ap, sta) = match (u8::from(flags.to_ds), u8::from(flags.from_ds)) {
+            (1, 0) => (a1, a2),
+            (0, 1) => (a2, a1),
+            _ => return false,
+        };
+
+        if u8::from(flags.protected) == 0 {
+            self.observe(ap, sta, body);
+            return false;
+        }
+        if body.len() < ccmp::HEADER_SIZE {
+            return false;
+        }
+        let key = if a1.0[0] & 1 != 0 {
+            self.group_keys.get(&(ap, ccmp::key_id(body))).copied()
+        } else {
+            self.handshakes
+                .get(&(ap, sta))
+                .and_then(|h| h.keys)
+                .map(|keys| keys.tk)
+        };
+        let (key, mut plaintext) =
+            match key.and_then(|key| key.decrypt(header, body).map(|p| (key, p))) {
+                Some(decrypted) => decrypted,
+                None => return false,
+            };
+        let fragmented = u8::from(frame.fc.flags.more_fragments) == 1
+            || frame
+                .seq_control
+                .as_ref()
+                .is_some_and(|sc| u8::from(sc.frag_num) != 0);
+        // The Michael MIC ends the MSDU, so it can only be removed from a fragment once all are put together.
+        if key.cipher == Cipher::Tkip {
+            if fragmented {
+                frame.tkip_mic = true;
+            } else {
+                plaintext.truncate(plaintext.len().saturating_sub(tkip::MIC_SIZE));
+            }
+        }
+
+        frame.frame_body = match FrameBody::parse_cleartext(
+            &frame.fc,
+            &frame.seq_control,
+            &frame.qos_control,
+            &plaintext,
+        ) {
+            Ok((_, body)) => body,
+            Err(_) => FrameBody::Malformed,
+        };
+        self.observe(ap, sta, &plaintext);
+        true
+    }
+
+    /// Follows the 4-way and group key handshakes, when `msdu` carries one of their messages.
+    fn observe(&mut self, ap: Addr, sta: Addr, msdu: &[u8]) {
+        if !msdu.starts_with(&EAPOL_SNAP) {
+            return;
+        }
+        let raw = &msdu[EAPOL_SNAP.len()..];
+        let (key, len) = match eapol::Frame::parse(raw) {
+            Ok((_, frame)) => match frame.body {
+                eapol::Body::Key(key) => (key, frame.len as usize),
+                _ => return,
+            },
+            Err(_) => return,
+        };
+        let raw = &raw[..4 + len];
+        let message = match key.handshake_message() {
+            Some(message) => message,
+            None => return,
+        };
+
+        let handshake = self.handshakes.entry((ap, sta)).or_default();
+        match message {
+            HandshakeMessage::Message1 => handshake.anonce = Some(key.nonce.0.clone()),
+            HandshakeMessage::Message2 => {
+                let anonce = match handshake.anonce {
+                    Some(ref anonce) => anonce,
+                    None => return,
+                };
+                let version = key.key_information.descriptor_version();
+                let keys = self.pmks.iter().find_map(|pmk| {
+                    PairwiseKeys::derive(pmk, &ap, &sta, anonce, &key.nonce.0, version)
+                        .filter(|keys| keys.verify(raw, &key.mic.0))
+                });
+                if keys.is_some() {
+                    handshake.keys = keys;
+                }
+            }
+            HandshakeMessage::Message3 | HandshakeMessage::Group1 => {
+                let gtk = handshake
+                    .keys
+                    .and_then(|keys| keys.group_key(&key, message))
+                    .and_then(|(key_id, gtk)| Some((key_id, TemporalKey::group(&gtk)?)));
+                if let Some((key_id, gtk)) = gtk {
+                    self.group_keys.insert((ap, key_id), gtk);
+                }
+            }
+            HandshakeMessage::Message4 | HandshakeMessage::Group2 => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer2::datalink::Payload;
+
+    const AP: Addr = Addr([0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+    const STA: Addr = Addr([0x02, 0x00, 0x00, 0x00, 0x00, 0x02]);
+
+    /// An EAPOL-Key frame of the 4-way handshake, behind its LLC/SNAP header.
+    fn key_msdu(key_information: u16, nonce: u8, key_data: &[u8]) -> Vec<u8> {
+        let len = MIC_OFFSET - 4 + MIC_SIZE + 2 + key_data.len();
+        let mut msdu = EAPOL_SNAP.to_vec();
+        msdu.extend_from_slice(&[0x02, 0x03]);
+        msdu.extend_from_slice(&(len as u16).to_be_bytes());
+        msdu.push(0x02);
+        msdu.extend_from_slice(&key_information.to_be_bytes());
+        msdu.extend_from_slice(&[0x00, 0x10, 0, 0, 0, 0, 0, 0, 0, 0x01]);
+        msdu.extend_from_slice(&[nonce; 32]);
+        msdu.extend_from_slice(&[0; 16 + 8 + 8 + MIC_SIZE]);
+        msdu.extend_from_slice(&(key_data.len() as u16).to_be_bytes());
+        msdu.extend_from_slice(key_data);
+        msdu
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// The PTK for these addresses and nonces, computed apart from this crate: the smaller address
+    /// and nonce come first in the PRF data, and the PTK splits into the KCK, KEK and TK in that order.
+    const KCK: &str = "e410179564337ccda85688c0e80b3fe8";
+    const KEK: &str = "3a68dcf945abaa4a57531df48802a331";
+    const TK: &str = "8c04c599171310ff282266953059f300";
+
+    /// Shows `decryptor` the first two messages of a 4-way handshake between AP and STA.
+    fn handshake(decryptor: &mut Decryptor) {
+        decryptor.observe(AP, STA, &key_msdu(0x008A, 0xAA, &[]));
+        let mut message2 = key_msdu(0x010A, 0x55, &[0x30, 0x02, 0x01, 0x00]);
+        let mic = sha1::hmac_sha1(&hex(KCK), &[&message2[EAPOL_SNAP.len()..]]);
+        let offset = EAPOL_SNAP.len() + MIC_OFFSET;
+        message2[offset..offset + MIC_SIZE].copy_from_slice(&mic[..MIC_SIZE]);
+        decryptor.observe(AP, STA, &message2);
+    }
+
+    /// The PSK of this passphrase is the IEEE 802.11 J.4 vector, which the sha1 tests check.
+    fn credentials() -> Credentials {
+        Credentials::Passphrase {
+            passphrase: "password".to_string(),
+            ssid: "IEEE".to_string(),
+        }
+    }
+
+    #[test]
+    fn assert_handshake() {
+        let wrong = Credentials::from_hex_psk(&"00".repeat(32)).unwrap();
+        let mut decryptor = Decryptor::new(&[wrong, credentials()]);
+        handshake(&mut decryptor);
+
+        let learned = decryptor.handshakes[&(AP, STA)].keys.unwrap();
+        assert_eq!(learned.tk.cipher, Cipher::Ccmp);
+        assert_eq!(learned.kck.to_vec(), hex(KCK));
+        assert_eq!(learned.kek.to_vec(), hex(KEK));
+        assert_eq!(learned.tk.key.to_vec(), hex(TK));
+    }
+
+    #[test]
+    fn assert_ccmp_data() {
+        let mut decryptor = Decryptor::new(&[credentials()]);
+        handshake(&mut decryptor);
+
+        // A protected QoS data frame from the station to its access point.
+        let mut mpdu = vec![0x88, 0x41, 0x00, 0x00];
+        mpdu.extend_from_slice(&AP.0);
+        mpdu.extend_from_slice(&STA.0);
+        mpdu.extend_from_slice(&AP.0);
+        mpdu.extend_from_slice(&[0x10, 0x00, 0x00, 0x00]);
+        // An ARP request for 192.168.1.1 from 192.168.1.2, behind its LLC/SNAP header.
+        let mut msdu = vec![0xAA, 0xAA, 0x03, 0x00, 0x00, 0x00, 0x08, 0x06];
+        msdu.extend_from_slice(&[0x00, 0x01, 0x08, 0x00, 0x06, 0x04, 0x00, 0x01]);
+        msdu.extend_from_slice(&STA.0);
+        msdu.extend_from_slice(&[192, 168, 1, 2, 0, 0, 0, 0, 0, 0, 192, 168, 1, 1]);
+        let mut tk = [0u8; 16];
+        tk.copy_from_slice(&hex(TK));
+        let body = ccmp::encrypt(&tk, &mpdu, 1, 0, &msdu);
+        mpdu.extend_from_slice(&body);
+
+        let (_, mut frame) = dot11::Frame::parse(&mpdu, false).unwrap();
+        assert!(frame.payload().is_none());
+        assert!(decryptor.process(&mut frame, &mpdu));
+        match frame.payload() {
+            Some(Payload::ARP(arp)) => {
+                assert_eq!(arp.sender_hw_addr, STA);
+                assert_eq!(arp.sender_ip_addr.0, [192, 168, 1, 2]);
+                assert_eq!(arp.target_ip_addr.0, [192, 168, 1, 1]);
+            }
+            _ => panic!("expected the decrypted ARP request"),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Duplicate detection and defragmentation (IEEE 802.11 10.3.2.14 and 10.4), done the way a receiving
+//! station does it, from the sequence control of the frames of each transmitter.
+
+use crate::{
+    core::ux::*,
+    layer2::{
+        datalink::Addr,
+        wifi::{
+            dot11::{FcsStatus, Frame, FrameBody, Type},
+            tkip,
+        },
+    },
+};
+
+use std::collections::HashMap;
+
+/// The traffic identifiers used to key frames that have no QoS Control field.
+const NON_QOS_DATA: u8 = 16;
+const MANAGEMENT: u8 = 17;
+
+/// The fragments of an MSDU or MMPDU received so far.
+struct Fragments {
+    seq_num: u16,
+    next_frag_num: u8,
+    data: Vec<u8>,
+    /// Whether the MSDU ends with a TKIP Michael MIC.
+    tkip_mic: bool,
+}
+
+/// Tracks the frames of each transmitter and traffic identifier, to flag retransmitted duplicates
+/// and to put fragmented MSDUs back together.
+#[derive(Default)]
+pub struct Reassembler {
+    /// The sequence and fragment numbers of the last frame received.
+    last: HashMap<(Addr, u8), (u16, u8)>,
+    pending: HashMap<(Addr, u8), Fragments>,
+}
+
+impl Reassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets `frame.duplicate` if it retransmits the last frame of its transmitter. When `frame` is
+    /// the last fragment of an MSDU whose other fragments were all seen, its body is replaced with the
+    /// reassembled MSDU.
+    pub fn process(&mut self, frame: &mut Frame) {
+        if frame.fcs_status == FcsStatus::Invalid {
+            return;
+        }
+        let (transmitter, seq_control) = match (frame.transmitter(), &frame.seq_control) {
+            (Some(transmitter), Some(seq_control)) => (*transmitter, seq_control),
+            _ => return,
+        };
+        let tid = match frame.qos_control {
+            Some(ref qos) => u8::from(qos.tid),
+            None if frame.fc.typ == Type::Management => MANAGEMENT,
+            None => NON_QOS_DATA,
+        };
+        let key = (transmitter, tid);
+        let seq_num = u16::from(seq_control.seq_num);
+        let frag_num = u8::from(seq_control.frag_num);
+
+        if frame.fc.flags.retry == u1::new(1) && self.last.get(&key) == Some(&(seq_num, frag_num)) {
+            frame.duplicate = true;
+            return;
+        }
+        self.last.insert(key, (seq_num, frag_num));
+
+        let fragment = match frame.frame_body {
+            FrameBody::Fragment(ref blob) => &blob.0,
+            _ => return,
+        };
+        if frag_num == 0 {
+            let fragments = Fragments {
+                seq_num,
+                next_frag_num: 1,
+                data: fragment.clone(),
+                tkip_mic: frame.tkip_mic,
+            };
+            self.pending.insert(key, fragments);
+            return;
+        }
+        match self.pending.get_mut(&key) {
+            Some(f) if f.seq_num == seq_num && f.next_frag_num == frag_num => {
+                f.data.extend_from_slice(fragment);
+                f.next_frag_num += 1;
+            }
+            // A fragment went missing: the MSDU cannot be reassembled.
+            _ => {
+                self.pending.remove(&key);
+                return;
+            }
+        }
+        if frame.fc.flags.more_fragments == u1::new(1) {
+            return;
+        }
+
+        if let Some(mut fragments) = self.pending.remove(&key) {
+            if fragments.tkip_mic {
+                let len = fragments.data.len().saturating_sub(tkip::MIC_SIZE);
+                fragments.data.truncate(len);
+            }
+            frame.frame_body =
+                match FrameBody::parse_unfragmented(&frame.fc, &frame.qos_control, &fragments.data)
+                {
+                    Ok((_, body)) => body,
+                    Err(_) => FrameBody::Malformed,
+                };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer2::datalink::Payload;
+
+    /// A non-QoS data frame from 02:00:00:00:00:02 to its access point, with sequence number 0x123.
+    fn fragment(frag_num: u8, more_fragments: bool, retry: bool, body: &[u8]) -> Frame {
+        let flags = 0x01 | if more_fragments { 0x04 } else { 0 } | if retry { 0x08 } else { 0 };
+        let mut mpdu = vec![0x08, flags, 0x00, 0x00];
+        mpdu.extend_from_slice(&[0x02, 0x00, 0x00, 0x00, 0x00, 0x01]);
+        mpdu.extend_from_slice(&[0x02, 0x00, 0x00, 0x00, 0x00, 0x02]);
+        mpdu.extend_from_slice(&[0xFF; 6]);
+        mpdu.extend_from_slice(&(0x1230 | frag_num as u16).to_le_bytes());
+        mpdu.extend_from_slice(body);
+        Frame::parse(&mpdu, false).unwrap().1
+    }
+
+    #[test]
+    fn assert_reassembly() {
+        let mut msdu = vec![0xAA, 0xAA, 0x03, 0x00, 0x00, 0x00, 0x08, 0x06];
+        // An ARP request for 192.168.1.1 from 192.168.1.2.
+        msdu.extend_from_slice(&[0x00, 0x01, 0x08, 0x00, 0x06, 0x04, 0x00, 0x01]);
+        msdu.extend_from_slice(&[0x02, 0x00, 0x00, 0x00, 0x00, 0x02, 192, 168, 1, 2]);
+        msdu.extend_from_slice(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 192, 168, 1, 1]);
+        let (first, second) = msdu.split_at(16);
+
+        let mut reassembler = Reassembler::new();
+        let mut frames = [
+            fragment(0, true, false, first),
+            fragment(0, true, true, first),
+            fragment(1, false, false, second),
+        ];
+        for frame in frames.iter_mut() {
+            reassembler.process(frame);
+        }
+
+        let seq_control = frames[0].seq_control.as_ref().unwrap();
+        assert_eq!(u16::from(seq_control.seq_num), 0x123);
+        assert!(matches!(frames[0].frame_body, FrameBody::Fragment(_)));
+        assert!(!frames[0].duplicate);
+        assert!(frames[1].duplicate);
+        match frames[2].payload() {
+            Some(Payload::ARP(packet)) => {
+                assert_eq!(packet.target_ip_addr.to_string(), "192.168.1.1")
+            }
+            p => panic!("expected the reassembled ARP request, got {:?}", p),
+        }
+
+        // A TKIP MSDU whose Michael MIC is split between the two fragments
+        let mut tkip_msdu = msdu.clone();
+        tkip_msdu.extend_from_slice(&[0x4D; tkip::MIC_SIZE]);
+        let (first, second) = tkip_msdu.split_at(msdu.len() - 2);
+        let mut frames = [
+            fragment(0, true, false, first),
+            fragment(1, false, false, second),
+        ];
+        for frame in frames.iter_mut() {
+            frame.tkip_mic = true;
+            reassembler.process(frame);
+        }
+        match frames[1].frame_body {
+            FrameBody::Data(ref body) => assert!(body.data.0.is_empty()),
+            ref body => panic!("expected the reassembled MSDU, got {:?}", body),
+        }
+        assert!(matches!(frames[1].payload(), Some(Payload::ARP(_))));
+    }
+}
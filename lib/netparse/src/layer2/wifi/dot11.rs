@@ -1,23 +1,29 @@
 use crate::{
     core::{
         blob::Blob,
+        crc::crc32,
         parse::{self, BitParsable},
         ux::*,
     },
-    layer2::{datalink::*, wifi::data::*, wifi::management::*},
+    layer2::{
+        datalink::*, wifi::action::ActionFrameBody, wifi::control::ControlFrameBody, wifi::data::*,
+        wifi::management::*, wifi::mesh::MeshControl, wifi::radiotap::RadioTapHeader,
+    },
 };
 
 use custom_debug_derive::*;
 use nom::{
     bits::bits,
     bytes::complete::take,
+    combinator::map,
     error::context,
-    number::complete::{le_u16, le_u32},
+    number::complete::{le_u16, le_u32, le_u8},
     sequence::tuple,
 };
 use serde::{Deserialize, Serialize};
 
-pub static SEQ_CONTROL_SIZE: usize = 4;
+/// Size of the frame check sequence that ends a frame, when the capture keeps it.
+pub static FCS_SIZE: usize = 4;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Dot11Addr {
@@ -36,6 +42,18 @@ pub enum Dot11Addr {
     BSSID(Addr),
 }
 
+impl Dot11Addr {
+    pub fn addr(&self) -> &Addr {
+        match self {
+            Dot11Addr::DestinationAddress(addr)
+            | Dot11Addr::ReceiverAddress(addr)
+            | Dot11Addr::SourceAddress(addr)
+            | Dot11Addr::TransmitterAddress(addr)
+            | Dot11Addr::BSSID(addr) => addr,
+        }
+    }
+}
+
 #[derive(CustomDebug, Serialize, Deserialize, PartialEq, Clone)]
 pub enum Type {
     Management = 0x0,
@@ -110,6 +128,20 @@ pub enum Subtype {
 }
 
 impl Subtype {
+    /// QoS data subtypes, whose header carries a QoS Control field.
+    pub fn is_qos(&self) -> bool {
+        matches!(
+            self,
+            Subtype::QoSData
+                | Subtype::QoSData_And_CFAck
+                | Subtype::QoSData_And_CFPoll
+                | Subtype::QoSData_And_CFAck_And_CFPoll
+                | Subtype::QoSNull
+                | Subtype::QoS_CFPoll
+                | Subtype::QoS_CFAck_And_CFPoll
+        )
+    }
+
     fn from_type(typ: Type, i: u4) -> Self {
         match i {
             i if typ == Type::Management && i == u4::new(0x0) => Subtype::AssociationRequest,
@@ -263,10 +295,98 @@ pub struct SeqControl {
 }
 
 impl SeqControl {
+    /// The fragment number takes the 4 low bits of the little-endian field, and the sequence number the rest.
     pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
         context("802.11 Sequence Control", |i| {
-            let (i, (frag_num, seq_num)) = bits(tuple((u4::parse, u12::parse)))(i)?;
-            let res = Self { frag_num, seq_num };
+            let (i, value) = le_u16(i)?;
+            let res = Self {
+                frag_num: u4::new((value & 0xF) as u8),
+                seq_num: u12::new(value >> 4),
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub enum AckPolicy {
+    NormalAck = 0x0,
+    NoAck,
+    NoExplicitAck,
+    BlockAck,
+}
+
+impl From<u2> for AckPolicy {
+    fn from(i: u2) -> Self {
+        match i {
+            i if i == u2::new(0x0) => AckPolicy::NormalAck,
+            i if i == u2::new(0x1) => AckPolicy::NoAck,
+            i if i == u2::new(0x2) => AckPolicy::NoExplicitAck,
+            _ => AckPolicy::BlockAck,
+        }
+    }
+}
+
+/// The QoS Control field of QoS data frames.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct QoSControl {
+    /// Traffic identifier: the user priority (0-7) the MSDU belongs to.
+    #[debug(format = "{}")]
+    pub tid: u4,
+    /// End of service period, for U-APSD.
+    #[debug(format = "{}")]
+    pub eosp: u1,
+    pub ack_policy: AckPolicy,
+    /// The body is an A-MSDU, i.e. several MSDUs aggregated behind subframe headers.
+    #[debug(format = "{}")]
+    pub amsdu_present: u1,
+    /// TXOP limit, TXOP duration requested or queue size, depending on the sender and subtype.
+    #[debug(format = "{}")]
+    pub txop: u8,
+}
+
+impl QoSControl {
+    /// In a mesh BSS, bit 8 of the field tells that the body starts with a Mesh Control field.
+    /// Elsewhere the bit belongs to `txop`, so it only means something for frames that may be mesh frames.
+    pub fn mesh_control_present(&self) -> bool {
+        self.txop & 0x01 != 0
+    }
+
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("802.11 QoS Control", |i| {
+            let (i, (amsdu_present, ack_policy, eosp, tid)) =
+                bits(tuple((u1::parse, u2::parse, u1::parse, u4::parse)))(i)?;
+            let (i, txop) = le_u8(i)?;
+            let res = Self {
+                tid,
+                eosp,
+                ack_policy: AckPolicy::from(ack_policy),
+                amsdu_present,
+                txop,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// The HT Control field, present in QoS data and management frames that have the `order` flag set.
+/// The two low bits tell which of the three variants the remaining bits follow.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub enum HTControl {
+    HT(#[debug(format = "0x{:08X}")] u32),
+    VHT(#[debug(format = "0x{:08X}")] u32),
+    HE(#[debug(format = "0x{:08X}")] u32),
+}
+
+impl HTControl {
+    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        context("802.11 HT Control", |i| {
+            let (i, value) = le_u32(i)?;
+            let res = match value & 0b11 {
+                0b11 => HTControl::HE(value),
+                0b01 => HTControl::VHT(value),
+                _ => HTControl::HT(value),
+            };
             Ok((i, res))
         })(i)
     }
@@ -275,32 +395,67 @@ impl SeqControl {
 #[derive(CustomDebug, Serialize, Deserialize)]
 pub enum FrameBody {
     Data(DataFrameBody),
+    /// The MSDUs aggregated in the body of a QoS data frame.
+    AMSDU(Vec<AMSDUSubframe>),
     Beacon(BeaconFrameBody),
     ProbeRequest(ProbeRequestFrameBody),
     ProbeResponse(ProbeResponseFrameBody),
     Deauthentication(DeauthenticationFrameBody),
-    Disassociation(ReasonCode),
+    Disassociation(Reason),
     Authentication(AuthenticationFrameBody),
     AssociationRequest(AssociationRequestFrameBody),
     ReassociationRequest(ReassociationRequestFrameBody),
     AssociationResponse(AssociationResponseFrameBody),
     ReassociationResponse(AssociationResponseFrameBody),
+    Action(ActionFrameBody),
+    Control(ControlFrameBody),
+    /// A fragment of an MSDU or MMPDU, which can only be decoded once reassembled.
+    Fragment(Blob),
     Encrypted(Blob),
     Empty,
     Malformed,
 }
 
 impl FrameBody {
-    fn parse<'a>(fc: &FrameControl, i: parse::Input<'a>) -> parse::ParseResult<'a, Self> {
+    fn parse<'a>(
+        fc: &FrameControl,
+        seq_control: &Option<SeqControl>,
+        qos_control: &Option<QoSControl>,
+        i: parse::Input<'a>,
+    ) -> parse::ParseResult<'a, Self> {
         if fc.flags.protected == u1::new(1) {
-            if let Some(len) = i.len().checked_sub(SEQ_CONTROL_SIZE) {
-                let (i, _) = take(len)(i)?;
-                let blob = Blob::new(i);
-                return Ok((i, FrameBody::Encrypted(blob)));
-            } else {
+            if i.is_empty() {
                 return Ok((i, FrameBody::Empty));
             }
+            let (i, body) = take(i.len())(i)?;
+            return Ok((i, FrameBody::Encrypted(Blob::new(body))));
         }
+        Self::parse_cleartext(fc, seq_control, qos_control, i)
+    }
+
+    /// Parses a body that is not encrypted, or that has been decrypted, whatever its `protected` flag says.
+    pub(crate) fn parse_cleartext<'a>(
+        fc: &FrameControl,
+        seq_control: &Option<SeqControl>,
+        qos_control: &Option<QoSControl>,
+        i: parse::Input<'a>,
+    ) -> parse::ParseResult<'a, Self> {
+        let first_fragment = seq_control
+            .as_ref()
+            .is_none_or(|sc| sc.frag_num == u4::new(0));
+        if fc.flags.more_fragments == u1::new(1) || !first_fragment {
+            let (i, body) = take(i.len())(i)?;
+            return Ok((i, FrameBody::Fragment(Blob::new(body))));
+        }
+        Self::parse_unfragmented(fc, qos_control, i)
+    }
+
+    /// Parses a whole MSDU or MMPDU, as sent in a single frame or reassembled from fragments.
+    pub(crate) fn parse_unfragmented<'a>(
+        fc: &FrameControl,
+        qos_control: &Option<QoSControl>,
+        i: parse::Input<'a>,
+    ) -> parse::ParseResult<'a, Self> {
         Ok(match fc.typ {
             Type::Data => match fc.subtype {
                 Subtype::Data
@@ -310,10 +465,27 @@ impl FrameBody {
                 | Subtype::QoSData
                 | Subtype::QoSData_And_CFAck
                 | Subtype::QoSData_And_CFPoll
-                | Subtype::QoSData_And_CFAck_And_CFPoll => {
-                    let (i, body) = DataFrameBody::parse(i)?;
-                    (i, FrameBody::Data(body))
-                }
+                | Subtype::QoSData_And_CFAck_And_CFPoll => match qos_control {
+                    Some(qos) if qos.amsdu_present == u1::new(1) => {
+                        let (i, subframes) = AMSDUSubframe::parse_all(i)?;
+                        (i, FrameBody::AMSDU(subframes))
+                    }
+                    // Mesh stations send individually addressed frames with four addresses and group
+                    // addressed frames from the DS. Fall back to a plain body if the field is not valid.
+                    Some(qos) if qos.mesh_control_present() && fc.flags.from_ds == u1::new(1) => {
+                        match DataFrameBody::parse_mesh(i) {
+                            Ok((i, body)) => (i, FrameBody::Data(body)),
+                            Err(_) => {
+                                let (i, body) = DataFrameBody::parse(i)?;
+                                (i, FrameBody::Data(body))
+                            }
+                        }
+                    }
+                    _ => {
+                        let (i, body) = DataFrameBody::parse(i)?;
+                        (i, FrameBody::Data(body))
+                    }
+                },
                 _ => (i, FrameBody::Empty),
             },
 
@@ -333,11 +505,16 @@ impl FrameBody {
                     (i, FrameBody::ProbeResponse(body))
                 }
 
-                Subtype::Deauthentication | Subtype::Disassociation => {
+                Subtype::Deauthentication => {
                     let (i, body) = DeauthenticationFrameBody::parse(i)?;
                     (i, FrameBody::Deauthentication(body))
                 }
 
+                Subtype::Disassociation => {
+                    let (i, reason) = Reason::parse(i)?;
+                    (i, FrameBody::Disassociation(reason))
+                }
+
                 Subtype::Authentication => {
                     let (i, body) = AuthenticationFrameBody::parse(i)?;
                     (i, FrameBody::Authentication(body))
@@ -358,14 +535,44 @@ impl FrameBody {
                     (i, FrameBody::AssociationResponse(body))
                 }
 
+                Subtype::Action | Subtype::NACK => {
+                    let (i, body) = ActionFrameBody::parse(i)?;
+                    (i, FrameBody::Action(body))
+                }
+
                 _ => (i, FrameBody::Empty),
             },
 
+            Type::Control => match fc.subtype {
+                Subtype::RequestToSend
+                | Subtype::ClearToSend
+                | Subtype::ACK
+                | Subtype::PSPoll
+                | Subtype::CFEnd
+                | Subtype::CFEnd_And_CFAck => (i, FrameBody::Empty),
+                _ => {
+                    let (i, body) = ControlFrameBody::parse(&fc.subtype, i)?;
+                    (i, FrameBody::Control(body))
+                }
+            },
+
             _ => (i, FrameBody::Empty),
         })
     }
 }
 
+/// Outcome of checking the frame check sequence.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+pub enum FcsStatus {
+    /// The CRC-32 of the frame matches its FCS.
+    Valid,
+    /// The CRC-32 does not match, or the driver flagged the frame as failing its FCS check.
+    /// The body of such a frame is not decoded.
+    Invalid,
+    /// The capture stripped the FCS and did not report a failure.
+    Absent,
+}
+
 #[derive(CustomDebug, Serialize, Deserialize)]
 /// The MAC Frame header.
 /// - LLC/SNAP Headers are encapsulated in the upper level.
@@ -379,9 +586,18 @@ pub struct Frame {
     pub addr3: Option<Dot11Addr>,
     pub seq_control: Option<SeqControl>,
     pub addr4: Option<Dot11Addr>,
+    pub qos_control: Option<QoSControl>,
+    pub ht_control: Option<HTControl>,
     pub frame_body: FrameBody,
-    #[debug(format = "0x{:08X}")]
-    pub fcs: u32,
+    #[debug(format = "{:08X?}")]
+    pub fcs: Option<u32>,
+    pub fcs_status: FcsStatus,
+    /// A retransmission of a frame that was already received, as found by the `defrag::Reassembler`.
+    /// Parsing alone never sets it.
+    pub duplicate: bool,
+    /// Set by the `decrypt::Decryptor` on TKIP fragments, whose MSDU still ends with its Michael MIC
+    /// for the `defrag::Reassembler` to remove.
+    pub tkip_mic: bool,
 }
 
 impl Frame {
@@ -401,6 +617,8 @@ impl Frame {
                     ControlFlags { to_ds: x, from_ds: y, .. } if x == u1::new(0) && y == u1::new(1) => {
 			(i, (DestinationAddress(addr1), Some(BSSID(addr2)), Some(SourceAddress(addr3)), Some(seq_control), None))
                     }
+                    // WDS links and mesh BSSs: the DA and SA are those of the far ends of the wireless
+                    // distribution system, or the mesh DA and SA.
                     _ => {
 			let (i, addr4) = Addr::parse(i)?;
 			(i, (ReceiverAddress(addr1), Some(TransmitterAddress(addr2)), Some(DestinationAddress(addr3)), Some(seq_control), Some(SourceAddress(addr4))))
@@ -416,6 +634,16 @@ impl Frame {
                     let (i, (addr1, addr2)) = tuple((Addr::parse, Addr::parse))(i)?;
                     (i, (BSSID(addr1), Some(TransmitterAddress(addr2)), None, None, None))
                 }
+                Subtype::CFEnd | Subtype::CFEnd_And_CFAck => {
+                    let (i, (addr1, addr2)) = tuple((Addr::parse, Addr::parse))(i)?;
+                    (i, (ReceiverAddress(addr1), Some(BSSID(addr2)), None, None, None))
+                }
+                Subtype::BAR | Subtype::BA | Subtype::Trigger | Subtype::BeamformingReportPoll | Subtype::VHT_OR_HE_NDP_Announcement => {
+                    let (i, (addr1, addr2)) = tuple((Addr::parse, Addr::parse))(i)?;
+                    (i, (ReceiverAddress(addr1), Some(TransmitterAddress(addr2)), None, None, None))
+                }
+                // CTS, ACK and the control wrapper only have a receiver address.
+                // A CTS-to-self is a CTS whose receiver is its own transmitter.
                 _ => {
                     let (i, addr1) = Addr::parse(i)?;
                     (i, (ReceiverAddress(addr1), None, None, None, None))
@@ -436,13 +664,119 @@ impl Frame {
 }
 
 impl Frame {
-    pub fn parse(i: parse::Input) -> parse::ParseResult<Self> {
-        context("802.11 MAC frame", |i| {
-            let (i, fc) = FrameControl::parse(i)?;
-            let (i, duration) = le_u16(i)?;
-            let (i, (addr1, addr2, addr3, seq_control, addr4)) = Frame::parse_addr(i, fc.clone())?;
-            let (i, frame_body) = FrameBody::parse(&fc, i)?;
-            let (i, fcs) = le_u32(i)?;
+    /// The network layer packet of an unencrypted data frame.
+    /// For an A-MSDU this is the packet of the first subframe; the others are in `frame_body`.
+    pub fn payload(&self) -> Option<&Payload> {
+        match self.frame_body {
+            FrameBody::Data(ref body) => body.payload.as_ref(),
+            FrameBody::AMSDU(ref subframes) => {
+                subframes.first().and_then(|s| s.body.payload.as_ref())
+            }
+            _ => None,
+        }
+    }
+
+    /// The association ID of a PS-Poll, which carries it in place of the duration.
+    pub fn ps_poll_aid(&self) -> Option<u16> {
+        match self.fc.subtype {
+            Subtype::PSPoll => Some(self.duration & 0x3FFF),
+            _ => None,
+        }
+    }
+
+    /// The Mesh Control field of a mesh data frame.
+    /// Its address extension holds the end-to-end addresses when they are outside the mesh; the mesh
+    /// source and destination are then `addr4` and `addr3`.
+    pub fn mesh_control(&self) -> Option<&MeshControl> {
+        match self.frame_body {
+            FrameBody::Data(ref body) => body.mesh_control.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The address of the station that put the frame on the air, for the frames that carry it.
+    pub fn transmitter(&self) -> Option<&Addr> {
+        self.addr2.as_ref().map(Dot11Addr::addr)
+    }
+
+    /// The length of the MAC header, i.e. the offset of the frame body in the MPDU.
+    pub fn header_len(&self) -> usize {
+        let addresses = [&self.addr2, &self.addr3, &self.addr4]
+            .iter()
+            .filter(|a| a.is_some())
+            .count()
+            + 1;
+        let mut res = 2 + 2 + 6 * addresses;
+        if self.seq_control.is_some() {
+            res += 2;
+        }
+        if self.qos_control.is_some() {
+            res += 2;
+        }
+        if self.ht_control.is_some() {
+            res += 4;
+        }
+        res
+    }
+
+    /// Parses a frame that extends to the end of `i`.
+    /// `has_fcs` tells whether the capture kept the trailing FCS; LINKTYPE_IEEE802_11 does not say, so callers have to know.
+    pub fn parse(i: parse::Input, has_fcs: bool) -> parse::ParseResult<Self> {
+        Self::parse_frame(i, has_fcs, false)
+    }
+
+    /// Parses the frame following a radiotap header, whose flags tell whether the FCS is present and whether the driver found it bad.
+    pub fn parse_radiotap<'a>(
+        i: parse::Input<'a>,
+        radiotap: &RadioTapHeader,
+    ) -> parse::ParseResult<'a, Self> {
+        let flags = radiotap.flags();
+        let has_fcs = flags.is_some_and(|f| f.fcs_at_end());
+        let bad_fcs = flags.is_some_and(|f| f.bad_fcs());
+        Self::parse_frame(i, has_fcs, bad_fcs)
+    }
+
+    fn parse_frame(i: parse::Input, has_fcs: bool, bad_fcs: bool) -> parse::ParseResult<Self> {
+        context("802.11 MAC frame", |i: parse::Input| {
+            let (mpdu, fcs) = if has_fcs {
+                let len = i
+                    .len()
+                    .checked_sub(FCS_SIZE)
+                    .ok_or_else(|| nom::Err::Error(parse::Error::malformed(i)))?;
+                let (mpdu, fcs_bytes) = i.split_at(len);
+                let (_, fcs) = le_u32(fcs_bytes)?;
+                (mpdu, Some(fcs))
+            } else {
+                (i, None)
+            };
+            let fcs_status = match fcs {
+                _ if bad_fcs => FcsStatus::Invalid,
+                Some(fcs) if fcs == crc32(mpdu) => FcsStatus::Valid,
+                Some(_) => FcsStatus::Invalid,
+                None => FcsStatus::Absent,
+            };
+
+            let (body, fc) = FrameControl::parse(mpdu)?;
+            let (body, duration) = le_u16(body)?;
+            let (body, (addr1, addr2, addr3, seq_control, addr4)) =
+                Frame::parse_addr(body, fc.clone())?;
+            let (body, qos_control) = match fc.typ {
+                Type::Data if fc.subtype.is_qos() => map(QoSControl::parse, Some)(body)?,
+                _ => (body, None),
+            };
+            // Only frames with a QoS Control field or management frames use `order` to signal HT Control.
+            let has_ht_control = fc.flags.order == u1::new(1)
+                && (qos_control.is_some() || fc.typ == Type::Management);
+            let (body, ht_control) = if has_ht_control {
+                map(HTControl::parse, Some)(body)?
+            } else {
+                (body, None)
+            };
+            let frame_body = if fcs_status == FcsStatus::Invalid {
+                FrameBody::Malformed
+            } else {
+                FrameBody::parse(&fc, &seq_control, &qos_control, body)?.1
+            };
             let res = Self {
                 fc,
                 duration,
@@ -451,10 +785,95 @@ impl Frame {
                 addr3,
                 addr4,
                 seq_control,
+                qos_control,
+                ht_control,
                 frame_body,
                 fcs,
+                fcs_status,
+                duplicate: false,
+                tkip_mic: false,
             };
-            Ok((i, res))
+            Ok((&i[i.len()..], res))
         })(i)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An ACK to 00:11:22:33:44:55, without FCS.
+    const ACK: &[u8] = &[0xD4, 0x00, 0x00, 0x00, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55];
+
+    fn with_fcs(mpdu: &[u8]) -> Vec<u8> {
+        let mut frame = mpdu.to_vec();
+        frame.extend_from_slice(&crc32(mpdu).to_le_bytes());
+        frame
+    }
+
+    #[test]
+    fn assert_fcs() {
+        let (rest, frame) = Frame::parse(ACK, false).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(frame.fcs, None);
+        assert_eq!(frame.fcs_status, FcsStatus::Absent);
+
+        let mut data = with_fcs(ACK);
+        let (_, frame) = Frame::parse(&data, true).unwrap();
+        assert_eq!(frame.fcs, Some(crc32(ACK)));
+        assert_eq!(frame.fcs_status, FcsStatus::Valid);
+
+        data[4] ^= 0x01;
+        let (_, frame) = Frame::parse(&data, true).unwrap();
+        assert_eq!(frame.fcs_status, FcsStatus::Invalid);
+        assert!(matches!(frame.frame_body, FrameBody::Malformed));
+
+        assert!(Frame::parse(&ACK[..2], true).is_err());
+    }
+
+    #[test]
+    fn assert_disassociation() {
+        let mut mpdu = vec![0xA0, 0x00, 0x00, 0x00];
+        mpdu.extend_from_slice(&[0x11; 18]); // addresses
+        mpdu.extend_from_slice(&[0x00, 0x00]); // sequence control
+        mpdu.extend_from_slice(&[0x08, 0x00]); // reason code
+
+        let (_, frame) = Frame::parse(&mpdu, false).unwrap();
+        match frame.frame_body {
+            FrameBody::Disassociation(ref reason) => {
+                assert_eq!(reason.code, Some(ReasonCode::LeavingNetworkDisassoc))
+            }
+            ref body => panic!("expected a disassociation, got {:?}", body),
+        }
+    }
+
+    #[test]
+    fn assert_qos_amsdu() {
+        let mut data = vec![0x88, 0x80, 0x00, 0x00];
+        data.extend_from_slice(&[0x11; 18]); // addresses
+        data.extend_from_slice(&[0x00, 0x00]); // sequence control
+        data.extend_from_slice(&[0x85, 0x00]); // QoS control: TID 5, A-MSDU
+        data.extend_from_slice(&[0x03, 0x00, 0x00, 0x00]); // HE variant HT control
+
+        // A 2 byte MSDU needs no padding, and the last subframe is never padded.
+        for len in 2..=3 {
+            data.extend_from_slice(&[0x22; 12]);
+            data.extend_from_slice(&[0x00, len]);
+            data.extend_from_slice(&vec![0xFF; len as usize]);
+        }
+
+        let (_, frame) = Frame::parse(&data, false).unwrap();
+        let qos = frame.qos_control.unwrap();
+        assert_eq!(qos.tid, u4::new(5));
+        assert_eq!(qos.amsdu_present, u1::new(1));
+        assert!(matches!(frame.ht_control, Some(HTControl::HE(3))));
+        match frame.frame_body {
+            FrameBody::AMSDU(ref subframes) => {
+                assert_eq!(subframes.len(), 2);
+                assert_eq!(subframes[0].body.data.0, vec![0xFF; 2]);
+                assert_eq!(subframes[1].body.data.0, vec![0xFF; 3]);
+            }
+            ref body => panic!("expected an A-MSDU, got {:?}", body),
+        }
+    }
+}
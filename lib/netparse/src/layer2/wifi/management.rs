@@ -1,6 +1,13 @@
-use crate::{core::parse::*, core::ux::*, layer2::datalink::*};
+use crate::{
+    core::blob::Blob,
+    core::parse::*,
+    core::ux::*,
+    layer2::{
+        datalink::*,
+        wifi::{action, mesh, phy, rsn, sae, vendor},
+    },
+};
 
-use super::dot11::SEQ_CONTROL_SIZE;
 use custom_debug_derive::*;
 use derive_try_from_primitive::*;
 use nom::{
@@ -14,7 +21,7 @@ use nom::{
     Offset,
 };
 use serde::{Deserialize, Serialize};
-use std::string::ToString;
+use std::{fmt, string::ToString};
 use strum_macros::Display;
 
 #[derive(CustomDebug, Serialize, Deserialize)]
@@ -62,7 +69,8 @@ impl CapabilityInfo {
     #[cfg_attr(rustfmt, rustfmt_skip)]
     pub fn parse(i: Input) -> ParseResult<Self> {
         context("802.11 Management Frame CapabilityInfo", |i| {
-            let (i, (ess, ibss, cf_pollable, cf_poll_request, privacy, short_preamble, pbcc, channel_agility)) = bits(tuple((
+            // The field is little endian and `bits` reads each byte from its most significant bit.
+            let (i, (channel_agility, pbcc, short_preamble, privacy, cf_poll_request, cf_pollable, ibss, ess)) = bits(tuple((
                 u1::parse,
                 u1::parse,
                 u1::parse,
@@ -72,7 +80,7 @@ impl CapabilityInfo {
                 u1::parse,
                 u1::parse,
             )))(i)?;
-            let (i, (_, _, short_slot_time, _, _, dsss_ofdm, _, _)) = bits(tuple((
+            let (i, (_, _, dsss_ofdm, _, _, short_slot_time, _, _)) = bits(tuple((
                 u1::parse,
                 u1::parse,
                 u1::parse,
@@ -101,174 +109,552 @@ impl CapabilityInfo {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, TryFromPrimitive, Clone, Copy, Display)]
+/// Why a station was deauthenticated or disassociated, or a peering or stream torn down
+/// (IEEE 802.11-2020 Table 9-49).
+#[derive(Serialize, Deserialize, Debug, TryFromPrimitive, Clone, Copy, PartialEq, Eq, Display)]
 #[repr(u16)]
 pub enum ReasonCode {
-    #[strum(serialize = "Reserved; unused")]
-    Reserved1 = 0x0,
-    #[strum(serialize = "Unspecified reason code")]
-    Unspecified,
-    #[strum(serialize = "Prior authentication is not valid")]
-    Invalid1,
+    #[strum(serialize = "Unspecified reason")]
+    Unspecified = 1,
+    #[strum(serialize = "Previous authentication no longer valid")]
+    InvalidAuthentication = 2,
+    #[strum(
+        serialize = "Deauthenticated because the sending station is leaving or has left the IBSS or ESS"
+    )]
+    LeavingNetworkDeauth = 3,
+    #[strum(serialize = "Disassociated due to inactivity")]
+    Inactivity = 4,
     #[strum(
-        serialize = "Station has left the basic service area or extended service area and is deauthenticated"
+        serialize = "Disassociated because the access point is unable to handle all currently associated stations"
     )]
-    OutOfRange1,
-    #[strum(serialize = "Inactivity timer expired and station was disassociated")]
-    Inactive,
-    #[strum(serialize = "Disassociated due to insufficient resources at the access point")]
-    InsufficientResources,
-    #[strum(serialize = "Incorrect frame type or subtype received from unauthenticated station")]
-    IncorrectFrameType1,
+    NoMoreStations = 5,
+    #[strum(serialize = "Class 2 frame received from nonauthenticated station")]
+    InvalidClass2Frame = 6,
+    #[strum(serialize = "Class 3 frame received from nonassociated station")]
+    InvalidClass3Frame = 7,
     #[strum(
-        serialize = "Station has left the basic service area or extended service area and is disassociated"
+        serialize = "Disassociated because the sending station is leaving or has left the BSS"
     )]
-    OutOfRange2,
+    LeavingNetworkDisassoc = 8,
     #[strum(
-        serialize = "Disassociated because of unacceptable values in Power Capability element"
+        serialize = "Station requesting (re)association is not authenticated with the responding station"
     )]
-    UnacceptableValue1,
+    NotAuthenticated = 9,
     #[strum(
-        serialize = "Disassociated because of unacceptable values in Supported Channels element"
+        serialize = "Disassociated because the information in the Power Capability element is unacceptable"
     )]
-    UnacceptableValue2,
-    #[strum(serialize = "Reserved")]
-    Reserved2,
+    UnacceptablePowerCapability = 10,
     #[strum(
-        serialize = "Invalid information element (added with 802.11i, and likely one of the 802.11i information elements)"
+        serialize = "Disassociated because the information in the Supported Channels element is unacceptable"
     )]
-    Invalid2,
-    #[strum(serialize = "Message integrity check failure")]
-    MessageIntegrityCheckFailed,
-    #[strum(serialize = "4-way keying handshake timeout")]
-    HandshakeTimeout1,
+    UnacceptableSupportedChannels = 11,
+    #[strum(serialize = "Disassociated due to BSS transition management")]
+    BSSTransitionDisassoc = 12,
+    #[strum(serialize = "Invalid element")]
+    InvalidElement = 13,
+    #[strum(serialize = "Message integrity code (MIC) failure")]
+    MICFailure = 14,
+    #[strum(serialize = "4-way handshake timeout")]
+    FourWayHandshakeTimeout = 15,
     #[strum(serialize = "Group key handshake timeout")]
-    HandshakeTimeout2,
+    GroupKeyHandshakeTimeout = 16,
     #[strum(
-        serialize = "4-way handshake information element has different security parameters from initial parameter set"
+        serialize = "Element in 4-way handshake different from (Re)Association Request, Probe Response or Beacon frame"
     )]
-    HandshakeTimeout3,
+    HandshakeElementMismatch = 17,
     #[strum(serialize = "Invalid group cipher")]
-    Invalid3,
+    InvalidGroupCipher = 18,
     #[strum(serialize = "Invalid pairwise cipher")]
-    Invalid4,
-    #[strum(serialize = "Invalid Authentication and Key Management Protocol")]
-    Invalid5,
+    InvalidPairwiseCipher = 19,
+    #[strum(serialize = "Invalid AKMP")]
+    InvalidAKMP = 20,
+    #[strum(serialize = "Unsupported version in RSN element")]
+    UnsupportedRSNEVersion = 21,
+    #[strum(serialize = "Invalid RSN element capabilities")]
+    InvalidRSNECapabilities = 22,
+    #[strum(serialize = "IEEE 802.1X authentication failed")]
+    AuthenticationFailed8021X = 23,
+    #[strum(serialize = "Cipher suite rejected because of the security policy")]
+    CipherOutOfPolicy = 24,
+    #[strum(
+        serialize = "TDLS direct-link teardown due to TDLS peer station unreachable via the TDLS direct link"
+    )]
+    TDLSPeerUnreachable = 25,
+    #[strum(serialize = "TDLS direct-link teardown for unspecified reason")]
+    TDLSUnspecified = 26,
+    #[strum(serialize = "Disassociated because session terminated by SSP request")]
+    SSPRequestedDisassoc = 27,
+    #[strum(serialize = "Disassociated because of lack of SSP roaming agreement")]
+    NoSSPRoamingAgreement = 28,
+    #[strum(
+        serialize = "Requested service rejected because of SSP cipher suite or AKM requirement"
+    )]
+    BadCipherOrAKM = 29,
+    #[strum(serialize = "Requested service not authorized in this location")]
+    NotAuthorizedThisLocation = 30,
+    #[strum(
+        serialize = "TS deleted because QoS access point lacks sufficient bandwidth for this QoS station due to a change in BSS service characteristics or operational mode"
+    )]
+    ServiceChangePrecludesTS = 31,
+    #[strum(serialize = "Disassociated for unspecified, QoS-related reason")]
+    UnspecifiedQoS = 32,
+    #[strum(
+        serialize = "Disassociated because QoS access point lacks sufficient bandwidth for this QoS station"
+    )]
+    NotEnoughBandwidth = 33,
+    #[strum(
+        serialize = "Disassociated because excessive number of frames need to be acknowledged, but are not acknowledged due to access point transmissions and/or poor channel conditions"
+    )]
+    MissingACKs = 34,
+    #[strum(
+        serialize = "Disassociated because station is transmitting outside the limits of its TXOPs"
+    )]
+    ExceededTXOP = 35,
+    #[strum(serialize = "Requesting station is leaving the BSS (or resetting)")]
+    StationLeaving = 36,
+    #[strum(serialize = "Requesting station is no longer using the stream or session")]
+    EndTSBADLS = 37,
+    #[strum(
+        serialize = "Requesting station received frames using a mechanism for which a setup has not been completed"
+    )]
+    UnknownTSBA = 38,
+    #[strum(serialize = "Requested from peer station due to timeout")]
+    Timeout = 39,
+    #[strum(serialize = "Peer station does not support the requested cipher suite")]
+    PeerKeyMismatch = 45,
+    #[strum(serialize = "Disassociated because authorized access limit reached")]
+    AuthorizedAccessLimitReached = 46,
+    #[strum(serialize = "Disassociated due to external service requirements")]
+    ExternalServiceRequirements = 47,
+    #[strum(serialize = "Invalid FT Action frame count")]
+    InvalidFTActionFrameCount = 48,
+    #[strum(serialize = "Invalid pairwise master key identifier (PMKID)")]
+    InvalidPMKID = 49,
+    #[strum(serialize = "Invalid MDE")]
+    InvalidMDE = 50,
+    #[strum(serialize = "Invalid FTE")]
+    InvalidFTE = 51,
+    #[strum(serialize = "Mesh peering canceled for unknown reasons")]
+    MeshPeeringCanceled = 52,
+    #[strum(
+        serialize = "The mesh station has reached the supported maximum number of peer mesh stations"
+    )]
+    MeshMaxPeers = 53,
+    #[strum(
+        serialize = "The received information violates the mesh configuration policy configured in the mesh station profile"
+    )]
+    MeshConfigurationPolicyViolation = 54,
+    #[strum(
+        serialize = "The mesh station has received a Mesh Peering Close frame requesting to close the mesh peering"
+    )]
+    MeshCloseReceived = 55,
+    #[strum(
+        serialize = "The mesh station has resent dot11MeshMaxRetries Mesh Peering Open frames, without receiving a Mesh Peering Confirm frame"
+    )]
+    MeshMaxRetries = 56,
+    #[strum(serialize = "The confirmTimer for the mesh peering instance times out")]
+    MeshConfirmTimeout = 57,
+    #[strum(
+        serialize = "The mesh station fails to unwrap the GTK or the values in the wrapped contents do not match"
+    )]
+    MeshInvalidGTK = 58,
+    #[strum(
+        serialize = "The mesh station receives inconsistent information about the mesh parameters between mesh peering Management frames"
+    )]
+    MeshInconsistentParameters = 59,
+    #[strum(
+        serialize = "The mesh station fails the authenticated mesh peering exchange because due to failure in selecting either the pairwise ciphersuite or group ciphersuite"
+    )]
+    MeshInvalidSecurityCapability = 60,
+    #[strum(
+        serialize = "The mesh station does not have proxy information for this external destination"
+    )]
+    MeshPathErrorNoProxyInformation = 61,
+    #[strum(
+        serialize = "The mesh station does not have forwarding information for this destination"
+    )]
+    MeshPathErrorNoForwardingInformation = 62,
+    #[strum(
+        serialize = "The mesh station determines that the link to the next hop of an active path in its forwarding information is no longer usable"
+    )]
+    MeshPathErrorDestinationUnreachable = 63,
     #[strum(
-        serialize = "Unsupported Robust Security Network Information Element (RSN IE) version"
+        serialize = "The deauthentication frame was sent because the MAC address of the station already exists in the mesh BSS"
     )]
-    Unsupported,
-    #[strum(serialize = "Invalid capabilities in RSN information element")]
-    Invalid6,
-    #[strum(serialize = "802.1X authentication failure")]
-    AuthenticationFailure,
-    #[strum(serialize = "Proposed cipher suite rejected due to configured policy")]
-    Rejected,
-    #[strum(serialize = "Reserved; unused")]
-    Reserved3,
-}
-
-impl ReasonCode {
-    pub fn parse(i: Input) -> ParseResult<String> {
+    MACAddressAlreadyExistsInMBSS = 64,
+    #[strum(
+        serialize = "The mesh station performs channel switch to meet regulatory requirements"
+    )]
+    MeshChannelSwitchRegulatoryRequirements = 65,
+    #[strum(serialize = "The mesh station performs channel switching with unspecified reason")]
+    MeshChannelSwitchUnspecified = 66,
+    #[strum(serialize = "Transmission link establishment in alternative channel failed")]
+    TransmissionLinkEstablishmentFailed = 67,
+    #[strum(serialize = "The alternative channel is occupied")]
+    AlternativeChannelOccupied = 68,
+    #[strum(serialize = "Disassociated due to poor RSSI conditions")]
+    PoorRSSIConditions = 71,
+}
+
+/// A reason code as it was sent, and its meaning when the standard defines one.
+#[derive(CustomDebug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Reason {
+    pub value: u16,
+    pub code: Option<ReasonCode>,
+}
+
+impl Reason {
+    pub fn parse(i: Input) -> ParseResult<Self> {
         context("Reason Code", |i| {
-            let (i, s) = map(le_u16, Self::try_from)(i)?;
-            match s {
-                Some(s) => Ok((i, s.to_string())),
-                None => Ok((i, "Unknown reason code".to_string())),
-            }
+            map(le_u16, |value| Self {
+                value,
+                code: ReasonCode::try_from(value),
+            })(i)
         })(i)
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, TryFromPrimitive, Clone, Copy, Display)]
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "{}", code),
+            None => write!(f, "Unknown reason code {}", self.value),
+        }
+    }
+}
+
+/// The outcome of an authentication, association or action request
+/// (IEEE 802.11-2020 Table 9-50).
+#[derive(Serialize, Deserialize, Debug, TryFromPrimitive, Clone, Copy, PartialEq, Eq, Display)]
 #[repr(u16)]
 pub enum StatusCode {
-    #[strum(serialize = "Operation completed successfully")]
-    Success = 0x0,
+    #[strum(serialize = "Successful")]
+    Success = 0,
     #[strum(serialize = "Unspecified failure")]
-    Unspecified,
-    #[strum(serialize = "Reserved; unused")]
-    Reserved1,
-    #[strum(serialize = "Requested capability set is too broad and cannot be supported")]
-    Unsupported1 = 0xA,
+    RefusedReasonUnspecified = 1,
+    #[strum(serialize = "TDLS wakeup schedule rejected but alternative schedule provided")]
+    TDLSRejectedAlternativeProvided = 2,
+    #[strum(serialize = "TDLS wakeup schedule rejected")]
+    TDLSRejected = 3,
+    #[strum(serialize = "Security disabled")]
+    SecurityDisabled = 5,
+    #[strum(serialize = "Unacceptable lifetime")]
+    UnacceptableLifetime = 6,
+    #[strum(serialize = "Not in same BSS")]
+    NotInSameBSS = 7,
+    #[strum(
+        serialize = "Cannot support all requested capabilities in the Capability Information field"
+    )]
+    RefusedCapabilitiesMismatch = 10,
+    #[strum(
+        serialize = "Reassociation denied due to inability to confirm that association exists"
+    )]
+    DeniedNoAssociationExists = 11,
+    #[strum(serialize = "Association denied due to reason outside the scope of this standard")]
+    DeniedOtherReason = 12,
+    #[strum(
+        serialize = "Responding station does not support the specified authentication algorithm"
+    )]
+    UnsupportedAuthAlgorithm = 13,
+    #[strum(
+        serialize = "Received an Authentication frame with authentication transaction sequence number out of expected sequence"
+    )]
+    TransactionSequenceError = 14,
+    #[strum(serialize = "Authentication rejected because of challenge failure")]
+    ChallengeFailure = 15,
+    #[strum(
+        serialize = "Authentication rejected due to timeout waiting for next frame in sequence"
+    )]
+    RejectedSequenceTimeout = 16,
+    #[strum(
+        serialize = "Association denied because access point is unable to handle additional associated stations"
+    )]
+    DeniedNoMoreStations = 17,
+    #[strum(
+        serialize = "Association denied due to requesting station not supporting all of the basic rates"
+    )]
+    RefusedBasicRatesMismatch = 18,
+    #[strum(
+        serialize = "Association denied due to requesting station not supporting the short preamble option"
+    )]
+    DeniedNoShortPreambleSupport = 19,
+    #[strum(
+        serialize = "Association request rejected because Spectrum Management capability is required"
+    )]
+    RejectedSpectrumManagementRequired = 22,
+    #[strum(
+        serialize = "Association request rejected because the information in the Power Capability element is unacceptable"
+    )]
+    RejectedBadPowerCapability = 23,
+    #[strum(
+        serialize = "Association request rejected because the information in the Supported Channels element is unacceptable"
+    )]
+    RejectedBadSupportedChannels = 24,
+    #[strum(
+        serialize = "Association denied due to requesting station not supporting the Short Slot Time option"
+    )]
+    DeniedNoShortSlotTimeSupport = 25,
+    #[strum(
+        serialize = "Association denied because the requesting station does not support HT features"
+    )]
+    DeniedNoHTSupport = 27,
+    #[strum(serialize = "R0KH unreachable")]
+    R0KHUnreachable = 28,
+    #[strum(
+        serialize = "Association denied because the requesting station does not support the phased coexistence operation (PCO) transition time required by the access point"
+    )]
+    DeniedPCOTimeNotSupported = 29,
+    #[strum(serialize = "Association request rejected temporarily; try again later")]
+    RefusedTemporarily = 30,
+    #[strum(serialize = "Robust management frame policy violation")]
+    RobustManagementPolicyViolation = 31,
+    #[strum(serialize = "Unspecified, QoS-related failure")]
+    UnspecifiedQoSFailure = 32,
+    #[strum(
+        serialize = "Association denied because QoS access point or PCP has insufficient bandwidth to handle another QoS station"
+    )]
+    DeniedInsufficientBandwidth = 33,
+    #[strum(
+        serialize = "Association denied due to excessive frame loss rates and/or poor conditions on current operating channel"
+    )]
+    DeniedPoorChannelConditions = 34,
+    #[strum(
+        serialize = "Association (with QoS BSS) denied because the requesting station does not support the QoS facility"
+    )]
+    DeniedQoSNotSupported = 35,
+    #[strum(serialize = "The request has been declined")]
+    RequestDeclined = 37,
+    #[strum(
+        serialize = "The request has not been successful as one or more parameters have invalid values"
+    )]
+    InvalidParameters = 38,
+    #[strum(
+        serialize = "The allocation or TS has not been created because the request cannot be honored; however, a suggested TSPEC/DMG TSPEC is provided so that the initiating station can attempt to set another allocation or TS with the suggested changes to the TSPEC/DMG TSPEC"
+    )]
+    RejectedWithSuggestedChanges = 39,
+    #[strum(
+        serialize = "Invalid element, i.e., an element defined in this standard for which the content does not meet the specifications"
+    )]
+    InvalidElement = 40,
+    #[strum(serialize = "Invalid group cipher")]
+    InvalidGroupCipher = 41,
+    #[strum(serialize = "Invalid pairwise cipher")]
+    InvalidPairwiseCipher = 42,
+    #[strum(serialize = "Invalid AKMP")]
+    InvalidAKMP = 43,
+    #[strum(serialize = "Unsupported RSNE version")]
+    UnsupportedRSNEVersion = 44,
+    #[strum(serialize = "Invalid RSNE capabilities")]
+    InvalidRSNECapabilities = 45,
+    #[strum(serialize = "Cipher suite rejected because of security policy")]
+    CipherOutOfPolicy = 46,
+    #[strum(
+        serialize = "The TS or allocation has not been created; however, the HC or PCP might be capable of creating a TS or allocation, in response to a request, after the time indicated in the TS Delay element"
+    )]
+    RejectedForDelayPeriod = 47,
+    #[strum(serialize = "Direct link is not allowed in the BSS by policy")]
+    DLSNotAllowed = 48,
+    #[strum(serialize = "The Destination station is not present within this BSS")]
+    NotPresent = 49,
+    #[strum(serialize = "The Destination station is not a QoS station")]
+    NotQoSStation = 50,
+    #[strum(serialize = "Association denied because the listen interval is too large")]
+    DeniedListenIntervalTooLarge = 51,
+    #[strum(serialize = "Invalid FT Action frame count")]
+    InvalidFTActionFrameCount = 52,
+    #[strum(serialize = "Invalid pairwise master key identifier (PMKID)")]
+    InvalidPMKID = 53,
+    #[strum(serialize = "Invalid MDE")]
+    InvalidMDE = 54,
+    #[strum(serialize = "Invalid FTE")]
+    InvalidFTE = 55,
+    #[strum(serialize = "Requested TCLAS processing is not supported by the access point or PCP")]
+    RequestedTCLASNotSupportedByAP = 56,
+    #[strum(
+        serialize = "The access point or PCP has insufficient TCLAS processing resources to satisfy the request"
+    )]
+    InsufficientTCLASProcessingResources = 57,
+    #[strum(
+        serialize = "The TS has not been created because the request cannot be honored; however, the HC or PCP suggests that the station transition to a different BSS to set up the TS"
+    )]
+    TryAnotherBSS = 58,
+    #[strum(serialize = "GAS Advertisement Protocol not supported")]
+    GASAdvertisementProtocolNotSupported = 59,
+    #[strum(serialize = "No outstanding GAS request")]
+    NoOutstandingGASRequest = 60,
+    #[strum(serialize = "GAS Response not received from the Advertisement Server")]
+    GASResponseNotReceivedFromServer = 61,
+    #[strum(serialize = "Station timed out waiting for GAS Query Response")]
+    GASQueryTimeout = 62,
+    #[strum(serialize = "LARGE GAS Response is larger than query response length limit")]
+    GASQueryResponseTooLarge = 63,
+    #[strum(serialize = "Request refused because home network does not support request")]
+    RejectedHomeWithSuggestedChanges = 64,
+    #[strum(serialize = "Advertisement Server in the network is not currently reachable")]
+    ServerUnreachable = 65,
+    #[strum(serialize = "Request refused due to permissions received via SSPN interface")]
+    RejectedForSSPPermissions = 67,
+    #[strum(
+        serialize = "Request refused because the access point or PCP does not support unauthenticated access"
+    )]
+    RefusedUnauthenticatedAccessNotSupported = 68,
+    #[strum(serialize = "Invalid contents of RSNE")]
+    InvalidRSNE = 72,
+    #[strum(serialize = "U-APSD coexistence is not supported")]
+    UAPSDCoexistenceNotSupported = 73,
+    #[strum(serialize = "Requested U-APSD coexistence mode is not supported")]
+    UAPSDCoexModeNotSupported = 74,
+    #[strum(
+        serialize = "Requested Interval/Duration value cannot be supported with U-APSD coexistence"
+    )]
+    BadIntervalWithUAPSDCoex = 75,
+    #[strum(serialize = "Authentication is rejected because an anti-clogging token is required")]
+    AntiCloggingTokenRequired = 76,
+    #[strum(
+        serialize = "Authentication is rejected because the offered finite cyclic group is not supported"
+    )]
+    UnsupportedFiniteCyclicGroup = 77,
+    #[strum(
+        serialize = "The TBTT adjustment request has not been successful because the station could not find an alternative TBTT"
+    )]
+    CannotFindAlternativeTBTT = 78,
+    #[strum(serialize = "Transmission failure")]
+    TransmissionFailure = 79,
+    #[strum(serialize = "Requested TCLAS not supported")]
+    RequestedTCLASNotSupported = 80,
+    #[strum(serialize = "TCLAS resources exhausted")]
+    TCLASResourcesExhausted = 81,
+    #[strum(serialize = "Rejected with suggested BSS transition")]
+    RejectedWithSuggestedBSSTransition = 82,
+    #[strum(serialize = "Reject with recommended schedule")]
+    RejectWithSchedule = 83,
+    #[strum(serialize = "Reject because no wakeup schedule specified")]
+    RejectNoWakeupSpecified = 84,
+    #[strum(serialize = "Success, the destination station is in power save mode")]
+    SuccessPowerSaveMode = 85,
+    #[strum(serialize = "FST pending, in process of admitting FST session")]
+    PendingAdmittingFSTSession = 86,
+    #[strum(serialize = "Performing FST now")]
+    PerformingFSTNow = 87,
+    #[strum(serialize = "FST pending, gap(s) in block ack window")]
+    PendingGapInBAWindow = 88,
+    #[strum(serialize = "Reject because of U-PID setting")]
+    RejectUPIDSetting = 89,
+    #[strum(serialize = "(Re)Association refused for some external reason")]
+    RefusedExternalReason = 92,
+    #[strum(serialize = "(Re)Association refused because of memory limits at the access point")]
+    RefusedAPOutOfMemory = 93,
     #[strum(
-        serialize = "Reassociation denied; prior association cannot be identified and transferred"
+        serialize = "(Re)Association refused because emergency services are not supported at the access point"
     )]
-    Denied1,
-    #[strum(serialize = "Association denied for a reason not specified in the 802.11 standard")]
-    Denied2,
-    #[strum(serialize = "Requested authentication algorithm not supported")]
-    Unsupported2,
-    #[strum(serialize = "Unexpected authentication sequence number")]
-    Unexpected,
-    #[strum(serialize = "Authentication rejected; the response to the challenge failed")]
-    Rejected1,
+    RejectedEmergencyServicesNotSupported = 94,
+    #[strum(serialize = "GAS query response not yet received")]
+    QueryResponseOutstanding = 95,
     #[strum(
-        serialize = "Authentication rejected; the next frame in the sequence did not arrive in the expected window"
+        serialize = "Reject since the request is for transition to a frequency band subject to DSE procedures and FST initiator is a dependent station"
     )]
-    Rejected2,
-    #[strum(serialize = "Association denied; the access point is resource-constrained")]
-    Denied3,
+    RejectDSEBand = 96,
+    #[strum(serialize = "Requested TCLAS processing has been terminated by the access point")]
+    TCLASProcessingTerminated = 97,
     #[strum(
-        serialize = "Association denied; the mobile station does not support all of the data rates required by the BSS"
+        serialize = "The TS schedule conflicts with an existing schedule; an alternative schedule is provided"
     )]
-    Denied4,
+    TSScheduleConflict = 98,
     #[strum(
-        serialize = "Association denied; the mobile station does not support the Short Preamble option"
+        serialize = "The association has been denied; however, one or more Multi-band elements are included that can be used by the receiving station to join the BSS"
     )]
-    Denied5,
+    DeniedWithSuggestedBandAndChannel = 99,
+    #[strum(serialize = "The request failed due to a reservation conflict")]
+    MCCAOPReservationConflict = 100,
+    #[strum(serialize = "The request failed due to exceeded MAF limit")]
+    MAFLimitExceeded = 101,
+    #[strum(serialize = "The request failed due to exceeded MCCA track limit")]
+    MCCATrackLimitExceeded = 102,
     #[strum(
-        serialize = "Association denied; the mobile station does not support the PBCC modulation option"
+        serialize = "Association denied because the information in the Spectrum Management field is unacceptable"
     )]
-    Denied6,
+    DeniedDueToSpectrumManagement = 103,
     #[strum(
-        serialize = "Association denied; the mobile station does not support the Channel Agility option"
+        serialize = "Association denied because the requesting station does not support VHT features"
     )]
-    Denied7,
-    #[strum(serialize = "Association denied; Spectrum Management is required")]
-    Denied8,
-    #[strum(serialize = "Association denied; Power Capability value is not acceptable")]
-    Denied9,
-    #[strum(serialize = "Association denied; Supported Channels is not acceptable")]
-    Denied10,
+    DeniedVHTNotSupported = 104,
+    #[strum(serialize = "Enablement denied")]
+    EnablementDenied = 105,
+    #[strum(serialize = "Enablement denied due to restriction from an authorized GDB")]
+    RestrictionFromAuthorizedGDB = 106,
+    #[strum(serialize = "Authorization deenabled")]
+    AuthorizationDeenabled = 107,
+    #[strum(serialize = "Energy-limited operation is not supported")]
+    EnergyLimitedOperationNotSupported = 108,
+    #[strum(serialize = "The request has been rejected; however, the NDP block ack is suggested")]
+    RejectedNDPBlockAckSuggested = 109,
     #[strum(
-        serialize = "Association denied; the mobile station does not support the Short Slot Time"
+        serialize = "The request has been rejected because the proposed max away duration is unacceptable"
     )]
-    Denied11,
-    #[strum(serialize = "Association denied; the mobile station does not support DSSS-OFDM")]
-    Denied12,
-    #[strum(serialize = "Reserved")]
-    Reserved2,
-    #[strum(serialize = "Information element not valid")]
-    Invalid1,
-    #[strum(serialize = "Group (broadcast/multicast) cipher not valid")]
-    Invalid2,
-    #[strum(serialize = "Pairwise (unicast) cipher not valid")]
-    Invalid3,
-    #[strum(serialize = "Authentication and Key Management Protocol (AKMP) not valid")]
-    Invalid4,
+    RejectedMaxAwayDurationUnacceptable = 110,
+    #[strum(serialize = "Flow control operation is supported")]
+    FlowControlOperationSupported = 111,
+    #[strum(serialize = "FILS authentication failure")]
+    FILSAuthenticationFailure = 112,
+    #[strum(serialize = "Unknown authentication server")]
+    UnknownAuthenticationServer = 113,
+    #[strum(serialize = "Request denied due to the allocation of notification period")]
+    DeniedNotificationPeriodAllocation = 116,
+    #[strum(serialize = "Request denied due to the channel splitting")]
+    DeniedChannelSplitting = 117,
+    #[strum(serialize = "Request denied due to the allocation")]
+    DeniedAllocation = 118,
     #[strum(
-        serialize = "Robust Security Network information element (RSN IE) version is not supported"
+        serialize = "Association denied because the requesting station does not support CMMG features"
     )]
-    Unsupported3,
-    #[strum(serialize = "RSN IE capabilites are not supported")]
-    Unsupported4,
-    #[strum(serialize = "Cipher suite rejected due to policy")]
-    Unsupported5,
-    #[strum(serialize = "Reserved for future standardization work")]
-    Unsupported6,
+    CMMGFeaturesNotSupported = 119,
+    #[strum(serialize = "The requested GAS fragment is not available")]
+    GASFragmentNotAvailable = 120,
+    #[strum(serialize = "Success, the CAG versions match")]
+    SuccessCAGVersionsMatch = 121,
+    #[strum(serialize = "Not authorized by the GLK")]
+    GLKNotAuthorized = 122,
+    #[strum(serialize = "Authentication rejected due to unknown password identifier")]
+    UnknownPasswordIdentifier = 123,
+    #[strum(
+        serialize = "Association denied because the requesting station is using a MAC address that violates the local MAC address policy"
+    )]
+    DeniedLocalMACAddressPolicyViolation = 125,
+    #[strum(
+        serialize = "SAE authentication uses direct hashing, instead of looping, to obtain the PWE"
+    )]
+    SAEHashToElement = 126,
 }
 
-impl StatusCode {
-    pub fn parse(i: Input) -> ParseResult<String> {
+/// A status code as it was sent, and its meaning when the standard defines one.
+#[derive(CustomDebug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct Status {
+    pub value: u16,
+    pub code: Option<StatusCode>,
+}
+
+impl Status {
+    pub fn parse(i: Input) -> ParseResult<Self> {
         context("Status Code", |i| {
-            let (i, s) = map(le_u16, Self::try_from)(i)?;
-            match s {
-                Some(s) => Ok((i, s.to_string())),
-                None => Ok((i, "Unknown status code".to_string())),
-            }
+            map(le_u16, |value| Self {
+                value,
+                code: StatusCode::try_from(value),
+            })(i)
         })(i)
     }
+
+    pub fn is_success(&self) -> bool {
+        self.code == Some(StatusCode::Success)
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "{}", code),
+            None => write!(f, "Unknown status code {}", self.value),
+        }
+    }
 }
 
 #[allow(non_camel_case_types)]
@@ -300,24 +686,31 @@ pub enum Element {
     Quiet(QuietElement) = 40,
     IBSS_DFS(IBSSDFS) = 41,
     ERPInfo(ERPInfo) = 42,
-    RobustSecurityInfo(UnknownElement) = 48,
-    ExtendedSupportedRates(UnknownElement) = 50,
-    WifiProtectedAccess(UnknownElement) = 221,
+    HTCapabilities(phy::HTCapabilities) = 45,
+    RobustSecurityInfo(rsn::RobustSecurityNetwork) = 48,
+    ExtendedSupportedRates(SupportedRates) = 50,
+    NeighborReport(action::NeighborReport) = 52,
+    HTOperation(phy::HTOperation) = 61,
+    MeshConfiguration(mesh::MeshConfiguration) = 113,
+    MeshID(mesh::MeshID) = 114,
+    RootAnnouncement(mesh::RootAnnouncement) = 126,
+    PathRequest(mesh::PathRequest) = 130,
+    PathReply(mesh::PathReply) = 131,
+    PathError(mesh::PathError) = 132,
+    VHTCapabilities(phy::VHTCapabilities) = 191,
+    VHTOperation(phy::VHTOperation) = 192,
+    VendorSpecific(vendor::VendorSpecific) = 221,
     Unknown(UnknownElement),
+    Extension(Extension) = 255,
 }
 
 impl Element {
-    fn parse_optional_fields(i: Input) -> ParseResult<Vec<Element>> {
-        let original_i = i;
-        let len = i.len().checked_sub(SEQ_CONTROL_SIZE);
-        if let Some(len) = len {
-            let (i, new_i) = take(len)(i)?;
-            let (_, res) = many0(Self::parse)(new_i)?;
-            Ok((i, res))
-        } else {
-            let err_slice = &original_i[..original_i.offset(i)];
-            Err(nom::Err::Error(Error::malformed(err_slice)))
-        }
+    /// Parses the elements that make up the rest of the frame body.
+    /// The FCS, if any, has already been split off by `dot11::Frame::parse`.
+    pub(crate) fn parse_optional_fields(i: Input) -> ParseResult<Vec<Element>> {
+        let (i, elements) = take(i.len())(i)?;
+        let (_, res) = many0(Self::parse)(elements)?;
+        Ok((i, res))
     }
 
     pub fn parse(i: Input) -> ParseResult<Self> {
@@ -406,6 +799,96 @@ impl Element {
                         (i, Element::ERPInfo(c))
                     }
 
+                    45 => Self::parse_or_unknown(
+                        i,
+                        id,
+                        len,
+                        phy::HTCapabilities::parse,
+                        Element::HTCapabilities,
+                    )?,
+
+                    48 => Self::parse_or_unknown(
+                        i,
+                        id,
+                        len,
+                        rsn::RobustSecurityNetwork::parse,
+                        Element::RobustSecurityInfo,
+                    )?,
+
+                    50 => {
+                        let (i, rates) = SupportedRates::parse(i, id, len)?;
+                        (i, Element::ExtendedSupportedRates(rates))
+                    }
+
+                    52 => {
+                        let (i, n) = action::NeighborReport::parse(i, id, len)?;
+                        (i, Element::NeighborReport(n))
+                    }
+
+                    61 => Self::parse_or_unknown(
+                        i,
+                        id,
+                        len,
+                        phy::HTOperation::parse,
+                        Element::HTOperation,
+                    )?,
+
+                    113 => {
+                        let (i, c) = mesh::MeshConfiguration::parse(i, id, len)?;
+                        (i, Element::MeshConfiguration(c))
+                    }
+
+                    114 => {
+                        let (i, m) = mesh::MeshID::parse(i, id, len)?;
+                        (i, Element::MeshID(m))
+                    }
+
+                    126 => {
+                        let (i, r) = mesh::RootAnnouncement::parse(i, id, len)?;
+                        (i, Element::RootAnnouncement(r))
+                    }
+
+                    130 => {
+                        let (i, p) = mesh::PathRequest::parse(i, id, len)?;
+                        (i, Element::PathRequest(p))
+                    }
+
+                    131 => {
+                        let (i, p) = mesh::PathReply::parse(i, id, len)?;
+                        (i, Element::PathReply(p))
+                    }
+
+                    132 => {
+                        let (i, p) = mesh::PathError::parse(i, id, len)?;
+                        (i, Element::PathError(p))
+                    }
+
+                    191 => Self::parse_or_unknown(
+                        i,
+                        id,
+                        len,
+                        phy::VHTCapabilities::parse,
+                        Element::VHTCapabilities,
+                    )?,
+
+                    192 => Self::parse_or_unknown(
+                        i,
+                        id,
+                        len,
+                        phy::VHTOperation::parse,
+                        Element::VHTOperation,
+                    )?,
+
+                    221 => {
+                        let (i, v) = vendor::VendorSpecific::parse(i, id, len)?;
+                        (i, Element::VendorSpecific(v))
+                    }
+
+                    255 => {
+                        let (i, e) = Extension::parse(i, id, len)?;
+                        (i, Element::Extension(e))
+                    }
+
                     _ => {
                         let (i, c) = UnknownElement::parse(i, id, len)?;
                         (i, Element::Unknown(c))
@@ -416,6 +899,138 @@ impl Element {
             },
         )(i)
     }
+
+    /// Decodes an element with `parser`, keeping it as an unknown element when its body is too short
+    /// or otherwise malformed, so that the elements after it are still read.
+    fn parse_or_unknown<'a, T>(
+        i: Input<'a>,
+        id: u8,
+        len: u8,
+        parser: impl Fn(Input<'a>, u8, u8) -> ParseResult<'a, T>,
+        element: impl Fn(T) -> Element,
+    ) -> ParseResult<'a, Self> {
+        match parser(i, id, len) {
+            Ok((i, res)) => Ok((i, element(res))),
+            Err(_) => map(|i| UnknownElement::parse(i, id, len), Element::Unknown)(i),
+        }
+    }
+}
+
+impl Element {
+    /// Every rate of the Supported Rates and Extended Supported Rates elements, which together list up to 255 rates.
+    pub fn supported_rates(elements: &[Element]) -> Vec<&SupportedRate> {
+        elements
+            .iter()
+            .flat_map(|e| match e {
+                Element::SupportedRates(rates) | Element::ExtendedSupportedRates(rates) => {
+                    rates.supported_rates.iter()
+                }
+                _ => [].iter(),
+            })
+            .collect()
+    }
+
+    /// The name of the mesh BSS announced in a list of elements.
+    pub fn find_mesh_id(elements: &[Element]) -> Option<&str> {
+        elements.iter().find_map(|e| match e {
+            Element::MeshID(mesh_id) => Some(mesh_id.mesh_id.as_str()),
+            _ => None,
+        })
+    }
+
+    /// The network name announced in a list of elements. It is empty for the wildcard SSID of probe
+    /// requests, and for hidden networks, whose beacons carry an empty SSID or one of NUL bytes.
+    pub fn find_ssid(elements: &[Element]) -> Option<&str> {
+        elements.iter().find_map(|e| match e {
+            Element::SSID(ssid) if ssid.ssid.bytes().all(|b| b == 0) => Some(""),
+            Element::SSID(ssid) => Some(ssid.ssid.as_str()),
+            _ => None,
+        })
+    }
+
+    pub fn find_rsn(elements: &[Element]) -> Option<&rsn::RobustSecurityNetwork> {
+        elements.iter().find_map(|e| match e {
+            Element::RobustSecurityInfo(rsn) => Some(rsn),
+            _ => None,
+        })
+    }
+}
+
+/// An element with ID 255, whose first byte is an extension ID that tells what follows.
+#[derive(CustomDebug, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Extension {
+    /// Session identifier of a FILS (fast initial link setup) authentication.
+    FILSSession(Blob) = 4,
+    FILSPublicKey {
+        #[debug(format = "{}")]
+        key_type: u8,
+        key: Blob,
+    } = 12,
+    FILSNonce(Blob) = 13,
+    /// The public key a station offers for Opportunistic Wireless Encryption.
+    OWEDiffieHellman {
+        #[debug(format = "{}")]
+        group: u16,
+        public_key: Blob,
+    } = 32,
+    HECapabilities(phy::HECapabilities) = 35,
+    HEOperation(phy::HEOperation) = 36,
+    EHTOperation(phy::EHTOperation) = 106,
+    EHTCapabilities(phy::EHTCapabilities) = 108,
+    Unknown {
+        #[debug(format = "{}")]
+        ext_id: u8,
+        data: Blob,
+    },
+}
+
+impl Extension {
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Management Frame Extension Element", |i| {
+            let (i, body) = take(len)(i)?;
+            let (body, ext_id) = le_u8(body)?;
+            let decoded = match ext_id {
+                4 => Ok((body, Extension::FILSSession(Blob::new(body)))),
+                12 => map(le_u8, |key_type| Extension::FILSPublicKey {
+                    key_type,
+                    key: Blob::new(&body[1..]),
+                })(body),
+                13 => Ok((body, Extension::FILSNonce(Blob::new(body)))),
+                32 => map(le_u16, |group| Extension::OWEDiffieHellman {
+                    group,
+                    public_key: Blob::new(&body[2..]),
+                })(body),
+                35 => map(
+                    |i| phy::HECapabilities::parse(i, id, len),
+                    Extension::HECapabilities,
+                )(body),
+                36 => map(
+                    |i| phy::HEOperation::parse(i, id, len),
+                    Extension::HEOperation,
+                )(body),
+                106 => map(
+                    |i| phy::EHTOperation::parse(i, id, len),
+                    Extension::EHTOperation,
+                )(body),
+                108 => map(
+                    |i| phy::EHTCapabilities::parse(i, id, len),
+                    Extension::EHTCapabilities,
+                )(body),
+                _ => Err(nom::Err::Error(Error::malformed(body))),
+            };
+            // Extensions we do not know, or that do not parse, are kept as raw bytes.
+            let res = match decoded {
+                Ok((_, res)) => res,
+                Err(_) => Extension::Unknown {
+                    ext_id,
+                    data: Blob::new(body),
+                },
+            };
+
+            Ok((i, res))
+        })(i)
+    }
 }
 
 #[derive(CustomDebug, Serialize, Deserialize)]
@@ -447,12 +1062,25 @@ pub struct CommonFieldsElement {
     pub len: u8,
 }
 
-#[derive(Serialize, Deserialize, Debug, TryFromPrimitive)]
+#[derive(Serialize, Deserialize, Debug, TryFromPrimitive, Clone, Copy, PartialEq, Display)]
 #[repr(u16)]
 pub enum AuthenticationAlgorithm {
-    OpenSystemAuthentication,
-    SharedKeyAuthentication,
-    Reserved,
+    #[strum(serialize = "Open System")]
+    OpenSystemAuthentication = 0,
+    #[strum(serialize = "Shared Key")]
+    SharedKeyAuthentication = 1,
+    #[strum(serialize = "Fast BSS Transition")]
+    FastBSSTransition = 2,
+    #[strum(serialize = "SAE")]
+    SAE = 3,
+    #[strum(serialize = "FILS Shared Key")]
+    FILSSharedKey = 4,
+    #[strum(serialize = "FILS Shared Key with PFS")]
+    FILSSharedKeyPFS = 5,
+    #[strum(serialize = "FILS Public Key")]
+    FILSPublicKey = 6,
+    #[strum(serialize = "Vendor specific")]
+    VendorSpecific = 0xFFFF,
 }
 
 impl AuthenticationAlgorithm {
@@ -464,7 +1092,7 @@ impl AuthenticationAlgorithm {
     }
 }
 
-#[derive(CustomDebug, Serialize, Deserialize)]
+#[derive(CustomDebug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct SupportedRate {
     #[debug(format = "{}")]
     /// A 7 bit field that represents a supported rate in multiples of 500 kbps.
@@ -475,9 +1103,13 @@ pub struct SupportedRate {
 }
 
 impl SupportedRate {
+    pub fn mbps(&self) -> f32 {
+        f32::from(u8::from(self.label)) / 2.0
+    }
+
     pub fn parse(i: Input) -> ParseResult<Self> {
         context("802.11 Management Frame Supported Rate", |i| {
-            let (i, (label, is_mandatory)) = bits(tuple((u7::parse, u1::parse)))(i)?;
+            let (i, (is_mandatory, label)) = bits(tuple((u1::parse, u7::parse)))(i)?;
             let res = Self {
                 label,
                 is_mandatory,
@@ -766,12 +1398,9 @@ impl SSID {
         context("802.11 Management Frame SSID", |i| {
             let common = CommonFieldsElement { id, len };
             let (i, ssid) = take(common.len)(i)?;
-            let mut ssid = std::str::from_utf8(ssid)
+            let ssid = std::str::from_utf8(ssid)
                 .unwrap_or("Invalid/Malformed SSID")
                 .to_string();
-            if ssid.is_empty() {
-                ssid = "Wildcard SSID".to_string();
-            }
             let res = Self { common, ssid };
 
             Ok((i, res))
@@ -1160,28 +1789,39 @@ impl BeaconFrameBody {
     }
 }
 
+impl BeaconFrameBody {
+    pub fn ssid(&self) -> Option<&str> {
+        Element::find_ssid(&self.dynamic_fields)
+    }
+
+    pub fn security(&self) -> rsn::Security {
+        rsn::Security::from_elements(&self.capability_info, &self.dynamic_fields)
+    }
+}
+
 #[derive(CustomDebug, Serialize, Deserialize)]
 pub struct ProbeRequestFrameBody {
-    pub ssid: Element,
-    pub supported_rates: Element,
-    pub extended_support_rates: Element,
+    pub dynamic_fields: Vec<Element>,
 }
 
 impl ProbeRequestFrameBody {
     pub fn parse(i: Input) -> ParseResult<Self> {
         context("802.11 Management Frame: Probe request body", |i| {
-            let (i, ssid) = Element::parse(i)?;
-            let (i, supported_rates) = Element::parse(i)?;
-            let (i, extended_support_rates) = Element::parse(i)?;
-            let res = Self {
-                ssid,
-                supported_rates,
-                extended_support_rates,
-            };
+            let (i, dynamic_fields) = Element::parse_optional_fields(i)?;
+            let res = Self { dynamic_fields };
 
             Ok((i, res))
         })(i)
     }
+
+    /// The network probed for, or `Some("")` for the wildcard SSID that any access point answers.
+    pub fn ssid(&self) -> Option<&str> {
+        Element::find_ssid(&self.dynamic_fields)
+    }
+
+    pub fn supported_rates(&self) -> Vec<&SupportedRate> {
+        Element::supported_rates(&self.dynamic_fields)
+    }
 }
 
 #[derive(CustomDebug, Serialize, Deserialize)]
@@ -1213,13 +1853,22 @@ impl ProbeResponseFrameBody {
     }
 }
 
+impl ProbeResponseFrameBody {
+    pub fn ssid(&self) -> Option<&str> {
+        Element::find_ssid(&self.dynamic_fields)
+    }
+
+    pub fn security(&self) -> rsn::Security {
+        rsn::Security::from_elements(&self.capability_info, &self.dynamic_fields)
+    }
+}
+
 #[derive(CustomDebug, Serialize, Deserialize)]
 pub struct AssociationRequestFrameBody {
     pub capability_info: CapabilityInfo,
     #[debug(format = "0x{:04X}")]
     pub listen_interval: u16,
-    pub ssid: Element,
-    pub supported_rates: Element,
+    pub dynamic_fields: Vec<Element>,
 }
 
 impl AssociationRequestFrameBody {
@@ -1227,18 +1876,29 @@ impl AssociationRequestFrameBody {
         context("802.11 Management Frame: association request body", |i| {
             let (i, capability_info) = CapabilityInfo::parse(i)?;
             let (i, listen_interval) = le_u16(i)?;
-            let (i, ssid) = Element::parse(i)?;
-            let (i, supported_rates) = Element::parse(i)?;
+            let (i, dynamic_fields) = Element::parse_optional_fields(i)?;
             let res = Self {
                 capability_info,
                 listen_interval,
-                ssid,
-                supported_rates,
+                dynamic_fields,
             };
 
             Ok((i, res))
         })(i)
     }
+
+    pub fn ssid(&self) -> Option<&str> {
+        Element::find_ssid(&self.dynamic_fields)
+    }
+
+    pub fn supported_rates(&self) -> Vec<&SupportedRate> {
+        Element::supported_rates(&self.dynamic_fields)
+    }
+
+    /// The ciphers and key management the station chose among those the access point advertised.
+    pub fn rsn(&self) -> Option<&rsn::RobustSecurityNetwork> {
+        Element::find_rsn(&self.dynamic_fields)
+    }
 }
 
 #[derive(CustomDebug, Serialize, Deserialize)]
@@ -1247,8 +1907,7 @@ pub struct ReassociationRequestFrameBody {
     #[debug(format = "0x{:04X}")]
     pub listen_interval: u16,
     pub current_ap_address: Addr,
-    pub ssid: Element,
-    pub supported_rates: Element,
+    pub dynamic_fields: Vec<Element>,
 }
 
 impl ReassociationRequestFrameBody {
@@ -1257,47 +1916,66 @@ impl ReassociationRequestFrameBody {
             let (i, capability_info) = CapabilityInfo::parse(i)?;
             let (i, listen_interval) = le_u16(i)?;
             let (i, current_ap_address) = Addr::parse(i)?;
-            let (i, ssid) = Element::parse(i)?;
-            let (i, supported_rates) = Element::parse(i)?;
+            let (i, dynamic_fields) = Element::parse_optional_fields(i)?;
             let res = Self {
                 capability_info,
                 listen_interval,
                 current_ap_address,
-                ssid,
-                supported_rates,
+                dynamic_fields,
             };
 
             Ok((i, res))
         })(i)
     }
+
+    pub fn ssid(&self) -> Option<&str> {
+        Element::find_ssid(&self.dynamic_fields)
+    }
+
+    pub fn supported_rates(&self) -> Vec<&SupportedRate> {
+        Element::supported_rates(&self.dynamic_fields)
+    }
+
+    pub fn rsn(&self) -> Option<&rsn::RobustSecurityNetwork> {
+        Element::find_rsn(&self.dynamic_fields)
+    }
 }
 
 #[derive(CustomDebug, Serialize, Deserialize)]
 pub struct AssociationResponseFrameBody {
     pub capability_info: CapabilityInfo,
-    pub status_code: String,
+    pub status_code: Status,
     #[debug(format = "0x{:04X}")]
     pub association_id: u16,
-    pub supported_rates: Element,
+    pub dynamic_fields: Vec<Element>,
 }
 
 impl AssociationResponseFrameBody {
     pub fn parse(i: Input) -> ParseResult<Self> {
-        context("802.11 Management Frame: association request body", |i| {
+        context("802.11 Management Frame: association response body", |i| {
             let (i, capability_info) = CapabilityInfo::parse(i)?;
-            let (i, status_code) = StatusCode::parse(i)?;
+            let (i, status_code) = Status::parse(i)?;
             let (i, association_id) = le_u16(i)?;
-            let (i, supported_rates) = Element::parse(i)?;
+            let (i, dynamic_fields) = Element::parse_optional_fields(i)?;
             let res = Self {
                 capability_info,
                 status_code,
                 association_id,
-                supported_rates,
+                dynamic_fields,
             };
 
             Ok((i, res))
         })(i)
     }
+
+    pub fn supported_rates(&self) -> Vec<&SupportedRate> {
+        Element::supported_rates(&self.dynamic_fields)
+    }
+
+    /// Only present in the responses of fast BSS transitions.
+    pub fn rsn(&self) -> Option<&rsn::RobustSecurityNetwork> {
+        Element::find_rsn(&self.dynamic_fields)
+    }
 }
 
 #[derive(CustomDebug, Serialize, Deserialize)]
@@ -1305,8 +1983,11 @@ pub struct AuthenticationFrameBody {
     pub algo_num: Option<AuthenticationAlgorithm>,
     #[debug(format = "0x{:04X}")]
     pub auth_seq: u16,
-    pub status_code: String,
-    pub challenge_text: Element,
+    pub status_code: Status,
+    /// The fields of SAE authentication, which come before its elements.
+    pub sae: Option<sae::SAE>,
+    /// The challenge text of shared key authentication, or the elements of fast BSS transitions and FILS.
+    pub dynamic_fields: Vec<Element>,
 }
 
 impl AuthenticationFrameBody {
@@ -1314,29 +1995,38 @@ impl AuthenticationFrameBody {
         context("802.11 Management Frame: auth frame body", |i| {
             let (i, algo_num) = AuthenticationAlgorithm::parse(i)?;
             let (i, auth_seq) = le_u16(i)?;
-            let (i, status_code) = StatusCode::parse(i)?;
-            let (i, challenge_text) = Element::parse(i)?;
+            let (i, status_code) = Status::parse(i)?;
+            let (i, sae) = match algo_num {
+                Some(AuthenticationAlgorithm::SAE) => sae::SAE::parse(i, auth_seq, &status_code)?,
+                _ => (i, None),
+            };
+            let (i, dynamic_fields) = Element::parse_optional_fields(i)?;
             let res = Self {
                 algo_num,
                 auth_seq,
                 status_code,
-                challenge_text,
+                sae,
+                dynamic_fields,
             };
 
             Ok((i, res))
         })(i)
     }
+
+    pub fn rsn(&self) -> Option<&rsn::RobustSecurityNetwork> {
+        Element::find_rsn(&self.dynamic_fields)
+    }
 }
 
 #[derive(CustomDebug, Serialize, Deserialize)]
 pub struct DeauthenticationFrameBody {
-    reason_code: String,
+    pub reason_code: Reason,
 }
 
 impl DeauthenticationFrameBody {
     pub fn parse(i: Input) -> ParseResult<Self> {
         context("802.11 Management Frame: deauthentication", |i| {
-            let (i, reason_code) = ReasonCode::parse(i)?;
+            let (i, reason_code) = Reason::parse(i)?;
             let res = Self { reason_code };
 
             Ok((i, res))
@@ -1345,4 +2035,102 @@ impl DeauthenticationFrameBody {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_rates_and_extensions() {
+        let elements: &[u8] = &[
+            1, 4, 0x82, 0x84, 0x8B, 0x96, // 1, 2, 5.5 and 11 Mb/s, all basic
+            50, 2, 0x0C, 0x6C, // 6 and 54 Mb/s
+            255, 5, 32, 0x13, 0x00, 0xAB, 0xCD, // OWE, group 19
+            255, 2, 200, 0x01, // unknown extension
+        ];
+        let (_, elements) = many0(Element::parse)(elements).unwrap();
+
+        let rates = Element::supported_rates(&elements);
+        let mbps: Vec<f32> = rates.iter().map(|r| r.mbps()).collect();
+        assert_eq!(mbps, vec![1.0, 2.0, 5.5, 11.0, 6.0, 54.0]);
+        assert_eq!(rates[0].is_mandatory, u1::new(1));
+        assert_eq!(rates[4].is_mandatory, u1::new(0));
+
+        match elements[2] {
+            Element::Extension(Extension::OWEDiffieHellman {
+                group,
+                ref public_key,
+            }) => {
+                assert_eq!(group, 19);
+                assert_eq!(public_key.0, vec![0xAB, 0xCD]);
+            }
+            ref e => panic!("expected an OWE element, got {:?}", e),
+        }
+        assert!(matches!(
+            elements[3],
+            Element::Extension(Extension::Unknown { ext_id: 200, .. })
+        ));
+    }
+
+    #[test]
+    fn assert_reason_and_status_codes() {
+        let (_, body) = DeauthenticationFrameBody::parse(&[0x0F, 0x00]).unwrap();
+        assert_eq!(body.reason_code.value, 15);
+        assert_eq!(
+            body.reason_code.code,
+            Some(ReasonCode::FourWayHandshakeTimeout)
+        );
+
+        let (_, reason) = Reason::parse(&[0x2E, 0x00]).unwrap();
+        assert_eq!(reason.code, Some(ReasonCode::AuthorizedAccessLimitReached));
+        assert_eq!(
+            reason.to_string(),
+            "Disassociated because authorized access limit reached"
+        );
+
+        let (_, reason) = Reason::parse(&[0x2A, 0x00]).unwrap();
+        assert_eq!(reason.code, None);
+        assert_eq!(reason.to_string(), "Unknown reason code 42");
+
+        let (_, status) = Status::parse(&[0x7E, 0x00]).unwrap();
+        assert_eq!(status.code, Some(StatusCode::SAEHashToElement));
+        assert!(!status.is_success());
+    }
+
+    #[test]
+    fn assert_probe_request_ssid() {
+        let (_, body) =
+            ProbeRequestFrameBody::parse(&[0, 4, b'h', b'o', b'm', b'e', 1, 1, 0x82]).unwrap();
+        assert_eq!(body.ssid(), Some("home"));
+        assert_eq!(body.supported_rates().len(), 1);
+
+        let (_, body) = ProbeRequestFrameBody::parse(&[0, 0, 1, 1, 0x82]).unwrap();
+        assert_eq!(body.ssid(), Some(""));
+
+        let (_, body) = ProbeRequestFrameBody::parse(&[1, 1, 0x82]).unwrap();
+        assert_eq!(body.ssid(), None);
+    }
+
+    #[test]
+    fn assert_malformed_elements() {
+        let elements: &[u8] = &[
+            45, 2, 0x6F, 0x01, // HT Capabilities cut short
+            61, 0, // empty HT Operation
+            48, 4, 0x01, 0x00, 0x00, 0x0F, // RSN with a truncated group cipher
+            0, 4, b'h', b'o', b'm', b'e',
+        ];
+        let (_, elements) = Element::parse_optional_fields(elements).unwrap();
+
+        assert!(matches!(
+            elements[0],
+            Element::Unknown(UnknownElement { id: 45, len: 2 })
+        ));
+        assert!(matches!(
+            elements[1],
+            Element::Unknown(UnknownElement { id: 61, len: 0 })
+        ));
+        assert!(matches!(
+            elements[2],
+            Element::Unknown(UnknownElement { id: 48, len: 4 })
+        ));
+        assert_eq!(Element::find_ssid(&elements), Some("home"));
+    }
+}
@@ -0,0 +1,528 @@
+//! 802.11s mesh networking: the Mesh Control field of mesh data frames, the elements that describe
+//! a mesh BSS and the HWMP path selection elements, and the bodies of the mesh action frames.
+
+use crate::{
+    core::{blob::Blob, parse::*},
+    layer2::{
+        datalink::Addr,
+        wifi::management::{CommonFieldsElement, Element},
+    },
+};
+
+use custom_debug_derive::*;
+use nom::{
+    bytes::complete::take,
+    combinator::{cond, verify},
+    error::context,
+    multi::count,
+    number::complete::{le_u16, le_u32, le_u8},
+    sequence::tuple,
+};
+use serde::{Deserialize, Serialize};
+
+/// The Mesh Control field that starts the body of mesh data frames and multihop action frames.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct MeshControl {
+    #[debug(format = "0x{:02X}")]
+    pub flags: u8,
+    /// Decremented at each hop; the frame is dropped when it reaches zero.
+    #[debug(format = "{}")]
+    pub ttl: u8,
+    /// Used with the mesh source address to drop broadcast frames already forwarded.
+    #[debug(format = "{}")]
+    pub sequence_number: u32,
+    /// The addresses of stations outside the mesh that the frame comes from or goes to.
+    pub address_extension: Vec<Addr>,
+}
+
+impl MeshControl {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 Mesh Control", |i| {
+            // Only the two address extension mode bits are defined, and mode 3 is reserved.
+            let (i, flags) = verify(le_u8, |f| f & 0xFC == 0 && f & 0b11 != 0b11)(i)?;
+            let (i, (ttl, sequence_number)) = tuple((le_u8, le_u32))(i)?;
+            let (i, address_extension) = count(Addr::parse, (flags & 0b11) as usize)(i)?;
+            let res = Self {
+                flags,
+                ttl,
+                sequence_number,
+                address_extension,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    /// The end-to-end destination and source (Address 5 and 6) of a data frame proxied through the mesh.
+    pub fn external_addresses(&self) -> Option<(&Addr, &Addr)> {
+        match self.address_extension[..] {
+            [ref da, ref sa] => Some((da, sa)),
+            _ => None,
+        }
+    }
+}
+
+/// The Mesh ID element (ID 114), the name of a mesh BSS, which sends beacons with a wildcard SSID.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct MeshID {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    pub mesh_id: String,
+}
+
+impl MeshID {
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Mesh ID", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (i, mesh_id) = take(len)(i)?;
+            let res = Self {
+                common,
+                mesh_id: String::from_utf8_lossy(mesh_id).to_string(),
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// The Mesh Configuration element (ID 113): the protocols a mesh BSS runs, which peers have to share.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct MeshConfiguration {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    /// 1 for HWMP.
+    #[debug(format = "{}")]
+    pub path_selection_protocol: u8,
+    /// 1 for the airtime link metric.
+    #[debug(format = "{}")]
+    pub path_selection_metric: u8,
+    #[debug(format = "{}")]
+    pub congestion_control_mode: u8,
+    /// 1 for neighbor offset synchronization.
+    #[debug(format = "{}")]
+    pub synchronization_method: u8,
+    /// 0 for an open mesh, 1 for SAE and 2 for 802.1X.
+    #[debug(format = "{}")]
+    pub authentication_protocol: u8,
+    #[debug(format = "0x{:02X}")]
+    pub formation_info: u8,
+    #[debug(format = "0x{:02X}")]
+    pub capability: u8,
+}
+
+impl MeshConfiguration {
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Mesh Configuration", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (i, body) = take(len)(i)?;
+            let (_, fields) = tuple((le_u8, le_u8, le_u8, le_u8, le_u8, le_u8, le_u8))(body)?;
+            let (
+                path_selection_protocol,
+                path_selection_metric,
+                congestion_control_mode,
+                synchronization_method,
+                authentication_protocol,
+                formation_info,
+                capability,
+            ) = fields;
+            let res = Self {
+                common,
+                path_selection_protocol,
+                path_selection_metric,
+                congestion_control_mode,
+                synchronization_method,
+                authentication_protocol,
+                formation_info,
+                capability,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    /// The number of mesh peerings the station has.
+    pub fn peerings(&self) -> u8 {
+        (self.formation_info >> 1) & 0x3F
+    }
+
+    pub fn connected_to_gate(&self) -> bool {
+        self.formation_info & 0x01 != 0
+    }
+
+    pub fn accepting_peerings(&self) -> bool {
+        self.capability & 0x01 != 0
+    }
+
+    pub fn forwarding(&self) -> bool {
+        self.capability & 0x08 != 0
+    }
+}
+
+/// The flag of HWMP elements telling that an external address follows the mesh station address.
+const ADDRESS_EXTENSION: u8 = 0x40;
+
+/// A destination of a path request.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct PathRequestTarget {
+    #[debug(format = "0x{:02X}")]
+    pub flags: u8,
+    pub address: Addr,
+    #[debug(format = "{}")]
+    pub sequence_number: u32,
+}
+
+/// The PREQ element (ID 130), flooded by HWMP to discover a path to its targets.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct PathRequest {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    #[debug(format = "0x{:02X}")]
+    pub flags: u8,
+    #[debug(format = "{}")]
+    pub hop_count: u8,
+    #[debug(format = "{}")]
+    pub ttl: u8,
+    #[debug(format = "{}")]
+    pub path_discovery_id: u32,
+    pub originator: Addr,
+    #[debug(format = "{}")]
+    pub originator_sequence_number: u32,
+    pub originator_external: Option<Addr>,
+    /// In TUs.
+    #[debug(format = "{}")]
+    pub lifetime: u32,
+    #[debug(format = "{}")]
+    pub metric: u32,
+    pub targets: Vec<PathRequestTarget>,
+}
+
+impl PathRequest {
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Mesh Path Request", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (i, body) = take(len)(i)?;
+            let (body, (flags, hop_count, ttl, path_discovery_id)) =
+                tuple((le_u8, le_u8, le_u8, le_u32))(body)?;
+            let (body, (originator, originator_sequence_number)) =
+                tuple((Addr::parse, le_u32))(body)?;
+            let (body, originator_external) =
+                cond(flags & ADDRESS_EXTENSION != 0, Addr::parse)(body)?;
+            let (body, (lifetime, metric, target_count)) = tuple((le_u32, le_u32, le_u8))(body)?;
+            let (_, targets) = count(
+                |i| {
+                    let (i, (flags, address, sequence_number)) =
+                        tuple((le_u8, Addr::parse, le_u32))(i)?;
+                    let target = PathRequestTarget {
+                        flags,
+                        address,
+                        sequence_number,
+                    };
+                    Ok((i, target))
+                },
+                target_count as usize,
+            )(body)?;
+            let res = Self {
+                common,
+                flags,
+                hop_count,
+                ttl,
+                path_discovery_id,
+                originator,
+                originator_sequence_number,
+                originator_external,
+                lifetime,
+                metric,
+                targets,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    /// Whether the originator is a root announcing itself to every station, rather than looking for a path.
+    pub fn proactive(&self) -> bool {
+        self.targets.len() == 1 && self.targets[0].address == Addr([0xFF; 6])
+    }
+}
+
+/// The PREP element (ID 131), the reply of a path request target, sent back along the discovered path.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct PathReply {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    #[debug(format = "0x{:02X}")]
+    pub flags: u8,
+    #[debug(format = "{}")]
+    pub hop_count: u8,
+    #[debug(format = "{}")]
+    pub ttl: u8,
+    pub target: Addr,
+    #[debug(format = "{}")]
+    pub target_sequence_number: u32,
+    pub target_external: Option<Addr>,
+    #[debug(format = "{}")]
+    pub lifetime: u32,
+    #[debug(format = "{}")]
+    pub metric: u32,
+    pub originator: Addr,
+    #[debug(format = "{}")]
+    pub originator_sequence_number: u32,
+}
+
+impl PathReply {
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Mesh Path Reply", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (i, body) = take(len)(i)?;
+            let (body, (flags, hop_count, ttl, target, target_sequence_number)) =
+                tuple((le_u8, le_u8, le_u8, Addr::parse, le_u32))(body)?;
+            let (body, target_external) = cond(flags & ADDRESS_EXTENSION != 0, Addr::parse)(body)?;
+            let (_, (lifetime, metric, originator, originator_sequence_number)) =
+                tuple((le_u32, le_u32, Addr::parse, le_u32))(body)?;
+            let res = Self {
+                common,
+                flags,
+                hop_count,
+                ttl,
+                target,
+                target_sequence_number,
+                target_external,
+                lifetime,
+                metric,
+                originator,
+                originator_sequence_number,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// A destination that became unreachable.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct PathErrorDestination {
+    #[debug(format = "0x{:02X}")]
+    pub flags: u8,
+    pub address: Addr,
+    #[debug(format = "{}")]
+    pub sequence_number: u32,
+    pub external: Option<Addr>,
+    #[debug(format = "{}")]
+    pub reason_code: u16,
+}
+
+/// The PERR element (ID 132), announcing destinations that can no longer be reached.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct PathError {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    #[debug(format = "{}")]
+    pub ttl: u8,
+    pub destinations: Vec<PathErrorDestination>,
+}
+
+impl PathError {
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Mesh Path Error", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (i, body) = take(len)(i)?;
+            let (body, (ttl, destination_count)) = tuple((le_u8, le_u8))(body)?;
+            let (_, destinations) = count(
+                |i| {
+                    let (i, (flags, address, sequence_number)) =
+                        tuple((le_u8, Addr::parse, le_u32))(i)?;
+                    let (i, external) = cond(flags & ADDRESS_EXTENSION != 0, Addr::parse)(i)?;
+                    let (i, reason_code) = le_u16(i)?;
+                    let destination = PathErrorDestination {
+                        flags,
+                        address,
+                        sequence_number,
+                        external,
+                        reason_code,
+                    };
+                    Ok((i, destination))
+                },
+                destination_count as usize,
+            )(body)?;
+            let res = Self {
+                common,
+                ttl,
+                destinations,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// The RANN element (ID 126), periodically flooded by a root mesh station so that others build paths to it.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct RootAnnouncement {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    #[debug(format = "0x{:02X}")]
+    pub flags: u8,
+    #[debug(format = "{}")]
+    pub hop_count: u8,
+    #[debug(format = "{}")]
+    pub ttl: u8,
+    pub root: Addr,
+    #[debug(format = "{}")]
+    pub sequence_number: u32,
+    /// In TUs.
+    #[debug(format = "{}")]
+    pub interval: u32,
+    #[debug(format = "{}")]
+    pub metric: u32,
+}
+
+impl RootAnnouncement {
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Mesh Root Announcement", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (i, body) = take(len)(i)?;
+            let (_, (flags, hop_count, ttl, root, sequence_number, interval, metric)) =
+                tuple((le_u8, le_u8, le_u8, Addr::parse, le_u32, le_u32, le_u32))(body)?;
+            let res = Self {
+                common,
+                flags,
+                hop_count,
+                ttl,
+                root,
+                sequence_number,
+                interval,
+                metric,
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// The body of Mesh Peering Open and Confirm frames, which set up a link between two mesh stations.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct MeshPeering {
+    #[debug(format = "0x{:04X}")]
+    pub capability: u16,
+    /// The association ID given to the peer, in Mesh Peering Confirm frames.
+    pub aid: Option<u16>,
+    pub elements: Vec<Element>,
+}
+
+impl MeshPeering {
+    pub fn parse_open(i: Input) -> ParseResult<Self> {
+        let (i, capability) = le_u16(i)?;
+        let (i, elements) = Element::parse_optional_fields(i)?;
+        let res = Self {
+            capability,
+            aid: None,
+            elements,
+        };
+        Ok((i, res))
+    }
+
+    pub fn parse_confirm(i: Input) -> ParseResult<Self> {
+        let (i, (capability, aid)) = tuple((le_u16, le_u16))(i)?;
+        let (i, elements) = Element::parse_optional_fields(i)?;
+        let res = Self {
+            capability,
+            aid: Some(aid),
+            elements,
+        };
+        Ok((i, res))
+    }
+}
+
+/// The body of a multihop action frame, forwarded across the mesh like a data frame.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct MultihopAction {
+    pub mesh_control: MeshControl,
+    pub body: Blob,
+}
+
+impl MultihopAction {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        let (i, mesh_control) = MeshControl::parse(i)?;
+        let (i, body) = take(i.len())(i)?;
+        let res = Self {
+            mesh_control,
+            body: Blob::new(body),
+        };
+        Ok((i, res))
+    }
+}
+
+/// One line per HWMP element of a list, such as `PREQ <originator> -> <targets>`.
+pub fn path_selection_summary(elements: &[Element]) -> Vec<String> {
+    elements
+        .iter()
+        .filter_map(|e| match e {
+            Element::PathRequest(preq) => {
+                let targets: Vec<String> =
+                    preq.targets.iter().map(|t| t.address.to_string()).collect();
+                Some(format!("PREQ {} -> {}", preq.originator, targets.join(",")))
+            }
+            Element::PathReply(prep) => Some(format!(
+                "PREP {} -> {} metric {}",
+                prep.target, prep.originator, prep.metric
+            )),
+            Element::PathError(perr) => {
+                let unreachable: Vec<String> = perr
+                    .destinations
+                    .iter()
+                    .map(|d| d.address.to_string())
+                    .collect();
+                Some(format!("PERR {}", unreachable.join(",")))
+            }
+            Element::RootAnnouncement(rann) => Some(format!("RANN {}", rann.root)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_path_selection() {
+        let elements: &[u8] = &[
+            130, 37, // PREQ
+            0x00, 0x00, 0x1F, 0x07, 0x00, 0x00,
+            0x00, // flags, hop count 0, TTL 31, discovery ID 7
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x01, 0x10, 0x00, 0x00, 0x00, // originator
+            0x88, 0x13, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x01, // lifetime, metric, 1 target
+            0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, //
+            132, 15, // PERR
+            0x1F, 0x01, // TTL 31, 1 destination
+            0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x03, 0x05, 0x00, 0x00, 0x00, 0x3F, 0x00,
+        ];
+        let (rest, elements) = Element::parse_optional_fields(elements).unwrap();
+        assert!(rest.is_empty());
+        match elements[0] {
+            Element::PathRequest(ref preq) => {
+                assert_eq!(preq.ttl, 31);
+                assert_eq!(preq.path_discovery_id, 7);
+                assert_eq!(preq.lifetime, 5000);
+                assert!(!preq.proactive());
+            }
+            ref e => panic!("expected a PREQ, got {:?}", e),
+        }
+        match elements[1] {
+            Element::PathError(ref perr) => assert_eq!(perr.destinations[0].reason_code, 63),
+            ref e => panic!("expected a PERR, got {:?}", e),
+        }
+        assert_eq!(
+            path_selection_summary(&elements),
+            vec![
+                "PREQ 02:00:00:00:00:01 -> 02:00:00:00:00:03",
+                "PERR 02:00:00:00:00:03"
+            ]
+        );
+
+        // Address extension mode 2: the frame is proxied between two stations outside the mesh.
+        let mesh_control: &[u8] = &[
+            0x02, 0x05, 0x2A, 0x00, 0x00, 0x00, // flags, TTL 5, sequence 42
+            0x02, 0x00, 0x00, 0x00, 0x00, 0x05, 0x02, 0x00, 0x00, 0x00, 0x00, 0x06,
+        ];
+        let (_, mesh_control) = MeshControl::parse(mesh_control).unwrap();
+        let (da, sa) = mesh_control.external_addresses().unwrap();
+        assert_eq!(da.to_string(), "02:00:00:00:00:05");
+        assert_eq!(sa.to_string(), "02:00:00:00:00:06");
+        assert!(MeshControl::parse(&[0x03, 0x05, 0, 0, 0, 0]).is_err());
+    }
+}
@@ -0,0 +1,691 @@
+use crate::{
+    core::{blob::Blob, parse::*},
+    layer2::wifi::management::{CommonFieldsElement, Element, Extension},
+};
+
+use custom_debug_derive::*;
+use nom::{
+    bytes::complete::take,
+    combinator::{map, opt},
+    error::context,
+    number::complete::{le_u16, le_u24, le_u32, le_u8},
+    sequence::tuple,
+};
+use serde::{Deserialize, Serialize};
+
+fn blob(len: usize) -> impl Fn(Input) -> ParseResult<Blob> {
+    move |i| map(take(len), Blob::new)(i)
+}
+
+/// A pair of receive and transmit MCS maps, present only when `present` is set.
+fn mcs_maps(present: bool, i: Input) -> ParseResult<Option<(MCSMap, MCSMap)>> {
+    if present {
+        map(tuple((map(le_u16, MCSMap), map(le_u16, MCSMap))), Some)(i)
+    } else {
+        Ok((i, None))
+    }
+}
+
+/// A VHT or HE MCS map: two bits per spatial stream, from 1 to 8, where 3 means not supported.
+#[derive(CustomDebug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct MCSMap(#[debug(format = "0x{:04X}")] pub u16);
+
+impl MCSMap {
+    /// The value for `nss` spatial streams (1-based): 0, 1 and 2 are the highest supported MCS group.
+    pub fn get(self, nss: u8) -> Option<u8> {
+        match (self.0 >> ((nss - 1) * 2)) & 0b11 {
+            3 => None,
+            v => Some(v as u8),
+        }
+    }
+
+    pub fn spatial_streams(self) -> u8 {
+        (1..=8).filter(|&nss| self.get(nss).is_some()).count() as u8
+    }
+}
+
+/// The HT Capabilities element (ID 45).
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct HTCapabilities {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    #[debug(format = "0x{:04X}")]
+    pub info: u16,
+    #[debug(format = "0x{:02X}")]
+    pub ampdu_params: u8,
+    /// One bit per supported MCS, 0 to 76.
+    pub rx_mcs: Blob,
+    /// Highest supported data rate in Mb/s, or 0 if not advertised.
+    #[debug(format = "{}")]
+    pub rx_highest_rate: u16,
+    #[debug(format = "0x{:02X}")]
+    pub tx_mcs_params: u8,
+    #[debug(format = "0x{:04X}")]
+    pub extended: u16,
+    #[debug(format = "0x{:08X}")]
+    pub tx_beamforming: u32,
+    #[debug(format = "0x{:02X}")]
+    pub antenna_selection: u8,
+}
+
+impl HTCapabilities {
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Management Frame HT Capabilities", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (i, body) = take(len)(i)?;
+            let (body, (info, ampdu_params, rx_mcs)) = tuple((le_u16, le_u8, blob(10)))(body)?;
+            let (body, (rx_highest_rate, tx_mcs_params, _)) =
+                tuple((le_u16, le_u8, take(3_usize)))(body)?;
+            let (_, (extended, tx_beamforming, antenna_selection)) =
+                tuple((le_u16, le_u32, le_u8))(body)?;
+            let res = Self {
+                common,
+                info,
+                ampdu_params,
+                rx_mcs,
+                rx_highest_rate: rx_highest_rate & 0x03FF,
+                tx_mcs_params,
+                extended,
+                tx_beamforming,
+                antenna_selection,
+            };
+
+            Ok((i, res))
+        })(i)
+    }
+
+    pub fn ldpc(&self) -> bool {
+        self.info & 0x0001 != 0
+    }
+
+    /// Both 20 and 40 MHz channels are supported, rather than only 20 MHz.
+    pub fn supports_40mhz(&self) -> bool {
+        self.info & 0x0002 != 0
+    }
+
+    pub fn greenfield(&self) -> bool {
+        self.info & 0x0010 != 0
+    }
+
+    /// Short (400ns) guard interval on 20 MHz channels.
+    pub fn short_gi_20(&self) -> bool {
+        self.info & 0x0020 != 0
+    }
+
+    /// Short (400ns) guard interval on 40 MHz channels.
+    pub fn short_gi_40(&self) -> bool {
+        self.info & 0x0040 != 0
+    }
+
+    pub fn tx_stbc(&self) -> bool {
+        self.info & 0x0080 != 0
+    }
+
+    /// Number of spatial streams STBC can be received on.
+    pub fn rx_stbc(&self) -> u8 {
+        (self.info >> 8 & 0b11) as u8
+    }
+
+    pub fn forty_mhz_intolerant(&self) -> bool {
+        self.info & 0x4000 != 0
+    }
+
+    /// Maximum A-MPDU length in bytes.
+    pub fn max_ampdu_len(&self) -> u32 {
+        (1 << (13 + (self.ampdu_params & 0b11))) - 1
+    }
+
+    pub fn supports_mcs(&self, mcs: u8) -> bool {
+        mcs < 77 && self.rx_mcs.0[mcs as usize / 8] & (1 << (mcs % 8)) != 0
+    }
+
+    /// Number of spatial streams, as the number of equal modulation MCS groups of 8 that are supported.
+    pub fn spatial_streams(&self) -> u8 {
+        self.rx_mcs.0[..4].iter().filter(|&&b| b != 0).count() as u8
+    }
+}
+
+/// The HT Operation element (ID 61).
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct HTOperation {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    #[debug(format = "{}")]
+    pub primary_channel: u8,
+    pub info: Blob,
+    pub basic_mcs: Blob,
+}
+
+impl HTOperation {
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Management Frame HT Operation", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (i, body) = take(len)(i)?;
+            let (_, (primary_channel, info, basic_mcs)) = tuple((le_u8, blob(5), blob(16)))(body)?;
+            let res = Self {
+                common,
+                primary_channel,
+                info,
+                basic_mcs,
+            };
+
+            Ok((i, res))
+        })(i)
+    }
+
+    /// Position of the secondary 20 MHz channel: 1 above the primary, -1 below, 0 if there is none.
+    pub fn secondary_channel_offset(&self) -> i8 {
+        match self.info.0[0] & 0b11 {
+            1 => 1,
+            3 => -1,
+            _ => 0,
+        }
+    }
+
+    /// Width of the channel used by the BSS, in MHz.
+    pub fn channel_width(&self) -> u16 {
+        if self.info.0[0] & 0b100 != 0 && self.secondary_channel_offset() != 0 {
+            40
+        } else {
+            20
+        }
+    }
+
+    /// 0: no protection, 1: non-member, 2: 20 MHz, 3: non-HT mixed.
+    pub fn protection(&self) -> u8 {
+        self.info.0[1] & 0b11
+    }
+}
+
+/// The VHT Capabilities element (ID 191).
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct VHTCapabilities {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    #[debug(format = "0x{:08X}")]
+    pub info: u32,
+    pub rx_mcs: MCSMap,
+    #[debug(format = "{}")]
+    pub rx_highest_rate: u16,
+    pub tx_mcs: MCSMap,
+    #[debug(format = "{}")]
+    pub tx_highest_rate: u16,
+}
+
+impl VHTCapabilities {
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Management Frame VHT Capabilities", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (i, body) = take(len)(i)?;
+            let (_, (info, rx_mcs, rx_highest_rate, tx_mcs, tx_highest_rate)) =
+                tuple((le_u32, le_u16, le_u16, le_u16, le_u16))(body)?;
+            let res = Self {
+                common,
+                info,
+                rx_mcs: MCSMap(rx_mcs),
+                rx_highest_rate: rx_highest_rate & 0x1FFF,
+                tx_mcs: MCSMap(tx_mcs),
+                tx_highest_rate: tx_highest_rate & 0x1FFF,
+            };
+
+            Ok((i, res))
+        })(i)
+    }
+
+    /// Maximum MPDU length: 3895, 7991 or 11454 bytes.
+    pub fn max_mpdu_len(&self) -> u16 {
+        match self.info & 0b11 {
+            0 => 3895,
+            1 => 7991,
+            _ => 11454,
+        }
+    }
+
+    /// The widest channel supported, in MHz. 80+80 MHz is reported as 160.
+    pub fn max_channel_width(&self) -> u16 {
+        match self.info >> 2 & 0b11 {
+            0 => 80,
+            _ => 160,
+        }
+    }
+
+    pub fn supports_80_plus_80(&self) -> bool {
+        self.info >> 2 & 0b11 == 2
+    }
+
+    pub fn rx_ldpc(&self) -> bool {
+        self.info & 0x0010 != 0
+    }
+
+    /// Short (400ns) guard interval on 80 MHz channels.
+    pub fn short_gi_80(&self) -> bool {
+        self.info & 0x0020 != 0
+    }
+
+    /// Short (400ns) guard interval on 160 and 80+80 MHz channels.
+    pub fn short_gi_160(&self) -> bool {
+        self.info & 0x0040 != 0
+    }
+
+    pub fn su_beamformer(&self) -> bool {
+        self.info & 0x0800 != 0
+    }
+
+    pub fn su_beamformee(&self) -> bool {
+        self.info & 0x1000 != 0
+    }
+
+    pub fn mu_beamformer(&self) -> bool {
+        self.info & 0x0008_0000 != 0
+    }
+
+    pub fn mu_beamformee(&self) -> bool {
+        self.info & 0x0010_0000 != 0
+    }
+
+    pub fn spatial_streams(&self) -> u8 {
+        self.rx_mcs.spatial_streams()
+    }
+
+    /// Highest MCS index supported on a single spatial stream: 7, 8 or 9.
+    pub fn max_mcs(&self) -> Option<u8> {
+        self.rx_mcs.get(1).map(|v| 7 + v)
+    }
+}
+
+/// Works out the width in MHz of a VHT or HE channel from its two center frequency segments.
+fn segments_width(base: u16, seg0: u8, seg1: u8) -> u16 {
+    match (seg0 as i16 - seg1 as i16).abs() {
+        _ if seg1 == 0 => base,
+        8 => 160,
+        d if d > 16 => 160,
+        _ => base,
+    }
+}
+
+/// The VHT Operation element (ID 192).
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct VHTOperation {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    /// 0 for 20 or 40 MHz, as given by HT Operation, 1 for 80, 160 or 80+80 MHz.
+    /// The deprecated values 2 (160 MHz) and 3 (80+80 MHz) may still be seen.
+    #[debug(format = "{}")]
+    pub channel_width: u8,
+    #[debug(format = "{}")]
+    pub center_seg0: u8,
+    #[debug(format = "{}")]
+    pub center_seg1: u8,
+    pub basic_mcs: MCSMap,
+}
+
+impl VHTOperation {
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Management Frame VHT Operation", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (i, body) = take(len)(i)?;
+            let (_, (channel_width, center_seg0, center_seg1, basic_mcs)) =
+                tuple((le_u8, le_u8, le_u8, map(le_u16, MCSMap)))(body)?;
+            let res = Self {
+                common,
+                channel_width,
+                center_seg0,
+                center_seg1,
+                basic_mcs,
+            };
+
+            Ok((i, res))
+        })(i)
+    }
+
+    /// Width of the channel in MHz, or None when it is 20 or 40 MHz and given by HT Operation.
+    pub fn width_mhz(&self) -> Option<u16> {
+        match self.channel_width {
+            0 => None,
+            1 => Some(segments_width(80, self.center_seg0, self.center_seg1)),
+            _ => Some(160),
+        }
+    }
+}
+
+/// The HE Capabilities element (extension ID 35).
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct HECapabilities {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    pub mac_capabilities: Blob,
+    pub phy_capabilities: Blob,
+    pub rx_mcs_80: MCSMap,
+    pub tx_mcs_80: MCSMap,
+    pub rx_mcs_160: Option<MCSMap>,
+    pub tx_mcs_160: Option<MCSMap>,
+    pub rx_mcs_80_plus_80: Option<MCSMap>,
+    pub tx_mcs_80_plus_80: Option<MCSMap>,
+    /// PPE thresholds, when present.
+    pub ppe_thresholds: Blob,
+}
+
+impl HECapabilities {
+    /// Parses the element body following the extension ID.
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Management Frame HE Capabilities", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (body, (mac_capabilities, phy_capabilities)) = tuple((blob(6), blob(11)))(i)?;
+            let (body, (rx_mcs_80, tx_mcs_80)) =
+                tuple((map(le_u16, MCSMap), map(le_u16, MCSMap)))(body)?;
+            let (body, mcs_160) = mcs_maps(phy_capabilities.0[0] & 0x08 != 0, body)?;
+            let (body, mcs_80_plus_80) = mcs_maps(phy_capabilities.0[0] & 0x10 != 0, body)?;
+            let res = Self {
+                common,
+                mac_capabilities,
+                phy_capabilities,
+                rx_mcs_80,
+                tx_mcs_80,
+                rx_mcs_160: mcs_160.map(|m| m.0),
+                tx_mcs_160: mcs_160.map(|m| m.1),
+                rx_mcs_80_plus_80: mcs_80_plus_80.map(|m| m.0),
+                tx_mcs_80_plus_80: mcs_80_plus_80.map(|m| m.1),
+                ppe_thresholds: Blob::new(body),
+            };
+
+            Ok((&i[i.len()..], res))
+        })(i)
+    }
+
+    /// 40 MHz channels in the 2.4 GHz band.
+    pub fn supports_40mhz_2ghz(&self) -> bool {
+        self.phy_capabilities.0[0] & 0x02 != 0
+    }
+
+    /// 40 and 80 MHz channels in the 5 and 6 GHz bands.
+    pub fn supports_80mhz(&self) -> bool {
+        self.phy_capabilities.0[0] & 0x04 != 0
+    }
+
+    pub fn supports_160mhz(&self) -> bool {
+        self.phy_capabilities.0[0] & 0x08 != 0
+    }
+
+    pub fn supports_80_plus_80(&self) -> bool {
+        self.phy_capabilities.0[0] & 0x10 != 0
+    }
+
+    pub fn ldpc(&self) -> bool {
+        self.phy_capabilities.0[1] & 0x20 != 0
+    }
+
+    pub fn su_beamformer(&self) -> bool {
+        self.phy_capabilities.0[3] & 0x80 != 0
+    }
+
+    pub fn mu_beamformer(&self) -> bool {
+        self.phy_capabilities.0[4] & 0x02 != 0
+    }
+
+    pub fn spatial_streams(&self) -> u8 {
+        self.rx_mcs_80.spatial_streams()
+    }
+
+    /// Highest MCS index supported on a single spatial stream: 7, 9 or 11.
+    pub fn max_mcs(&self) -> Option<u8> {
+        self.rx_mcs_80.get(1).map(|v| 7 + 2 * v)
+    }
+}
+
+/// The 6 GHz Operation Information of an HE Operation element.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct SixGHzOperation {
+    #[debug(format = "{}")]
+    pub primary_channel: u8,
+    #[debug(format = "0x{:02X}")]
+    pub control: u8,
+    #[debug(format = "{}")]
+    pub center_seg0: u8,
+    #[debug(format = "{}")]
+    pub center_seg1: u8,
+    #[debug(format = "{}")]
+    pub min_rate: u8,
+}
+
+impl SixGHzOperation {
+    pub fn width_mhz(&self) -> u16 {
+        match self.control & 0b11 {
+            0 => 20,
+            1 => 40,
+            2 => 80,
+            _ => segments_width(80, self.center_seg0, self.center_seg1),
+        }
+    }
+}
+
+/// The HE Operation element (extension ID 36).
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct HEOperation {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    #[debug(format = "0x{:06X}")]
+    pub params: u32,
+    #[debug(format = "0x{:02X}")]
+    pub bss_color_info: u8,
+    pub basic_mcs: MCSMap,
+    /// Channel width, center segment 0 and center segment 1, laid out as in VHT Operation.
+    #[debug(format = "{:?}")]
+    pub vht_operation: Option<(u8, u8, u8)>,
+    pub max_cohosted_bssid_indicator: Option<u8>,
+    pub six_ghz_operation: Option<SixGHzOperation>,
+}
+
+impl HEOperation {
+    /// Parses the element body following the extension ID.
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Management Frame HE Operation", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (body, (params, bss_color_info, basic_mcs)) =
+                tuple((le_u24, le_u8, map(le_u16, MCSMap)))(i)?;
+            let (body, vht_operation) = if params & 0x4000 != 0 {
+                map(tuple((le_u8, le_u8, le_u8)), Some)(body)?
+            } else {
+                (body, None)
+            };
+            let (body, max_cohosted_bssid_indicator) = if params & 0x8000 != 0 {
+                map(le_u8, Some)(body)?
+            } else {
+                (body, None)
+            };
+            let (_, six_ghz_operation) = if params & 0x02_0000 != 0 {
+                opt(map(
+                    tuple((le_u8, le_u8, le_u8, le_u8, le_u8)),
+                    |(primary_channel, control, center_seg0, center_seg1, min_rate)| {
+                        SixGHzOperation {
+                            primary_channel,
+                            control,
+                            center_seg0,
+                            center_seg1,
+                            min_rate,
+                        }
+                    },
+                ))(body)?
+            } else {
+                (body, None)
+            };
+            let res = Self {
+                common,
+                params,
+                bss_color_info,
+                basic_mcs,
+                vht_operation,
+                max_cohosted_bssid_indicator,
+                six_ghz_operation,
+            };
+
+            Ok((&i[i.len()..], res))
+        })(i)
+    }
+
+    /// The BSS color, which tells overlapping BSSs apart.
+    pub fn bss_color(&self) -> u8 {
+        self.bss_color_info & 0x3F
+    }
+
+    pub fn bss_color_disabled(&self) -> bool {
+        self.bss_color_info & 0x80 != 0
+    }
+
+    pub fn twt_required(&self) -> bool {
+        self.params & 0x0008 != 0
+    }
+
+    pub fn width_mhz(&self) -> Option<u16> {
+        if let Some(ref six_ghz) = self.six_ghz_operation {
+            return Some(six_ghz.width_mhz());
+        }
+        match self.vht_operation {
+            Some((1, seg0, seg1)) => Some(segments_width(80, seg0, seg1)),
+            Some((2, _, _)) | Some((3, _, _)) => Some(160),
+            _ => None,
+        }
+    }
+}
+
+/// The EHT (802.11be) Capabilities element (extension ID 108).
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct EHTCapabilities {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    #[debug(format = "0x{:04X}")]
+    pub mac_capabilities: u16,
+    pub phy_capabilities: Blob,
+    /// The supported EHT-MCS and NSS sets, followed by PPE thresholds when present.
+    pub mcs_nss: Blob,
+}
+
+impl EHTCapabilities {
+    /// Parses the element body following the extension ID.
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Management Frame EHT Capabilities", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (body, (mac_capabilities, phy_capabilities)) = tuple((le_u16, blob(9)))(i)?;
+            let res = Self {
+                common,
+                mac_capabilities,
+                phy_capabilities,
+                mcs_nss: Blob::new(body),
+            };
+
+            Ok((&i[i.len()..], res))
+        })(i)
+    }
+
+    /// 320 MHz channels in the 6 GHz band.
+    pub fn supports_320mhz(&self) -> bool {
+        self.phy_capabilities.0[0] & 0x02 != 0
+    }
+}
+
+/// The EHT Operation element (extension ID 106).
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct EHTOperation {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    #[debug(format = "0x{:02X}")]
+    pub params: u8,
+    #[debug(format = "0x{:08X}")]
+    pub basic_mcs: u32,
+    /// Channel width, center frequency segment 0 and center frequency segment 1.
+    #[debug(format = "{:?}")]
+    pub info: Option<(u8, u8, u8)>,
+    pub disabled_subchannels: Option<u16>,
+}
+
+impl EHTOperation {
+    /// Parses the element body following the extension ID.
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Management Frame EHT Operation", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (body, (params, basic_mcs)) = tuple((le_u8, le_u32))(i)?;
+            let (body, info) = if params & 0x01 != 0 {
+                map(tuple((le_u8, le_u8, le_u8)), Some)(body)?
+            } else {
+                (body, None)
+            };
+            let (_, disabled_subchannels) = if info.is_some() && params & 0x02 != 0 {
+                map(le_u16, Some)(body)?
+            } else {
+                (body, None)
+            };
+            let res = Self {
+                common,
+                params,
+                basic_mcs,
+                info,
+                disabled_subchannels,
+            };
+
+            Ok((&i[i.len()..], res))
+        })(i)
+    }
+
+    pub fn width_mhz(&self) -> Option<u16> {
+        self.info.map(|(width, _, _)| match width & 0b111 {
+            0 => 20,
+            1 => 40,
+            2 => 80,
+            3 => 160,
+            _ => 320,
+        })
+    }
+}
+
+/// The width of the channel a BSS operates on, in MHz, from the operation elements it advertises.
+pub fn channel_width(elements: &[Element]) -> u16 {
+    let mut width = 20;
+    for element in elements {
+        let w = match element {
+            Element::HTOperation(ht) => Some(ht.channel_width()),
+            Element::VHTOperation(vht) => vht.width_mhz(),
+            Element::Extension(Extension::HEOperation(he)) => he.width_mhz(),
+            Element::Extension(Extension::EHTOperation(eht)) => eht.width_mhz(),
+            _ => None,
+        };
+        width = width.max(w.unwrap_or(20));
+    }
+    width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_ht_vht_he() {
+        let elements: &[u8] = &[
+            // HT Capabilities: 40 MHz, SGI 20/40, two spatial streams
+            45, 26, 0x62, 0x00, 0x17, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // HT Operation: channel 36, secondary above
+            61, 22, 36, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            // VHT Operation: 80 MHz centered on channel 42
+            192, 5, 1, 42, 0, 0xFC, 0xFF, // HE Operation: BSS color 12
+            255, 7, 36, 0x00, 0x00, 0x00, 0x0C, 0xFC, 0xFF,
+        ];
+        let (_, elements) = nom::multi::many0(Element::parse)(elements).unwrap();
+        assert_eq!(elements.len(), 4);
+        match elements[0] {
+            Element::HTCapabilities(ref ht) => {
+                assert!(ht.supports_40mhz() && ht.short_gi_20() && ht.short_gi_40());
+                assert_eq!(ht.spatial_streams(), 2);
+                assert!(ht.supports_mcs(15) && !ht.supports_mcs(16));
+            }
+            ref e => panic!("expected HT Capabilities, got {:?}", e),
+        }
+        match elements[3] {
+            Element::Extension(Extension::HEOperation(ref he)) => {
+                assert_eq!(he.bss_color(), 12);
+                assert_eq!(he.basic_mcs.spatial_streams(), 1);
+            }
+            ref e => panic!("expected HE Operation, got {:?}", e),
+        }
+        assert_eq!(channel_width(&elements), 80);
+    }
+}
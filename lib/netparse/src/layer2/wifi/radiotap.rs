@@ -1,13 +1,562 @@
-use crate::core::parse;
+use crate::core::{blob::Blob, parse};
 
 use custom_debug_derive::*;
 use nom::{
     bytes::complete::take,
+    combinator::map,
     error::context,
-    number::complete::{le_u16, le_u32, le_u8},
+    number::complete::{le_i8, le_u16, le_u32, le_u64, le_u8},
+    sequence::tuple,
 };
 use serde::{Deserialize, Serialize};
 
+/// Bit 29 of a presence word: the next word belongs to the radiotap namespace.
+static RADIOTAP_NAMESPACE: u32 = 29;
+/// Bit 30 of a presence word: a vendor namespace header follows, and the next word belongs to it.
+static VENDOR_NAMESPACE: u32 = 30;
+/// Bit 31 of a presence word: another presence word follows.
+static EXT: u32 = 31;
+
+#[derive(CustomDebug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Flags(#[debug(format = "0x{:02X}")] pub u8);
+
+impl Flags {
+    pub fn cfp(&self) -> bool {
+        self.0 & 0x01 != 0
+    }
+
+    pub fn short_preamble(&self) -> bool {
+        self.0 & 0x02 != 0
+    }
+
+    pub fn wep(&self) -> bool {
+        self.0 & 0x04 != 0
+    }
+
+    pub fn fragmentation(&self) -> bool {
+        self.0 & 0x08 != 0
+    }
+
+    /// The frame includes its 4 byte FCS.
+    pub fn fcs_at_end(&self) -> bool {
+        self.0 & 0x10 != 0
+    }
+
+    pub fn data_pad(&self) -> bool {
+        self.0 & 0x20 != 0
+    }
+
+    /// The frame failed its FCS check.
+    pub fn bad_fcs(&self) -> bool {
+        self.0 & 0x40 != 0
+    }
+
+    pub fn short_gi(&self) -> bool {
+        self.0 & 0x80 != 0
+    }
+}
+
+#[derive(CustomDebug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Channel {
+    /// Center frequency in MHz.
+    #[debug(format = "{}")]
+    pub freq: u16,
+    #[debug(format = "0x{:04X}")]
+    pub flags: u16,
+}
+
+impl Channel {
+    fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        map(tuple((le_u16, le_u16)), |(freq, flags)| Self {
+            freq,
+            flags,
+        })(i)
+    }
+
+    /// The IEEE channel number of the center frequency, in the 2.4, 5 or 6 GHz band.
+    pub fn number(&self) -> Option<u16> {
+        freq_to_channel(self.freq)
+    }
+}
+
+pub fn freq_to_channel(freq: u16) -> Option<u16> {
+    match freq {
+        2484 => Some(14),
+        2412..=2472 => Some((freq - 2407) / 5),
+        5955..=7115 => Some((freq - 5950) / 5),
+        5000..=5950 => Some((freq - 5000) / 5),
+        _ => None,
+    }
+}
+
+#[derive(CustomDebug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct XChannel {
+    #[debug(format = "0x{:08X}")]
+    pub flags: u32,
+    #[debug(format = "{}")]
+    pub freq: u16,
+    #[debug(format = "{}")]
+    pub channel: u8,
+    #[debug(format = "{}")]
+    pub max_power: u8,
+}
+
+impl XChannel {
+    fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        map(
+            tuple((le_u32, le_u16, le_u8, le_u8)),
+            |(flags, freq, channel, max_power)| Self {
+                flags,
+                freq,
+                channel,
+                max_power,
+            },
+        )(i)
+    }
+}
+
+/// 802.11n (HT) rate information.
+#[derive(CustomDebug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MCS {
+    #[debug(format = "0x{:02X}")]
+    pub known: u8,
+    #[debug(format = "0x{:02X}")]
+    pub flags: u8,
+    #[debug(format = "{}")]
+    pub index: u8,
+}
+
+impl MCS {
+    fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        map(tuple((le_u8, le_u8, le_u8)), |(known, flags, index)| Self {
+            known,
+            flags,
+            index,
+        })(i)
+    }
+
+    pub fn bandwidth(&self) -> u16 {
+        match self.flags & 0x03 {
+            1 => 40,
+            _ => 20,
+        }
+    }
+
+    pub fn short_gi(&self) -> bool {
+        self.flags & 0x04 != 0
+    }
+
+    pub fn rate_mbps(&self) -> Option<f64> {
+        let nss = self.index / 8 + 1;
+        ht_vht_rate(self.bandwidth(), self.index % 8, nss, self.short_gi())
+    }
+}
+
+#[derive(CustomDebug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AMPDUStatus {
+    #[debug(format = "{}")]
+    pub reference: u32,
+    #[debug(format = "0x{:04X}")]
+    pub flags: u16,
+    #[debug(format = "0x{:02X}")]
+    pub delimiter_crc: u8,
+    #[debug(skip)]
+    pub reserved: u8,
+}
+
+impl AMPDUStatus {
+    fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        map(
+            tuple((le_u32, le_u16, le_u8, le_u8)),
+            |(reference, flags, delimiter_crc, reserved)| Self {
+                reference,
+                flags,
+                delimiter_crc,
+                reserved,
+            },
+        )(i)
+    }
+}
+
+/// 802.11ac (VHT) rate information.
+#[derive(CustomDebug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VHT {
+    #[debug(format = "0x{:04X}")]
+    pub known: u16,
+    #[debug(format = "0x{:02X}")]
+    pub flags: u8,
+    #[debug(format = "{}")]
+    pub bandwidth: u8,
+    /// Per user: MCS in the high nibble, number of spatial streams in the low nibble.
+    #[debug(format = "{:02X?}")]
+    pub mcs_nss: [u8; 4],
+    #[debug(format = "0x{:02X}")]
+    pub coding: u8,
+    #[debug(format = "{}")]
+    pub group_id: u8,
+    #[debug(format = "{}")]
+    pub partial_aid: u16,
+}
+
+impl VHT {
+    fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        let (i, (known, flags, bandwidth)) = tuple((le_u16, le_u8, le_u8))(i)?;
+        let (i, users) = take(4_usize)(i)?;
+        let (i, (coding, group_id, partial_aid)) = tuple((le_u8, le_u8, le_u16))(i)?;
+        let mut mcs_nss = [0u8; 4];
+        mcs_nss.copy_from_slice(users);
+
+        let res = Self {
+            known,
+            flags,
+            bandwidth,
+            mcs_nss,
+            coding,
+            group_id,
+            partial_aid,
+        };
+        Ok((i, res))
+    }
+
+    pub fn bandwidth_mhz(&self) -> u16 {
+        match self.bandwidth {
+            0 => 20,
+            1..=3 => 40,
+            4..=10 => 80,
+            _ => 160,
+        }
+    }
+
+    pub fn short_gi(&self) -> bool {
+        self.flags & 0x04 != 0
+    }
+
+    /// The rate of the first user, which is the only one for single user transmissions.
+    pub fn rate_mbps(&self) -> Option<f64> {
+        let mcs = self.mcs_nss[0] >> 4;
+        let nss = self.mcs_nss[0] & 0x0F;
+        ht_vht_rate(self.bandwidth_mhz(), mcs, nss, self.short_gi())
+    }
+}
+
+#[derive(CustomDebug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Timestamp {
+    #[debug(format = "{}")]
+    pub timestamp: u64,
+    #[debug(format = "{}")]
+    pub accuracy: u16,
+    #[debug(format = "0x{:02X}")]
+    pub unit_position: u8,
+    #[debug(format = "0x{:02X}")]
+    pub flags: u8,
+}
+
+impl Timestamp {
+    fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        map(
+            tuple((le_u64, le_u16, le_u8, le_u8)),
+            |(timestamp, accuracy, unit_position, flags)| Self {
+                timestamp,
+                accuracy,
+                unit_position,
+                flags,
+            },
+        )(i)
+    }
+}
+
+/// 802.11ax (HE) information, as six little endian data words.
+#[derive(CustomDebug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HE {
+    #[debug(format = "{:04X?}")]
+    pub data: [u16; 6],
+}
+
+impl HE {
+    fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        map(
+            tuple((le_u16, le_u16, le_u16, le_u16, le_u16, le_u16)),
+            |(a, b, c, d, e, f)| Self {
+                data: [a, b, c, d, e, f],
+            },
+        )(i)
+    }
+
+    pub fn mcs(&self) -> u8 {
+        ((self.data[2] >> 8) & 0x0F) as u8
+    }
+
+    /// The bandwidth of a PPDU that spans the whole channel, or `None` for one on a resource unit.
+    pub fn bandwidth_mhz(&self) -> Option<u16> {
+        match self.data[4] & 0x0F {
+            0 => Some(20),
+            1 => Some(40),
+            2 => Some(80),
+            3 => Some(160),
+            _ => None,
+        }
+    }
+
+    /// The size in tones of the resource unit an OFDMA PPDU was sent on.
+    pub fn ru_tones(&self) -> Option<u16> {
+        match self.data[4] & 0x0F {
+            4 => Some(26),
+            5 => Some(52),
+            6 => Some(106),
+            7 => Some(242),
+            8 => Some(484),
+            9 => Some(996),
+            10 => Some(2 * 996),
+            _ => None,
+        }
+    }
+
+    /// The data subcarriers of the bandwidth or resource unit.
+    fn data_subcarriers(&self) -> Option<f64> {
+        let res = match self.data[4] & 0x0F {
+            4 => 24.0,
+            5 => 48.0,
+            6 => 102.0,
+            0 | 7 => 234.0,
+            1 | 8 => 468.0,
+            2 | 9 => 980.0,
+            3 | 10 => 1960.0,
+            _ => return None,
+        };
+        Some(res)
+    }
+
+    /// Guard interval in nanoseconds.
+    pub fn gi_ns(&self) -> u16 {
+        match (self.data[4] >> 4) & 0x03 {
+            0 => 800,
+            1 => 1600,
+            _ => 3200,
+        }
+    }
+
+    pub fn nss(&self) -> u8 {
+        (self.data[5] & 0x0F) as u8
+    }
+
+    pub fn rate_mbps(&self) -> Option<f64> {
+        let subcarriers = self.data_subcarriers()?;
+        let (bits, coding) = modulation(self.mcs())?;
+        let symbol_us = 12.8 + self.gi_ns() as f64 / 1000.0;
+        Some(self.nss().max(1) as f64 * subcarriers * bits * coding / symbol_us)
+    }
+}
+
+#[derive(CustomDebug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HEMU {
+    #[debug(format = "0x{:04X}")]
+    pub flags1: u16,
+    #[debug(format = "0x{:04X}")]
+    pub flags2: u16,
+    #[debug(format = "{:02X?}")]
+    pub ru_channel1: [u8; 4],
+    #[debug(format = "{:02X?}")]
+    pub ru_channel2: [u8; 4],
+}
+
+impl HEMU {
+    fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        let (i, (flags1, flags2)) = tuple((le_u16, le_u16))(i)?;
+        let (i, (ru1, ru2)) = tuple((take(4_usize), take(4_usize)))(i)?;
+        let mut res = Self {
+            flags1,
+            flags2,
+            ru_channel1: [0; 4],
+            ru_channel2: [0; 4],
+        };
+        res.ru_channel1.copy_from_slice(ru1);
+        res.ru_channel2.copy_from_slice(ru2);
+        Ok((i, res))
+    }
+}
+
+#[derive(CustomDebug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HEMUOtherUser {
+    #[debug(format = "0x{:04X}")]
+    pub per_user_1: u16,
+    #[debug(format = "0x{:04X}")]
+    pub per_user_2: u16,
+    #[debug(format = "{}")]
+    pub per_user_position: u8,
+    #[debug(format = "0x{:02X}")]
+    pub per_user_known: u8,
+}
+
+impl HEMUOtherUser {
+    fn parse(i: parse::Input) -> parse::ParseResult<Self> {
+        map(
+            tuple((le_u16, le_u16, le_u8, le_u8)),
+            |(per_user_1, per_user_2, per_user_position, per_user_known)| Self {
+                per_user_1,
+                per_user_2,
+                per_user_position,
+                per_user_known,
+            },
+        )(i)
+    }
+}
+
+/// Fields defined by a vendor, identified by OUI and sub namespace, kept undecoded.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct VendorNamespace {
+    #[debug(format = "{:02X?}")]
+    pub oui: [u8; 3],
+    #[debug(format = "{}")]
+    pub sub_namespace: u8,
+    pub data: Blob,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum Field {
+    /// Value of the MAC's 64-bit timer in microseconds when the first bit of the frame arrived.
+    TSFT(u64),
+    Flags(Flags),
+    /// Legacy TX/RX rate in units of 500 Kbps.
+    Rate(u8),
+    Channel(Channel),
+    FHSS {
+        hop_set: u8,
+        hop_pattern: u8,
+    },
+    AntennaSignal(i8),
+    AntennaNoise(i8),
+    LockQuality(u16),
+    TxAttenuation(u16),
+    DbTxAttenuation(u16),
+    DbmTxPower(i8),
+    Antenna(u8),
+    DbAntennaSignal(u8),
+    DbAntennaNoise(u8),
+    RxFlags(u16),
+    TxFlags(u16),
+    RtsRetries(u8),
+    DataRetries(u8),
+    XChannel(XChannel),
+    MCS(MCS),
+    AMPDUStatus(AMPDUStatus),
+    VHT(VHT),
+    Timestamp(Timestamp),
+    HE(HE),
+    HEMU(HEMU),
+    HEMUOtherUser(HEMUOtherUser),
+    ZeroLengthPSDU(u8),
+    LSIG {
+        data1: u16,
+        data2: u16,
+    },
+    Vendor(VendorNamespace),
+}
+
+/// The alignment and size of the radiotap namespace field with the given presence bit.
+fn layout(bit: u32) -> Option<(usize, usize)> {
+    let res = match bit {
+        0 => (8, 8),
+        1 | 2 => (1, 1),
+        3 => (2, 4),
+        4 => (2, 2),
+        5 | 6 => (1, 1),
+        7..=9 => (2, 2),
+        10..=13 => (1, 1),
+        14 | 15 => (2, 2),
+        16 | 17 => (1, 1),
+        18 => (4, 8),
+        19 => (1, 3),
+        20 => (4, 8),
+        21 => (2, 12),
+        22 => (8, 12),
+        23 => (2, 12),
+        24 => (2, 12),
+        25 => (2, 6),
+        26 => (1, 1),
+        27 => (2, 4),
+        _ => return None,
+    };
+    Some(res)
+}
+
+fn parse_field(i: parse::Input, bit: u32) -> parse::ParseResult<Field> {
+    match bit {
+        0 => map(le_u64, Field::TSFT)(i),
+        1 => map(le_u8, |f| Field::Flags(Flags(f)))(i),
+        2 => map(le_u8, Field::Rate)(i),
+        3 => map(Channel::parse, Field::Channel)(i),
+        4 => map(tuple((le_u8, le_u8)), |(hop_set, hop_pattern)| {
+            Field::FHSS {
+                hop_set,
+                hop_pattern,
+            }
+        })(i),
+        5 => map(le_i8, Field::AntennaSignal)(i),
+        6 => map(le_i8, Field::AntennaNoise)(i),
+        7 => map(le_u16, Field::LockQuality)(i),
+        8 => map(le_u16, Field::TxAttenuation)(i),
+        9 => map(le_u16, Field::DbTxAttenuation)(i),
+        10 => map(le_i8, Field::DbmTxPower)(i),
+        11 => map(le_u8, Field::Antenna)(i),
+        12 => map(le_u8, Field::DbAntennaSignal)(i),
+        13 => map(le_u8, Field::DbAntennaNoise)(i),
+        14 => map(le_u16, Field::RxFlags)(i),
+        15 => map(le_u16, Field::TxFlags)(i),
+        16 => map(le_u8, Field::RtsRetries)(i),
+        17 => map(le_u8, Field::DataRetries)(i),
+        18 => map(XChannel::parse, Field::XChannel)(i),
+        19 => map(MCS::parse, Field::MCS)(i),
+        20 => map(AMPDUStatus::parse, Field::AMPDUStatus)(i),
+        21 => map(VHT::parse, Field::VHT)(i),
+        22 => map(Timestamp::parse, Field::Timestamp)(i),
+        23 => map(HE::parse, Field::HE)(i),
+        24 => map(HEMU::parse, Field::HEMU)(i),
+        25 => map(HEMUOtherUser::parse, Field::HEMUOtherUser)(i),
+        26 => map(le_u8, Field::ZeroLengthPSDU)(i),
+        27 => map(tuple((le_u16, le_u16)), |(data1, data2)| Field::LSIG {
+            data1,
+            data2,
+        })(i),
+        _ => Err(nom::Err::Error(parse::Error::malformed(i))),
+    }
+}
+
+/// Bits per subcarrier and coding rate of an 802.11n/ac/ax MCS index.
+fn modulation(mcs: u8) -> Option<(f64, f64)> {
+    let res = match mcs {
+        0 => (1.0, 1.0 / 2.0),
+        1 => (2.0, 1.0 / 2.0),
+        2 => (2.0, 3.0 / 4.0),
+        3 => (4.0, 1.0 / 2.0),
+        4 => (4.0, 3.0 / 4.0),
+        5 => (6.0, 2.0 / 3.0),
+        6 => (6.0, 3.0 / 4.0),
+        7 => (6.0, 5.0 / 6.0),
+        8 => (8.0, 3.0 / 4.0),
+        9 => (8.0, 5.0 / 6.0),
+        10 => (10.0, 3.0 / 4.0),
+        11 => (10.0, 5.0 / 6.0),
+        _ => return None,
+    };
+    Some(res)
+}
+
+/// The PHY rate of an HT or VHT transmission, from its data subcarriers and symbol duration.
+fn ht_vht_rate(bandwidth: u16, mcs: u8, nss: u8, short_gi: bool) -> Option<f64> {
+    let subcarriers = match bandwidth {
+        20 => 52.0,
+        40 => 108.0,
+        80 => 234.0,
+        _ => 468.0,
+    };
+    let (bits, coding) = modulation(mcs)?;
+    let symbol_us = if short_gi { 3.6 } else { 4.0 };
+    if nss == 0 {
+        return None;
+    }
+    Some(nss as f64 * subcarriers * bits * coding / symbol_us)
+}
+
 #[derive(CustomDebug, Serialize, Deserialize)]
 pub struct RadioTapHeader {
     #[debug(format = "0x{:02X}")]
@@ -18,6 +567,10 @@ pub struct RadioTapHeader {
     pub it_len: u16,
     #[debug(format = "0x{:04X}")]
     pub it_present: u32,
+    /// The presence words following `it_present` when its extension bit is set.
+    #[debug(format = "{:08X?}")]
+    pub ext_present: Vec<u32>,
+    pub fields: Vec<Field>,
 }
 
 impl RadioTapHeader {
@@ -27,17 +580,230 @@ impl RadioTapHeader {
             let (i, it_version) = le_u8(i)?;
             let (i, it_pad) = le_u8(i)?;
             let (i, it_len) = le_u16(i)?;
-            let (_, it_present) = le_u32(i)?;
-            let (i, _) = take(it_len)(original_i)?;
+            let (mut i, it_present) = le_u32(i)?;
+
+            let mut ext_present = vec![];
+            let mut last = it_present;
+            while last & (1 << EXT) != 0 {
+                let (rest, word) = le_u32(i)?;
+                ext_present.push(word);
+                last = word;
+                i = rest;
+            }
+
+            let (remaining, header) = take(it_len)(original_i)?;
+            let fields_start = original_i.len() - i.len();
+            if fields_start > header.len() {
+                return Err(nom::Err::Error(parse::Error::malformed(original_i)));
+            }
+
+            let mut present = vec![it_present];
+            present.extend(ext_present.iter());
+            let fields = Self::parse_fields(header, fields_start, &present)?;
 
             let res = Self {
                 it_version,
                 it_pad,
                 it_len,
                 it_present,
+                ext_present,
+                fields,
             };
 
-            Ok((i, res))
+            Ok((remaining, res))
         })(i)
     }
+
+    /// Walks the presence words in order, aligning each field to its natural size relative to the start of the header.
+    /// Walking stops at the first bit whose layout is unknown, since everything after it would be misaligned.
+    fn parse_fields<'a>(
+        header: parse::Input<'a>,
+        mut offset: usize,
+        present: &[u32],
+    ) -> Result<Vec<Field>, nom::Err<parse::Error<parse::Input<'a>>>> {
+        let mut fields = vec![];
+        let mut vendor_namespace = false;
+        // The number of the first bit of a word in the radiotap namespace: a word continues the namespace
+        // of the previous one 32 bits further, unless that one switched namespaces with bit 29 or 30.
+        let mut base = 0;
+
+        for word in present {
+            // The fields of a vendor namespace were already skipped as part of its data.
+            if !vendor_namespace {
+                for bit in 0..RADIOTAP_NAMESPACE {
+                    if word & (1 << bit) == 0 {
+                        continue;
+                    }
+                    let (align, size) = match layout(base + bit) {
+                        Some(layout) => layout,
+                        None => return Ok(fields),
+                    };
+                    offset = offset.div_ceil(align) * align;
+                    if offset + size > header.len() {
+                        return Err(nom::Err::Error(parse::Error::malformed(header)));
+                    }
+                    let (_, field) = parse_field(&header[offset..offset + size], base + bit)?;
+                    fields.push(field);
+                    offset += size;
+                }
+            }
+
+            if word & (1 << VENDOR_NAMESPACE) != 0 {
+                let (field, next) = Self::parse_vendor(header, offset)?;
+                fields.push(field);
+                offset = next;
+                vendor_namespace = true;
+                base = 0;
+            } else if word & (1 << RADIOTAP_NAMESPACE) != 0 {
+                vendor_namespace = false;
+                base = 0;
+            } else {
+                base += 32;
+            }
+        }
+
+        Ok(fields)
+    }
+
+    /// Parses a vendor namespace header and its data, returning the offset of the next field.
+    fn parse_vendor<'a>(
+        header: parse::Input<'a>,
+        offset: usize,
+    ) -> Result<(Field, usize), nom::Err<parse::Error<parse::Input<'a>>>> {
+        let offset = offset.div_ceil(2) * 2;
+        if offset + 6 > header.len() {
+            return Err(nom::Err::Error(parse::Error::malformed(header)));
+        }
+        let (_, (oui, sub_namespace, skip_length)) =
+            tuple((take(3_usize), le_u8, le_u16))(&header[offset..])?;
+        let start = offset + 6;
+        let end = start + skip_length as usize;
+        if end > header.len() {
+            return Err(nom::Err::Error(parse::Error::malformed(header)));
+        }
+
+        let mut res = VendorNamespace {
+            oui: [0; 3],
+            sub_namespace,
+            data: Blob::new(&header[start..end]),
+        };
+        res.oui.copy_from_slice(oui);
+        Ok((Field::Vendor(res), end))
+    }
+
+    pub fn flags(&self) -> Option<Flags> {
+        self.fields.iter().find_map(|f| match f {
+            Field::Flags(flags) => Some(*flags),
+            _ => None,
+        })
+    }
+
+    pub fn channel(&self) -> Option<Channel> {
+        self.fields.iter().find_map(|f| match f {
+            Field::Channel(channel) => Some(*channel),
+            Field::XChannel(x) => Some(Channel {
+                freq: x.freq,
+                flags: x.flags as u16,
+            }),
+            _ => None,
+        })
+    }
+
+    /// The signal of the first antenna field, which is the combined signal when several antennas are reported.
+    pub fn signal_dbm(&self) -> Option<i8> {
+        self.fields.iter().find_map(|f| match f {
+            Field::AntennaSignal(signal) => Some(*signal),
+            _ => None,
+        })
+    }
+
+    pub fn noise_dbm(&self) -> Option<i8> {
+        self.fields.iter().find_map(|f| match f {
+            Field::AntennaNoise(noise) => Some(*noise),
+            _ => None,
+        })
+    }
+
+    /// The data rate from whichever of the legacy, HT, VHT or HE fields is present.
+    pub fn rate_mbps(&self) -> Option<f64> {
+        self.fields.iter().find_map(|f| match f {
+            Field::Rate(rate) => Some(*rate as f64 / 2.0),
+            Field::MCS(mcs) => mcs.rate_mbps(),
+            Field::VHT(vht) => vht.rate_mbps(),
+            Field::HE(he) => he.rate_mbps(),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Flags, rate, channel and signal, followed by an 802.11 ACK.
+    const TEST_HEADER: &[u8] = &[
+        0x00, 0x00, 0x12, 0x00, 0x2E, 0x48, 0x00, 0x00, 0x10, 0x6C, 0x85, 0x09, 0xA0, 0x00, 0xC4,
+        0x01, 0x00, 0x00, 0xD4, 0x00,
+    ];
+
+    /// TSFT and MCS over two presence words, with a vendor namespace in between.
+    const TEST_EXT_HEADER: &[u8] = &[
+        0x00, 0x00, 0x24, 0x00, 0x01, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0xA0, 0x00, 0x00, 0x08,
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x00, 0x10, 0x18, 0x01, 0x02, 0x00,
+        0xAA, 0xBB, 0x07, 0x00, 0x07, 0x00,
+    ];
+
+    #[test]
+    fn assert_common_fields() {
+        let (remaining, header) = RadioTapHeader::parse(TEST_HEADER).unwrap();
+
+        assert_eq!(remaining, &[0xD4, 0x00]);
+        assert!(header.flags().unwrap().fcs_at_end());
+        assert_eq!(header.rate_mbps(), Some(54.0));
+        assert_eq!(header.channel().unwrap().freq, 2437);
+        assert_eq!(header.channel().unwrap().number(), Some(6));
+        assert_eq!(header.signal_dbm(), Some(-60));
+    }
+
+    #[test]
+    fn assert_extended_bitmaps() {
+        let (remaining, header) = RadioTapHeader::parse(TEST_EXT_HEADER).unwrap();
+
+        assert!(remaining.is_empty());
+        assert_eq!(header.ext_present.len(), 2);
+        match header.fields[..] {
+            [Field::TSFT(0x0807060504030201), Field::Vendor(ref vendor), Field::MCS(ref mcs)] => {
+                assert_eq!(vendor.oui, [0x00, 0x10, 0x18]);
+                assert_eq!(vendor.data.0, &[0xAA, 0xBB]);
+                assert_eq!(mcs.index, 7);
+            }
+            _ => panic!("unexpected fields {:?}", header.fields),
+        }
+        assert!((header.rate_mbps().unwrap() - 65.0).abs() < 0.01);
+
+        // Flags, then a second word of the radiotap namespace whose bit 0 is bit 32
+        let header = [
+            0x00, 0x00, 0x0D, 0x00, 0x02, 0x00, 0x00, 0x80, 0x01, 0x00, 0x00, 0x00, 0x10,
+        ];
+        let (_, header) = RadioTapHeader::parse(&header).unwrap();
+        match header.fields[..] {
+            [Field::Flags(flags)] => assert!(flags.fcs_at_end()),
+            _ => panic!("unexpected fields {:?}", header.fields),
+        }
+    }
+
+    #[test]
+    fn assert_he_bandwidth() {
+        let he = |data5| HE {
+            data: [0, 0, 0, 0, data5, 1],
+        };
+        assert_eq!(he(3).bandwidth_mhz(), Some(160));
+        assert_eq!(he(3).ru_tones(), None);
+        assert_eq!(he(4).bandwidth_mhz(), None);
+        assert_eq!(he(4).ru_tones(), Some(26));
+        assert_eq!(he(10).ru_tones(), Some(1992));
+        assert_eq!(he(11).rate_mbps(), None);
+        // MCS 0 on a 26-tone RU: 24 data subcarriers of BPSK 1/2 every 13.6 µs
+        assert!((he(4).rate_mbps().unwrap() - 0.882).abs() < 0.001);
+    }
 }
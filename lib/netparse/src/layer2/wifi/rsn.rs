@@ -0,0 +1,358 @@
+use crate::{
+    core::{blob::Blob, parse::*, ux::u1},
+    layer2::wifi::{
+        management::{CapabilityInfo, CommonFieldsElement, Element},
+        vendor::{VendorContent, MICROSOFT_OUI},
+    },
+};
+
+use custom_debug_derive::*;
+use derive_try_from_primitive::*;
+use nom::{
+    bytes::complete::take,
+    combinator::map,
+    error::context,
+    multi::count,
+    number::complete::{le_u16, le_u8},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The OUI of suites defined by IEEE 802.11 itself.
+pub static IEEE_OUI: [u8; 3] = [0x00, 0x0F, 0xAC];
+
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Cipher {
+    UseGroup = 0,
+    WEP40 = 1,
+    TKIP = 2,
+    CCMP = 4,
+    WEP104 = 5,
+    BIPCMAC128 = 6,
+    GroupNotAllowed = 7,
+    GCMP128 = 8,
+    GCMP256 = 9,
+    CCMP256 = 10,
+    BIPGMAC128 = 11,
+    BIPGMAC256 = 12,
+    BIPCMAC256 = 13,
+}
+
+/// Authentication and key management suites.
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum AKM {
+    IEEE8021X = 1,
+    PSK = 2,
+    FT8021X = 3,
+    FTPSK = 4,
+    IEEE8021XSHA256 = 5,
+    PSKSHA256 = 6,
+    TDLS = 7,
+    SAE = 8,
+    FTSAE = 9,
+    APPeerKey = 10,
+    SuiteB = 11,
+    SuiteB192 = 12,
+    FT8021XSHA384 = 13,
+    FILSSHA256 = 14,
+    FILSSHA384 = 15,
+    FTFILSSHA256 = 16,
+    FTFILSSHA384 = 17,
+    OWE = 18,
+    FTPSKSHA384 = 19,
+    PSKSHA384 = 20,
+    SAEExt = 24,
+    FTSAEExt = 25,
+}
+
+impl AKM {
+    pub fn is_psk(self) -> bool {
+        use AKM::*;
+        matches!(self, PSK | FTPSK | PSKSHA256 | FTPSKSHA384 | PSKSHA384)
+    }
+
+    pub fn is_sae(self) -> bool {
+        use AKM::*;
+        matches!(self, SAE | FTSAE | SAEExt | FTSAEExt)
+    }
+
+    /// Suites that authenticate through an 802.1X server.
+    pub fn is_enterprise(self) -> bool {
+        use AKM::*;
+        matches!(
+            self,
+            IEEE8021X
+                | FT8021X
+                | IEEE8021XSHA256
+                | SuiteB
+                | SuiteB192
+                | FT8021XSHA384
+                | FILSSHA256
+                | FILSSHA384
+                | FTFILSSHA256
+                | FTFILSSHA384
+        )
+    }
+}
+
+/// A cipher or AKM suite selector: an OUI followed by a type defined by the owner of the OUI.
+#[derive(CustomDebug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Suite {
+    #[debug(format = "{:02X?}")]
+    pub oui: [u8; 3],
+    #[debug(format = "{}")]
+    pub suite_type: u8,
+}
+
+impl Suite {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("802.11 RSN suite selector", |i| {
+            let (i, oui) = take(3_usize)(i)?;
+            let (i, suite_type) = le_u8(i)?;
+            let res = Self {
+                oui: [oui[0], oui[1], oui[2]],
+                suite_type,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    /// Parses a suite count followed by that many suites.
+    pub fn parse_list(i: Input) -> ParseResult<Vec<Self>> {
+        let (i, n) = le_u16(i)?;
+        count(Self::parse, n as usize)(i)
+    }
+
+    /// Suites under the IEEE OUI, and the Microsoft OUI of WPA elements, share the same types.
+    fn is_standard(&self) -> bool {
+        self.oui == IEEE_OUI || self.oui == MICROSOFT_OUI
+    }
+
+    pub fn cipher(&self) -> Option<Cipher> {
+        if self.is_standard() {
+            Cipher::try_from(self.suite_type)
+        } else {
+            None
+        }
+    }
+
+    pub fn akm(&self) -> Option<AKM> {
+        if self.is_standard() {
+            AKM::try_from(self.suite_type)
+        } else {
+            None
+        }
+    }
+}
+
+/// The RSN Capabilities field.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct Capabilities(pub u16);
+
+impl fmt::Debug for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{:04X}", self.0)
+    }
+}
+
+impl Capabilities {
+    pub fn preauth(self) -> bool {
+        self.0 & 0x0001 != 0
+    }
+
+    pub fn no_pairwise(self) -> bool {
+        self.0 & 0x0002 != 0
+    }
+
+    /// Number of PTKSA replay counters, as a power of two.
+    pub fn ptksa_replay_counter(self) -> u8 {
+        (self.0 >> 2 & 0b11) as u8
+    }
+
+    /// Number of GTKSA replay counters, as a power of two.
+    pub fn gtksa_replay_counter(self) -> u8 {
+        (self.0 >> 4 & 0b11) as u8
+    }
+
+    /// Management frame protection required.
+    pub fn mfp_required(self) -> bool {
+        self.0 & 0x0040 != 0
+    }
+
+    /// Management frame protection capable.
+    pub fn mfp_capable(self) -> bool {
+        self.0 & 0x0080 != 0
+    }
+
+    pub fn extended_key_id(self) -> bool {
+        self.0 & 0x2000 != 0
+    }
+}
+
+/// The RSN element (ID 48), advertising the ciphers and key management of WPA2 and WPA3 networks.
+/// Every field after the version may be left out, in which case the ones after it are left out too.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct RobustSecurityNetwork {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    #[debug(format = "{}")]
+    pub version: u16,
+    pub group_cipher: Option<Suite>,
+    pub pairwise_ciphers: Vec<Suite>,
+    pub akm_suites: Vec<Suite>,
+    pub capabilities: Option<Capabilities>,
+    pub pmkids: Vec<Blob>,
+    pub group_management_cipher: Option<Suite>,
+}
+
+impl RobustSecurityNetwork {
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Management Frame RSN", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (i, body) = take(len)(i)?;
+
+            let (body, version) = le_u16(body)?;
+            let mut res = Self {
+                common,
+                version,
+                group_cipher: None,
+                pairwise_ciphers: vec![],
+                akm_suites: vec![],
+                capabilities: None,
+                pmkids: vec![],
+                group_management_cipher: None,
+            };
+            if body.is_empty() {
+                return Ok((i, res));
+            }
+            let (body, group_cipher) = Suite::parse(body)?;
+            res.group_cipher = Some(group_cipher);
+            if body.is_empty() {
+                return Ok((i, res));
+            }
+            let (body, pairwise_ciphers) = Suite::parse_list(body)?;
+            res.pairwise_ciphers = pairwise_ciphers;
+            if body.is_empty() {
+                return Ok((i, res));
+            }
+            let (body, akm_suites) = Suite::parse_list(body)?;
+            res.akm_suites = akm_suites;
+            if body.is_empty() {
+                return Ok((i, res));
+            }
+            let (body, capabilities) = map(le_u16, Capabilities)(body)?;
+            res.capabilities = Some(capabilities);
+            if body.is_empty() {
+                return Ok((i, res));
+            }
+            let (body, n) = le_u16(body)?;
+            let (body, pmkids) = count(map(take(16_usize), Blob::new), n as usize)(body)?;
+            res.pmkids = pmkids;
+            if body.is_empty() {
+                return Ok((i, res));
+            }
+            let (_, group_management_cipher) = Suite::parse(body)?;
+            res.group_management_cipher = Some(group_management_cipher);
+
+            Ok((i, res))
+        })(i)
+    }
+
+    pub fn akms(&self) -> impl Iterator<Item = AKM> + '_ {
+        self.akm_suites.iter().filter_map(Suite::akm)
+    }
+
+    pub fn security(&self) -> Security {
+        let (psk, sae) = (self.akms().any(AKM::is_psk), self.akms().any(AKM::is_sae));
+        match (psk, sae) {
+            (true, true) => Security::WPA2WPA3,
+            (false, true) => Security::WPA3SAE,
+            (true, false) => Security::WPA2PSK,
+            _ if self.akms().any(AKM::is_enterprise) => Security::Enterprise,
+            _ if self.akms().any(|a| a == AKM::OWE) => Security::OWE,
+            _ => Security::Unknown,
+        }
+    }
+}
+
+/// How a BSS protects its traffic, as advertised in its beacons and probe responses.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Security {
+    Open,
+    WEP,
+    /// The pre-standard WPA, advertised in a vendor-specific element.
+    WPA,
+    WPA2PSK,
+    WPA3SAE,
+    /// WPA3 transition mode, where both PSK and SAE are accepted.
+    WPA2WPA3,
+    OWE,
+    Enterprise,
+    /// Protected, with key management suites we do not recognize.
+    Unknown,
+}
+
+impl Security {
+    /// Derives the security of a BSS from its capability information and elements.
+    pub fn from_elements(capability_info: &CapabilityInfo, elements: &[Element]) -> Self {
+        let rsn = Element::find_rsn(elements);
+        let wpa = elements.iter().any(|e| match e {
+            Element::VendorSpecific(v) => matches!(v.content, VendorContent::WPA(_)),
+            _ => false,
+        });
+        match rsn {
+            Some(rsn) => rsn.security(),
+            None if wpa => Security::WPA,
+            None if capability_info.privacy == u1::new(1) => Security::WEP,
+            None => Security::Open,
+        }
+    }
+}
+
+impl fmt::Display for Security {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let label = match self {
+            Security::Open => "Open",
+            Security::WEP => "WEP",
+            Security::WPA => "WPA",
+            Security::WPA2PSK => "WPA2-PSK",
+            Security::WPA3SAE => "WPA3-SAE",
+            Security::WPA2WPA3 => "WPA2/WPA3",
+            Security::OWE => "OWE",
+            Security::Enterprise => "Enterprise",
+            Security::Unknown => "Unknown",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assert_rsn_wpa2_psk() {
+        let element: &[u8] = &[
+            0x30, 0x14, 0x01, 0x00, // RSN, version 1
+            0x00, 0x0F, 0xAC, 0x04, // group cipher CCMP
+            0x01, 0x00, 0x00, 0x0F, 0xAC, 0x04, // pairwise CCMP
+            0x01, 0x00, 0x00, 0x0F, 0xAC, 0x02, // AKM PSK
+            0x80, 0x00, // MFP capable
+        ];
+        let (rest, element) = Element::parse(element).unwrap();
+        assert!(rest.is_empty());
+        let rsn = match element {
+            Element::RobustSecurityInfo(rsn) => rsn,
+            e => panic!("expected an RSN element, got {:?}", e),
+        };
+        assert_eq!(rsn.group_cipher.unwrap().cipher(), Some(Cipher::CCMP));
+        assert_eq!(rsn.pairwise_ciphers[0].cipher(), Some(Cipher::CCMP));
+        assert_eq!(rsn.akms().collect::<Vec<_>>(), vec![AKM::PSK]);
+        let capabilities = rsn.capabilities.unwrap();
+        assert!(capabilities.mfp_capable() && !capabilities.mfp_required());
+        assert!(rsn.pmkids.is_empty() && rsn.group_management_cipher.is_none());
+        assert_eq!(rsn.security().to_string(), "WPA2-PSK");
+    }
+}
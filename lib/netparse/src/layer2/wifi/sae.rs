@@ -0,0 +1,210 @@
+//! SAE (IEEE 802.11 12.4), the password authentication of WPA3-Personal and of secure mesh peerings,
+//! carried in the fields that Authentication frames with algorithm 3 add before their elements.
+
+use crate::{
+    core::{blob::Blob, parse::*},
+    layer2::wifi::management::{Element, Status, StatusCode},
+};
+
+use custom_debug_derive::*;
+use nom::{
+    bytes::complete::take, combinator::map, error::context, multi::many0, number::complete::le_u16,
+};
+use serde::{Deserialize, Serialize};
+
+/// The length of the scalar and of the element of a commit in a finite cyclic group
+/// (IEEE 802.11 12.4.4): the length of the order of the group, and of its prime.
+pub fn group_sizes(group: u16) -> Option<(usize, usize)> {
+    match group {
+        // MODP groups, whose elements are integers modulo the prime
+        5 => Some((192, 192)),
+        14 => Some((256, 256)),
+        15 => Some((384, 384)),
+        16 => Some((512, 512)),
+        17 => Some((768, 768)),
+        18 => Some((1024, 1024)),
+        22 => Some((20, 128)),
+        23 => Some((28, 256)),
+        24 => Some((32, 256)),
+        // Elliptic curve groups, whose elements are the two coordinates of a point
+        19 | 28 => Some((32, 64)),
+        20 | 29 => Some((48, 96)),
+        21 => Some((66, 132)),
+        30 => Some((64, 128)),
+        _ => None,
+    }
+}
+
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub enum SAE {
+    /// The first message, committing to a password element in the given group.
+    Commit {
+        #[debug(format = "{}")]
+        group: u16,
+        /// Echoed from an access point that asked for one, to prove the station can receive at its address.
+        anti_clogging_token: Option<Blob>,
+        scalar: Blob,
+        element: Blob,
+    },
+    /// A commit in a group whose sizes are unknown, left undecoded.
+    UnknownGroup {
+        #[debug(format = "{}")]
+        group: u16,
+        data: Blob,
+    },
+    /// The answer of a busy access point to a commit, which the station repeats with the token.
+    AntiCloggingTokenRequest {
+        #[debug(format = "{}")]
+        group: u16,
+        token: Blob,
+    },
+    /// The answer to a commit in a group the peer does not support.
+    RejectedGroup {
+        #[debug(format = "{}")]
+        group: u16,
+    },
+    /// The second message, proving knowledge of the password.
+    Confirm {
+        #[debug(format = "{}")]
+        send_confirm: u16,
+        confirm: Blob,
+    },
+}
+
+impl SAE {
+    /// Parses the SAE fields of an Authentication frame, which depend on its sequence number and status.
+    /// Returns `None` when the frame carries none, as is the case of most failures.
+    pub fn parse<'a>(
+        i: Input<'a>,
+        auth_seq: u16,
+        status: &Status,
+    ) -> ParseResult<'a, Option<Self>> {
+        context("802.11 SAE", move |i| match (auth_seq, status.code) {
+            (1, Some(StatusCode::Success)) | (1, Some(StatusCode::SAEHashToElement)) => {
+                let (i, group) = le_u16(i)?;
+                let (i, res) = Self::parse_commit(i, group)?;
+                Ok((i, Some(res)))
+            }
+            (1, Some(StatusCode::AntiCloggingTokenRequired)) => {
+                let (i, group) = le_u16(i)?;
+                let (i, token) = map(take(i.len()), Blob::new)(i)?;
+                Ok((i, Some(SAE::AntiCloggingTokenRequest { group, token })))
+            }
+            (1, Some(StatusCode::UnsupportedFiniteCyclicGroup)) if i.len() >= 2 => {
+                let (i, group) = le_u16(i)?;
+                Ok((i, Some(SAE::RejectedGroup { group })))
+            }
+            (2, Some(StatusCode::Success)) => {
+                let (i, send_confirm) = le_u16(i)?;
+                let (i, confirm) = map(take(i.len()), Blob::new)(i)?;
+                let res = SAE::Confirm {
+                    send_confirm,
+                    confirm,
+                };
+                Ok((i, Some(res)))
+            }
+            _ => Ok((i, None)),
+        })(i)
+    }
+
+    fn parse_commit(i: Input, group: u16) -> ParseResult<Self> {
+        let (scalar_len, element_len) = match group_sizes(group) {
+            Some(sizes) => sizes,
+            None => {
+                let (i, data) = map(take(i.len()), Blob::new)(i)?;
+                return Ok((i, SAE::UnknownGroup { group, data }));
+            }
+        };
+        // A token has no length of its own: the bytes left over by the scalar and element are either
+        // elements that follow them, or a token that precedes them.
+        let extra = i.len().saturating_sub(scalar_len + element_len);
+        let trailing_elements = match many0(Element::parse)(&i[i.len() - extra..]) {
+            Ok((rest, _)) => rest.is_empty(),
+            Err(_) => false,
+        };
+        let (i, anti_clogging_token) = if extra > 0 && !trailing_elements {
+            let (i, token) = map(take(extra), Blob::new)(i)?;
+            (i, Some(token))
+        } else {
+            (i, None)
+        };
+        let (i, scalar) = map(take(scalar_len), Blob::new)(i)?;
+        let (i, element) = map(take(element_len), Blob::new)(i)?;
+        let res = SAE::Commit {
+            group,
+            anti_clogging_token,
+            scalar,
+            element,
+        };
+        Ok((i, res))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SAE::Commit { .. } | SAE::UnknownGroup { .. } => "SAE commit",
+            SAE::AntiCloggingTokenRequest { .. } => "SAE anti-clogging token request",
+            SAE::RejectedGroup { .. } => "SAE group rejected",
+            SAE::Confirm { .. } => "SAE confirm",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer2::wifi::management::{AuthenticationAlgorithm, AuthenticationFrameBody};
+
+    /// An SAE commit in group 19, with the given bytes between its group and its scalar.
+    fn commit(token: &[u8]) -> Vec<u8> {
+        let mut body = vec![0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 19, 0x00];
+        body.extend_from_slice(token);
+        body.extend_from_slice(&[0x11; 32]);
+        body.extend_from_slice(&[0x22; 64]);
+        body
+    }
+
+    #[test]
+    fn assert_commit() {
+        let (_, body) = AuthenticationFrameBody::parse(&commit(&[])).unwrap();
+        assert_eq!(body.algo_num, Some(AuthenticationAlgorithm::SAE));
+        match body.sae {
+            Some(SAE::Commit {
+                group: 19,
+                anti_clogging_token: None,
+                ref scalar,
+                ref element,
+            }) => {
+                assert_eq!(scalar.0, vec![0x11; 32]);
+                assert_eq!(element.0, vec![0x22; 64]);
+            }
+            ref sae => panic!("expected a commit, got {:?}", sae),
+        }
+        assert!(body.dynamic_fields.is_empty());
+
+        let (_, body) = AuthenticationFrameBody::parse(&commit(&[0xA5; 5])).unwrap();
+        match body.sae {
+            Some(SAE::Commit {
+                anti_clogging_token: Some(ref token),
+                ref scalar,
+                ..
+            }) => {
+                assert_eq!(token.0, vec![0xA5; 5]);
+                assert_eq!(scalar.0, vec![0x11; 32]);
+            }
+            ref sae => panic!("expected a commit with a token, got {:?}", sae),
+        }
+
+        // A password identifier element after the element of the commit
+        let mut frame = commit(&[]);
+        frame.extend_from_slice(&[255, 4, 33, b'b', b'o', b'b']);
+        let (_, body) = AuthenticationFrameBody::parse(&frame).unwrap();
+        assert!(matches!(
+            body.sae,
+            Some(SAE::Commit {
+                anti_clogging_token: None,
+                ..
+            })
+        ));
+        assert_eq!(body.dynamic_fields.len(), 1);
+    }
+}
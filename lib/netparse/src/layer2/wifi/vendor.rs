@@ -0,0 +1,434 @@
+use crate::{
+    core::{blob::Blob, parse::*},
+    layer2::{
+        datalink::Addr,
+        wifi::{management::CommonFieldsElement, rsn::Suite},
+    },
+};
+
+use custom_debug_derive::*;
+use derive_try_from_primitive::*;
+use nom::{
+    bytes::complete::take,
+    combinator::{all_consuming, map},
+    error::context,
+    multi::{count, many0},
+    number::complete::{be_u16, le_u16, le_u8},
+    sequence::tuple,
+};
+use serde::{Deserialize, Serialize};
+
+/// Microsoft, owner of the WPA, WMM and WPS element types.
+pub static MICROSOFT_OUI: [u8; 3] = [0x00, 0x50, 0xF2];
+/// The Wi-Fi Alliance, owner of the P2P element type.
+pub static WFA_OUI: [u8; 3] = [0x50, 0x6F, 0x9A];
+
+fn string(i: Input) -> String {
+    String::from_utf8_lossy(i).to_string()
+}
+
+/// A vendor-specific element (ID 221), identified by the OUI of the vendor and a type byte defined by it.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct VendorSpecific {
+    #[debug(skip)]
+    pub common: CommonFieldsElement,
+    #[debug(format = "{:02X?}")]
+    pub oui: [u8; 3],
+    pub content: VendorContent,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum VendorContent {
+    WPA(WPA),
+    WMM(WMM),
+    WPS(Vec<WPSAttribute>),
+    P2P(Vec<P2PAttribute>),
+    /// The bytes following the OUI of an element we do not decode.
+    Unknown(Blob),
+}
+
+impl VendorSpecific {
+    pub fn parse(i: Input, id: u8, len: u8) -> ParseResult<Self> {
+        context("802.11 Management Frame Vendor Specific", |i| {
+            let common = CommonFieldsElement { id, len };
+            let (i, body) = take(len)(i)?;
+            let (body, oui) = map(take(3_usize), |o: Input| [o[0], o[1], o[2]])(body)?;
+            let typ = body.first().copied();
+            let decoded = match typ {
+                Some(1) if oui == MICROSOFT_OUI => map(WPA::parse, VendorContent::WPA)(&body[1..]),
+                Some(2) if oui == MICROSOFT_OUI => map(WMM::parse, VendorContent::WMM)(&body[1..]),
+                Some(4) if oui == MICROSOFT_OUI => map(
+                    all_consuming(many0(WPSAttribute::parse)),
+                    VendorContent::WPS,
+                )(&body[1..]),
+                Some(9) if oui == WFA_OUI => map(
+                    all_consuming(many0(P2PAttribute::parse)),
+                    VendorContent::P2P,
+                )(&body[1..]),
+                _ => Ok((body, VendorContent::Unknown(Blob::new(body)))),
+            };
+            // A vendor element we fail to decode is still kept, as raw bytes.
+            let content = match decoded {
+                Ok((_, content)) => content,
+                Err(_) => VendorContent::Unknown(Blob::new(body)),
+            };
+            let res = Self {
+                common,
+                oui,
+                content,
+            };
+
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// The pre-standard WPA element, laid out like the start of the RSN element with Microsoft suite selectors.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct WPA {
+    #[debug(format = "{}")]
+    pub version: u16,
+    pub group_cipher: Option<Suite>,
+    pub pairwise_ciphers: Vec<Suite>,
+    pub akm_suites: Vec<Suite>,
+}
+
+impl WPA {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("WPA element", |i| {
+            let (i, version) = le_u16(i)?;
+            let mut res = Self {
+                version,
+                group_cipher: None,
+                pairwise_ciphers: vec![],
+                akm_suites: vec![],
+            };
+            if i.is_empty() {
+                return Ok((i, res));
+            }
+            let (i, group_cipher) = Suite::parse(i)?;
+            res.group_cipher = Some(group_cipher);
+            if i.is_empty() {
+                return Ok((i, res));
+            }
+            let (i, pairwise_ciphers) = Suite::parse_list(i)?;
+            res.pairwise_ciphers = pairwise_ciphers;
+            if i.is_empty() {
+                return Ok((i, res));
+            }
+            let (i, akm_suites) = Suite::parse_list(i)?;
+            res.akm_suites = akm_suites;
+
+            Ok((i, res))
+        })(i)
+    }
+}
+
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum AccessCategory {
+    BestEffort = 0,
+    Background = 1,
+    Video = 2,
+    Voice = 3,
+}
+
+/// EDCA parameters of one access category.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct ACParameters {
+    pub aci: Option<AccessCategory>,
+    /// Admission control is mandatory.
+    pub acm: bool,
+    #[debug(format = "{}")]
+    pub aifsn: u8,
+    #[debug(format = "{}")]
+    pub ecw_min: u8,
+    #[debug(format = "{}")]
+    pub ecw_max: u8,
+    /// In units of 32 microseconds.
+    #[debug(format = "{}")]
+    pub txop_limit: u16,
+}
+
+impl ACParameters {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("WMM AC parameters", |i| {
+            let (i, (aci_aifsn, ecw, txop_limit)) = tuple((le_u8, le_u8, le_u16))(i)?;
+            let res = Self {
+                aci: AccessCategory::try_from(aci_aifsn >> 5 & 0b11),
+                acm: aci_aifsn & 0x10 != 0,
+                aifsn: aci_aifsn & 0x0F,
+                ecw_min: ecw & 0x0F,
+                ecw_max: ecw >> 4,
+                txop_limit,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    pub fn cw_min(&self) -> u16 {
+        (1 << self.ecw_min) - 1
+    }
+
+    pub fn cw_max(&self) -> u16 {
+        (1 << self.ecw_max) - 1
+    }
+
+    pub fn txop_limit_us(&self) -> u32 {
+        u32::from(self.txop_limit) * 32
+    }
+}
+
+/// A WMM (WME) information or parameter element.
+#[derive(CustomDebug, Serialize, Deserialize)]
+pub struct WMM {
+    /// 0 for the information element, 1 for the parameter element.
+    #[debug(format = "{}")]
+    pub subtype: u8,
+    #[debug(format = "{}")]
+    pub version: u8,
+    #[debug(format = "0x{:02X}")]
+    pub qos_info: u8,
+    /// The parameters of the four access categories, only in the parameter element.
+    pub ac_parameters: Vec<ACParameters>,
+}
+
+impl WMM {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("WMM element", |i| {
+            let (i, (subtype, version, qos_info)) = tuple((le_u8, le_u8, le_u8))(i)?;
+            let (i, ac_parameters) = match subtype {
+                1 => {
+                    let (i, _reserved) = le_u8(i)?;
+                    count(ACParameters::parse, 4)(i)?
+                }
+                _ => (i, vec![]),
+            };
+            let res = Self {
+                subtype,
+                version,
+                qos_info,
+                ac_parameters,
+            };
+            Ok((i, res))
+        })(i)
+    }
+
+    /// U-APSD is supported, as advertised by an access point.
+    pub fn uapsd(&self) -> bool {
+        self.qos_info & 0x80 != 0
+    }
+}
+
+#[derive(Debug, TryFromPrimitive, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum WPSState {
+    NotConfigured = 1,
+    Configured = 2,
+}
+
+/// A Wi-Fi Protected Setup attribute. Unlike elements, these use big endian 16 bit types and lengths.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum WPSAttribute {
+    Version(u8),
+    State(Option<WPSState>),
+    DeviceName(String),
+    Manufacturer(String),
+    ModelName(String),
+    ModelNumber(String),
+    SerialNumber(String),
+    /// Bitmap of the methods that can be used to configure the device: PIN, push button, NFC, etc.
+    ConfigMethods(u16),
+    SelectedRegistrar(bool),
+    DevicePasswordID(u16),
+    UUID(Blob),
+    RFBands(u8),
+    Other {
+        typ: u16,
+        data: Blob,
+    },
+}
+
+impl WPSAttribute {
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("WPS attribute", |i| {
+            let (i, (typ, len)) = tuple((be_u16, be_u16))(i)?;
+            let (i, data) = take(len)(i)?;
+            let first = data.first().copied().unwrap_or(0);
+            let be16 = || {
+                data.get(..2)
+                    .map_or(0, |b| u16::from_be_bytes([b[0], b[1]]))
+            };
+            let res = match typ {
+                0x104A => WPSAttribute::Version(first),
+                0x1044 => WPSAttribute::State(WPSState::try_from(first)),
+                0x1011 => WPSAttribute::DeviceName(string(data)),
+                0x1021 => WPSAttribute::Manufacturer(string(data)),
+                0x1023 => WPSAttribute::ModelName(string(data)),
+                0x1024 => WPSAttribute::ModelNumber(string(data)),
+                0x1042 => WPSAttribute::SerialNumber(string(data)),
+                0x1008 => WPSAttribute::ConfigMethods(be16()),
+                0x1041 => WPSAttribute::SelectedRegistrar(first != 0),
+                0x1012 => WPSAttribute::DevicePasswordID(be16()),
+                0x1047 => WPSAttribute::UUID(Blob::new(data)),
+                0x103C => WPSAttribute::RFBands(first),
+                _ => WPSAttribute::Other {
+                    typ,
+                    data: Blob::new(data),
+                },
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+/// A Wi-Fi Direct (P2P) attribute. The length is little endian, unlike WPS.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum P2PAttribute {
+    Status(u8),
+    Capability {
+        device: u8,
+        group: u8,
+    },
+    DeviceID(Addr),
+    ListenChannel {
+        operating_class: u8,
+        channel: u8,
+    },
+    OperatingChannel {
+        operating_class: u8,
+        channel: u8,
+    },
+    DeviceInfo {
+        addr: Addr,
+        config_methods: u16,
+        primary_device_type: Blob,
+        device_name: String,
+    },
+    Other {
+        id: u8,
+        data: Blob,
+    },
+}
+
+impl P2PAttribute {
+    /// Device Info: the device address, WPS config methods, device types and a WPS Device Name attribute.
+    fn parse_device_info(i: Input) -> ParseResult<Self> {
+        let (i, (addr, config_methods, primary_device_type, n)) =
+            tuple((Addr::parse, be_u16, map(take(8_usize), Blob::new), le_u8))(i)?;
+        let (i, _secondary_device_types) = take(8 * n as usize)(i)?;
+        let (i, device_name) = match WPSAttribute::parse(i)? {
+            (i, WPSAttribute::DeviceName(name)) => (i, name),
+            (i, _) => (i, String::new()),
+        };
+        let res = P2PAttribute::DeviceInfo {
+            addr,
+            config_methods,
+            primary_device_type,
+            device_name,
+        };
+        Ok((i, res))
+    }
+
+    /// Listen and operating channels: a country string, an operating class and a channel number.
+    fn parse_channel(i: Input) -> ParseResult<(u8, u8)> {
+        let (i, (_country, operating_class, channel)) = tuple((take(3_usize), le_u8, le_u8))(i)?;
+        Ok((i, (operating_class, channel)))
+    }
+
+    pub fn parse(i: Input) -> ParseResult<Self> {
+        context("P2P attribute", |i| {
+            let (i, (id, len)) = tuple((le_u8, le_u16))(i)?;
+            let (i, data) = take(len)(i)?;
+            let decoded = match id {
+                0 => map(le_u8, P2PAttribute::Status)(data),
+                2 => map(tuple((le_u8, le_u8)), |(device, group)| {
+                    P2PAttribute::Capability { device, group }
+                })(data),
+                3 => map(Addr::parse, P2PAttribute::DeviceID)(data),
+                6 => map(Self::parse_channel, |(operating_class, channel)| {
+                    P2PAttribute::ListenChannel {
+                        operating_class,
+                        channel,
+                    }
+                })(data),
+                13 => Self::parse_device_info(data),
+                17 => map(Self::parse_channel, |(operating_class, channel)| {
+                    P2PAttribute::OperatingChannel {
+                        operating_class,
+                        channel,
+                    }
+                })(data),
+                _ => Err(nom::Err::Error(Error::malformed(data))),
+            };
+            let res = match decoded {
+                Ok((_, attribute)) => attribute,
+                Err(_) => P2PAttribute::Other {
+                    id,
+                    data: Blob::new(data),
+                },
+            };
+            Ok((i, res))
+        })(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer2::wifi::{
+        management::Element,
+        rsn::{Cipher, AKM},
+    };
+
+    #[test]
+    fn assert_vendor_elements() {
+        let elements: &[u8] = &[
+            // WPA1, TKIP/TKIP/PSK
+            221, 22, 0x00, 0x50, 0xF2, 0x01, 0x01, 0x00, 0x00, 0x50, 0xF2, 0x02, 0x01, 0x00, 0x00,
+            0x50, 0xF2, 0x02, 0x01, 0x00, 0x00, 0x50, 0xF2, 0x02,
+            // WMM parameter element, best effort only shown in detail
+            221, 24, 0x00, 0x50, 0xF2, 0x02, 0x01, 0x01, 0x80, 0x00, 0x03, 0xA4, 0x00, 0x00, 0x27,
+            0xA4, 0x00, 0x00, 0x42, 0x43, 0x5E, 0x00, 0x62, 0x32, 0x2F, 0x00,
+            // WPS: configured, device name "AP"
+            221, 15, 0x00, 0x50, 0xF2, 0x04, 0x10, 0x44, 0x00, 0x01, 0x02, 0x10, 0x11, 0x00, 0x02,
+            b'A', b'P', // Someone else's
+            221, 5, 0x00, 0x10, 0x18, 0x02, 0x00,
+        ];
+        let (_, elements) = many0(Element::parse)(elements).unwrap();
+        let contents: Vec<_> = elements
+            .iter()
+            .map(|e| match e {
+                Element::VendorSpecific(v) => &v.content,
+                e => panic!("expected a vendor element, got {:?}", e),
+            })
+            .collect();
+        match contents[0] {
+            VendorContent::WPA(wpa) => {
+                assert_eq!(wpa.group_cipher.unwrap().cipher(), Some(Cipher::TKIP));
+                assert_eq!(wpa.akm_suites[0].akm(), Some(AKM::PSK));
+            }
+            c => panic!("expected WPA, got {:?}", c),
+        }
+        match contents[1] {
+            VendorContent::WMM(wmm) => {
+                let be = &wmm.ac_parameters[0];
+                assert_eq!(be.aci, Some(AccessCategory::BestEffort));
+                assert_eq!((be.aifsn, be.cw_min(), be.cw_max()), (3, 15, 1023));
+                assert_eq!(wmm.ac_parameters[3].txop_limit_us(), 1504);
+            }
+            c => panic!("expected WMM, got {:?}", c),
+        }
+        match contents[2] {
+            VendorContent::WPS(attributes) => {
+                assert!(matches!(
+                    attributes[0],
+                    WPSAttribute::State(Some(WPSState::Configured))
+                ));
+                assert!(matches!(attributes[1], WPSAttribute::DeviceName(ref n) if n == "AP"));
+            }
+            c => panic!("expected WPS, got {:?}", c),
+        }
+        assert!(matches!(contents[3], VendorContent::Unknown(ref b) if b.0 == vec![0x02, 0x00]));
+    }
+}
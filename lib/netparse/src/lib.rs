@@ -2,13 +2,31 @@
 pub mod layer2 {
     pub mod arp;
     pub mod wifi {
+        pub mod action;
+        pub mod ccmp;
+        pub mod control;
         pub mod data;
+        pub mod decrypt;
+        pub mod defrag;
         pub mod dot11;
         pub mod management;
+        pub mod mesh;
+        pub mod phy;
         pub mod radiotap;
+        pub mod rsn;
+        pub mod sae;
+        pub mod tkip;
+        pub mod vendor;
     }
     pub mod datalink;
+    pub mod eap;
+    pub mod eapol;
     pub mod ethernet;
+    pub mod loopback;
+    pub mod ppp;
+    pub mod sll;
+    pub mod sll2;
+    pub mod stp;
 }
 
 pub mod layer3 {
@@ -22,9 +40,19 @@ pub mod layer3 {
     }
 }
 
+pub mod filter {
+    pub mod expr;
+    pub mod field;
+}
+
 pub mod core {
+    pub mod aes;
     pub mod blob;
+    pub mod crc;
     pub mod hex_slice;
+    pub mod md5;
     pub mod parse;
+    pub mod rc4;
+    pub mod sha1;
     pub mod ux;
 }